@@ -21,6 +21,8 @@ mod objective_target;
 mod objective_timeframe;
 mod objective_variable;
 mod pipeline;
+mod pipeline_backfill;
+mod pipeline_dead_letter;
 mod pipeline_monitor_log;
 mod pipeline_trigger_data;
 mod report;
@@ -56,6 +58,8 @@ pub use objective_target::*;
 pub use objective_timeframe::*;
 pub use objective_variable::*;
 pub use pipeline::*;
+pub use pipeline_backfill::*;
+pub use pipeline_dead_letter::*;
 pub use pipeline_monitor_log::*;
 pub use pipeline_trigger_data::*;
 pub use report::*;
@@ -476,7 +476,7 @@ pub struct PipelineStage {
     pub on: Option<ParameterJoint>,
 }
 
-#[derive(Display, Serde, PartialEq, Debug, StrEnum)]
+#[derive(Display, Serde, PartialEq, Debug, StrEnum, Clone)]
 pub enum PipelineTriggerType {
     Insert,
     Merge,
@@ -502,6 +502,18 @@ impl PipelineTriggerType {
     }
 }
 
+/// on failure, pipeline is retried with backoff, and dead-lettered when all retries failed.
+#[adapt_model(storable)]
+pub struct PipelineFailurePolicy {
+    /// count of retries after the first failure, 0 when not defined,
+    /// ignored when unit of work is not used
+    pub retries: Option<u32>,
+    /// wait before the first retry, doubled for each next retry
+    pub backoff_in_mills: Option<u32>,
+    /// persist the failed execution as dead letter when all retries failed
+    pub dead_letter: Option<bool>,
+}
+
 pub type PipelineId = String;
 
 #[adapt_model(opt_lock, tenant_based)]
@@ -515,4 +527,7 @@ pub struct Pipeline {
     pub validated: Option<bool>,
     pub conditional: Option<bool>,
     pub on: Option<ParameterJoint>,
+    pub failure_policy: Option<PipelineFailurePolicy>,
+    /// time budget of one execution, no limit when not defined
+    pub timeout_in_mills: Option<u32>,
 }
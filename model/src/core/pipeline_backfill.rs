@@ -0,0 +1,67 @@
+use crate::{BaseDataModel, ModelErrorCode, ParameterJoint, PipelineId, Storable, TopicDataId};
+use chrono::NaiveDateTime;
+use elf_base::serde::option_naive_datetime;
+use elf_base::{ErrorCode, StdR};
+use elf_model_marco::{adapt_model, Display, Serde, StrEnum};
+
+pub type PipelineBackfillId = String;
+
+#[derive(Display, Serde, Debug, StrEnum)]
+pub enum PipelineBackfillTimeColumn {
+    InsertTime,
+    UpdateTime,
+}
+
+/// run pipeline over existing rows of its topic, each row is fed as an insert trigger,
+/// and row is not saved again.
+#[adapt_model(storable)]
+pub struct PipelineBackfill {
+    /// backfill is resumed from its checkpoint, when run again with same id
+    pub backfill_id: Option<PipelineBackfillId>,
+    pub pipeline_id: Option<PipelineId>,
+    /// filter of rows, on factors of pipeline topic
+    pub filter: Option<ParameterJoint>,
+    /// rows are filtered by insert time or update time, when start time or end time given
+    pub time_column: Option<PipelineBackfillTimeColumn>,
+    /// inclusive
+    #[serde(with = "option_naive_datetime")]
+    pub start_time: Option<NaiveDateTime>,
+    /// inclusive
+    #[serde(with = "option_naive_datetime")]
+    pub end_time: Option<NaiveDateTime>,
+    /// count of rows scanned in one round, checkpoint is saved after each round
+    pub page_size: Option<u32>,
+}
+
+/// progress of backfill, saved after each round
+#[adapt_model(storable)]
+pub struct PipelineBackfillCheckpoint {
+    pub backfill_id: Option<PipelineBackfillId>,
+    pub pipeline_id: Option<PipelineId>,
+    /// fingerprint of filter, time column and time range,
+    /// backfill resumes only when they are not changed
+    pub fingerprint: Option<String>,
+    /// id of last processed row, backfill resumes after it
+    pub last_data_id: Option<TopicDataId>,
+    /// count of processed rows, including the failed ones
+    pub processed: Option<u32>,
+    pub failed: Option<u32>,
+    pub finished: Option<bool>,
+    #[serde(with = "option_naive_datetime")]
+    pub last_modified_at: Option<NaiveDateTime>,
+}
+
+impl Clone for PipelineBackfillCheckpoint {
+    fn clone(&self) -> Self {
+        PipelineBackfillCheckpoint {
+            backfill_id: self.backfill_id.clone(),
+            pipeline_id: self.pipeline_id.clone(),
+            fingerprint: self.fingerprint.clone(),
+            last_data_id: self.last_data_id.clone(),
+            processed: self.processed,
+            failed: self.failed,
+            finished: self.finished,
+            last_modified_at: self.last_modified_at,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{
+    BaseDataModel, PipelineId, PipelineTriggerTraceId, PipelineTriggerType, Storable, TenantId,
+    TopicData, TopicDataId, TopicId,
+};
+use chrono::NaiveDateTime;
+use elf_base::serde::option_naive_datetime;
+use elf_model_marco::adapt_model;
+
+pub type PipelineDeadLetterId = String;
+
+/// failed pipeline execution, which is persisted after all retries failed.
+/// old value and new value are the snapshot of trigger data, to replay the execution.
+#[adapt_model(storable)]
+pub struct PipelineDeadLetter {
+    pub dead_letter_id: Option<PipelineDeadLetterId>,
+    pub trace_id: Option<PipelineTriggerTraceId>,
+    pub pipeline_id: Option<PipelineId>,
+    pub topic_id: Option<TopicId>,
+    pub data_id: Option<TopicDataId>,
+    pub trigger_type: Option<PipelineTriggerType>,
+    pub old_value: Option<TopicData>,
+    pub new_value: Option<TopicData>,
+    /// error of the last attempt
+    pub error: Option<String>,
+    /// count of attempts, including the first run and retries
+    pub attempts: Option<u32>,
+    #[serde(with = "option_naive_datetime")]
+    pub created_at: Option<NaiveDateTime>,
+    pub tenant_id: Option<TenantId>,
+}
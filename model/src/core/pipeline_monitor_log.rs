@@ -1,12 +1,14 @@
 use crate::{
-	BaseDataModel, ModelErrorCode, Pageable, PipelineActionId, PipelineActionType, PipelineId,
-	PipelineStageId, PipelineTriggerTraceId, PipelineUnitId, Storable, TenantId, TopicDataId,
-	TopicId,
+	BaseDataModel, FactorId, ModelErrorCode, Pageable, ParameterExpressionOperator,
+	ParameterJointType, PipelineAction, PipelineActionId, PipelineActionType, PipelineId,
+	PipelineStageId, PipelineTriggerTraceId, PipelineUnitId, Storable, TenantId, TopicData,
+	TopicDataId, TopicDataValue, TopicId,
 };
 use chrono::NaiveDateTime;
 use elf_base::serde::option_naive_datetime;
 use elf_base::{ErrorCode, StdR};
 use elf_model_marco::{adapt_model, Display, Serde, StrEnum};
+use serde::{Deserialize, Serialize};
 
 #[derive(Display, Serde, StrEnum)]
 #[pattern = "upper-case"]
@@ -17,11 +19,66 @@ pub enum MonitorLogStatus {
     IGNORED,
     /// exception occurred
     ERROR,
+    /// time budget of pipeline or trace exceeded
+    TIMEOUT,
 }
 
-/// TODO Any needs to be changed to some struct, according to where it is
-///  there sure thing is, it is not a [String]
-pub type NotKnownYetDataStruct = String;
+/// operand of evaluated expression.
+/// factor is given when operand is a factor of the topic which is found by,
+/// otherwise value is the resolved value.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorLogParameterOperand {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factor_id: Option<FactorId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factor_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<TopicDataValue>,
+}
+
+impl MonitorLogParameterOperand {
+    pub fn factor(factor_id: FactorId, factor_name: String) -> Self {
+        Self {
+            factor_id: Some(factor_id),
+            factor_name: Some(factor_name),
+            value: None,
+        }
+    }
+
+    pub fn value(value: TopicDataValue) -> Self {
+        Self {
+            factor_id: None,
+            factor_name: None,
+            value: Some(value),
+        }
+    }
+}
+
+/// snapshot of evaluated expression, right is none when operator is one of empty and not-empty
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorLogParameterExpression {
+    pub left: MonitorLogParameterOperand,
+    pub operator: ParameterExpressionOperator,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<MonitorLogParameterOperand>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum MonitorLogParameterCondition {
+    Expression(MonitorLogParameterExpression),
+    Joint(MonitorLogParameterJoint),
+}
+
+/// snapshot of evaluated parameter joint, with resolved values
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorLogParameterJoint {
+    pub joint_type: ParameterJointType,
+    pub filters: Vec<MonitorLogParameterCondition>,
+}
 
 #[adapt_model(storable)]
 pub struct StandardMonitorLog {
@@ -41,7 +98,7 @@ pub struct ConditionalMonitorLog {
     /// result of prerequisite, True when it is not defined
     pub prerequisite: Option<bool>,
     /// definition of prerequisite
-    pub prerequisite_defined_as: Option<NotKnownYetDataStruct>,
+    pub prerequisite_defined_as: Option<MonitorLogParameterJoint>,
 }
 
 pub type MonitorLogActionId = String;
@@ -50,13 +107,22 @@ pub type MonitorLogActionId = String;
 pub struct MonitorLogAction {
     //(StandardMonitorLog):
     pub uid: Option<MonitorLogActionId>,
+    pub status: Option<MonitorLogStatus>,
+    #[serde(with = "option_naive_datetime")]
+    pub start_time: Option<NaiveDateTime>,
+    pub spent_in_mills: Option<u32>,
+    pub error: Option<String>,
     pub action_id: Option<PipelineActionId>,
     pub r#type: Option<PipelineActionType>,
     pub insert_count: Option<u32>,
     pub update_count: Option<u32>,
     pub delete_count: Option<u32>,
+    /// count of version conflicts on merge, each conflict causes a retry
+    pub conflict_count: Option<u32>,
     /// definition of action
-    pub defined_as: Option<NotKnownYetDataStruct>,
+    pub defined_as: Option<PipelineAction>,
+    /// runtime describing of find by, for read, write and delete actions
+    pub find_by: Option<MonitorLogParameterJoint>,
     /// touched value,
     /// for deletion, update and insert, always be list of dict
     /// for read-exists, bool,
@@ -64,14 +130,14 @@ pub struct MonitorLogAction {
     /// for read-factor, arithmetic, Decimal
     /// for read-row, dict
     /// for read-rows, list of dict
-    pub touched: Option<NotKnownYetDataStruct>,
+    pub touched: Option<TopicDataValue>,
 }
 
 #[adapt_model(storable)]
 pub struct MonitorLogFindByAction {
     //(MonitorLogAction):
     /// runtime describing of find by
-    pub find_by: Option<NotKnownYetDataStruct>,
+    pub find_by: Option<MonitorLogParameterJoint>,
 }
 
 #[adapt_model(storable)]
@@ -114,9 +180,16 @@ pub struct MonitorWriteToExternalAction {
 pub struct MonitorLogUnit {
     //(ConditionalMonitorLog):
     pub unit_id: Option<PipelineUnitId>,
+    pub status: Option<MonitorLogStatus>,
+    #[serde(with = "option_naive_datetime")]
+    pub start_time: Option<NaiveDateTime>,
+    pub spent_in_mills: Option<u32>,
+    pub error: Option<String>,
+    pub prerequisite: Option<bool>,
+    pub prerequisite_defined_as: Option<MonitorLogParameterJoint>,
     pub name: Option<String>,
     pub loop_variable_name: Option<String>,
-    pub loop_variable_value: Option<NotKnownYetDataStruct>,
+    pub loop_variable_value: Option<TopicDataValue>,
     pub actions: Option<Vec<MonitorLogAction>>,
 }
 
@@ -124,6 +197,13 @@ pub struct MonitorLogUnit {
 pub struct MonitorLogStage {
     //(ConditionalMonitorLog):
     pub stage_id: Option<PipelineStageId>,
+    pub status: Option<MonitorLogStatus>,
+    #[serde(with = "option_naive_datetime")]
+    pub start_time: Option<NaiveDateTime>,
+    pub spent_in_mills: Option<u32>,
+    pub error: Option<String>,
+    pub prerequisite: Option<bool>,
+    pub prerequisite_defined_as: Option<MonitorLogParameterJoint>,
     pub name: Option<String>,
     pub units: Option<Vec<MonitorLogUnit>>,
 }
@@ -134,12 +214,21 @@ pub type PipelineMonitorLogId = String;
 pub struct PipelineMonitorLog {
     //(ConditionalMonitorLog):
     pub uid: Option<PipelineMonitorLogId>,
+    /// uid of monitor log of the first attempt when retried, none for the first attempt
+    pub retry_of: Option<PipelineMonitorLogId>,
+    pub status: Option<MonitorLogStatus>,
+    #[serde(with = "option_naive_datetime")]
+    pub start_time: Option<NaiveDateTime>,
+    pub spent_in_mills: Option<u32>,
+    pub error: Option<String>,
+    pub prerequisite: Option<bool>,
+    pub prerequisite_defined_as: Option<MonitorLogParameterJoint>,
     pub trace_id: Option<PipelineTriggerTraceId>,
     pub pipeline_id: Option<PipelineId>,
     pub topic_id: Option<TopicId>,
     pub data_id: Option<TopicDataId>,
-    pub old_value: Option<NotKnownYetDataStruct>,
-    pub new_value: Option<NotKnownYetDataStruct>,
+    pub old_value: Option<TopicData>,
+    pub new_value: Option<TopicData>,
     pub stages: Option<Vec<MonitorLogStage>>,
 }
 
@@ -174,3 +263,71 @@ impl Pageable for PipelineMonitorLogCriteria {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        MonitorLogAction, MonitorLogParameterCondition, MonitorLogParameterExpression,
+        MonitorLogParameterJoint, MonitorLogParameterOperand, MonitorLogStage, MonitorLogUnit,
+        ParameterExpressionOperator, ParameterJointType, PipelineMonitorLog, TopicDataValue,
+    };
+    use chrono::NaiveDateTime;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_round_trip() {
+        let now = NaiveDateTime::default();
+        let find_by = MonitorLogParameterJoint {
+            joint_type: ParameterJointType::And,
+            filters: vec![MonitorLogParameterCondition::Expression(
+                MonitorLogParameterExpression {
+                    left: MonitorLogParameterOperand::factor("f1".to_string(), "name".to_string()),
+                    operator: ParameterExpressionOperator::Equals,
+                    right: Some(MonitorLogParameterOperand::value(TopicDataValue::Str(
+                        "x".to_string(),
+                    ))),
+                },
+            )],
+        };
+        let row = HashMap::from([("name".to_string(), TopicDataValue::Str("x".to_string()))]);
+        let log = PipelineMonitorLog::new()
+            .uid("1".to_string())
+            .start_time(now)
+            .new_value(row.clone())
+            .stages(vec![MonitorLogStage::new().start_time(now).units(vec![
+                MonitorLogUnit::new()
+                    .start_time(now)
+                    .loop_variable_value(TopicDataValue::Bool(true))
+                    .actions(vec![
+                        MonitorLogAction::new()
+                            .start_time(now)
+                            .find_by(find_by)
+                            .touched(TopicDataValue::Vec(vec![TopicDataValue::Map(row)])),
+                    ]),
+            ])]);
+
+        let json = serde_json::to_string(&log).unwrap();
+        assert!(json.contains(r#""newValue":{"name":"x"}"#));
+        assert!(json.contains(
+            r#""findBy":{"jointType":"and","filters":[{"left":{"factorId":"f1","factorName":"name"},"operator":"equals","right":{"value":"x"}}]}"#
+        ));
+
+        let log: PipelineMonitorLog = serde_json::from_str(&json).unwrap();
+        let stages = log.stages.unwrap();
+        let units = stages[0].units.as_ref().unwrap();
+        assert!(matches!(
+            units[0].loop_variable_value,
+            Some(TopicDataValue::Bool(true))
+        ));
+        let actions = units[0].actions.as_ref().unwrap();
+        let find_by = actions[0].find_by.as_ref().unwrap();
+        assert!(matches!(
+            &find_by.filters[0],
+            MonitorLogParameterCondition::Expression(e) if e.left.factor_id.as_deref() == Some("f1")
+        ));
+        assert!(matches!(
+            &actions[0].touched,
+            Some(TopicDataValue::Vec(rows)) if matches!(&rows[0], TopicDataValue::Map(_))
+        ));
+    }
+}
@@ -18,6 +18,18 @@ pub struct PipelineTriggerData {
     pub trace_id: Option<PipelineTriggerTraceId>,
 }
 
+/// trigger data of many rows, all rows are triggered on same topic by same trigger type
+#[adapt_model(storable)]
+pub struct PipelineTriggerBatchData {
+    /// topic name
+    pub code: Option<TopicCode>,
+    /// rows of current data
+    pub data: Option<Vec<TopicData>>,
+    pub trigger_type: Option<PipelineTriggerType>,
+    /// pass tenant id when use super admin
+    pub tenant_id: Option<TenantId>,
+}
+
 #[adapt_model(storable)]
 pub struct PipelineTriggerDataWithPAT {
     pub pat: Option<String>,
@@ -3,7 +3,7 @@ use elf_base::{ErrorCode, StdR};
 use elf_model_marco::{adapt_model, Display, Serde, StrEnum, VariousStructTypes};
 use serde::{Deserialize, Serialize};
 
-#[derive(Display, Serde, Debug, StrEnum)]
+#[derive(Display, Serde, Debug, StrEnum, Clone)]
 pub enum ParameterExpressionOperator {
     Empty,
     NotEmpty,
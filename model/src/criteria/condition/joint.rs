@@ -2,7 +2,7 @@ use crate::{BaseDataModel, ModelErrorCode, ParameterCondition, Storable};
 use elf_base::{ErrorCode, StdR};
 use elf_model_marco::{adapt_model, Display, Serde, StrEnum};
 
-#[derive(Display, Serde, Debug, StrEnum)]
+#[derive(Display, Serde, Debug, StrEnum, Clone)]
 pub enum ParameterJointType {
     And,
     Or,
@@ -17,8 +17,11 @@ use elf_model_marco::{adapt_model, Display, Serde, StrEnum, VPF};
 #[pattern = "ampersand-prefix"]
 pub enum VariablePredefineFunctions {
     // Sequence functions
-    /// get next sequence number, [only in-memory]. \[&nextSeq], \[&nextSeq()]
-    #[restrict(context = false, max_param_count = 0)]
+    /// get next sequence number, [only in-memory]. \[&nextSeq], \[&nextSeq()], \[&nextSeq(name)]
+    ///
+    /// - no parameter: next id of global id generator, as a string,
+    /// - [name]: string. next value of given named sequence, counted per tenant.
+    #[restrict(context = false, max_param_count = 1)]
     NextSeq,
     // Aggregation functions
     /// count of vec or map, [only in-memory]. [x.&count], [x.&count()], \[&count(x)]
@@ -541,7 +544,7 @@ impl VariablePredefineFunctions {
         let any_param_true = true;
 
         match self {
-            Self::NextSeq => no_param_false,
+            Self::NextSeq => single_param_false,
             Self::Count => no_param_false,
             Self::Length => no_param_false,
             Self::Len => no_param_false,
@@ -616,7 +619,7 @@ impl VariablePredefineFunctions {
         let any_param_true = true;
 
         match self {
-            Self::NextSeq => no_param_false,
+            Self::NextSeq => single_param_false,
             Self::Count => no_param_false,
             Self::Length => no_param_false,
             Self::Len => no_param_false,
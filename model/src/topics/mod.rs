@@ -5,6 +5,8 @@ mod pipeline_monitor;
 mod pipeline_monitor_error_log;
 mod query_performance;
 mod query_performance_log;
+mod raw_pipeline_backfill_checkpoint;
+mod raw_pipeline_dead_letter;
 mod raw_pipeline_monitor_log;
 
 pub use dqc::*;
@@ -14,4 +16,6 @@ pub use pipeline_monitor::*;
 pub use pipeline_monitor_error_log::*;
 pub use query_performance::*;
 pub use query_performance_log::*;
+pub use raw_pipeline_backfill_checkpoint::*;
+pub use raw_pipeline_dead_letter::*;
 pub use raw_pipeline_monitor_log::*;
@@ -0,0 +1,55 @@
+use crate::{Factor, FactorIndexGroup, FactorType, Topic, TopicKind, TopicType};
+
+pub fn ask_raw_pipeline_backfill_checkpoint_topic() -> Topic {
+    let mut factors = Vec::new();
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpbc-f-1"))
+            .name(String::from("backfillId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::UniqueIndex2)
+            .precision(String::from("50")),
+    );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpbc-f-2"))
+            .name(String::from("pipelineId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index1)
+            .precision(String::from("50")),
+    );
+
+    fn create_factor(
+        factor_id: &'static str,
+        name: &'static str,
+        factor_type: FactorType,
+    ) -> Factor {
+        Factor::new()
+            .factor_id(String::from(factor_id))
+            .name(String::from(name))
+            .r#type(factor_type)
+    }
+
+    factors.push(create_factor("rpbc-f-3", "lastDataId", FactorType::Text));
+    factors.push(create_factor("rpbc-f-4", "processed", FactorType::Unsigned));
+    factors.push(create_factor("rpbc-f-5", "failed", FactorType::Unsigned));
+    factors.push(create_factor("rpbc-f-6", "finished", FactorType::Boolean));
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpbc-f-7"))
+            .name(String::from("lastModifiedAt"))
+            .r#type(FactorType::FullDatetime)
+            .flatten(true),
+    );
+    factors.push(create_factor("rpbc-f-8", "fingerprint", FactorType::Text));
+
+    Topic::new()
+        .name(String::from("raw_pipeline_backfill_checkpoint"))
+        .kind(TopicKind::System)
+        .r#type(TopicType::Raw)
+        .factors(factors)
+        .description(String::from("Pipeline backfill checkpoint raw topic"))
+        .build()
+}
@@ -0,0 +1,82 @@
+use crate::{Factor, FactorIndexGroup, FactorType, Topic, TopicKind, TopicType};
+
+pub fn ask_raw_pipeline_dead_letter_topic() -> Topic {
+    let mut factors = Vec::new();
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-1"))
+            .name(String::from("deadLetterId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::UniqueIndex2)
+            .precision(String::from("50")),
+    );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-2"))
+            .name(String::from("traceId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index1)
+            .precision(String::from("50")),
+    );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-3"))
+            .name(String::from("pipelineId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index2)
+            .precision(String::from("50")),
+    );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-4"))
+            .name(String::from("topicId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index3)
+            .precision(String::from("50")),
+    );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-5"))
+            .name(String::from("dataId"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index4)
+            .precision(String::from("50")),
+    );
+
+    fn create_factor(
+        factor_id: &'static str,
+        name: &'static str,
+        factor_type: FactorType,
+    ) -> Factor {
+        Factor::new()
+            .factor_id(String::from(factor_id))
+            .name(String::from(name))
+            .r#type(factor_type)
+    }
+
+    factors.push(create_factor("rpdl-f-6", "triggerType", FactorType::Text));
+    factors.push(create_factor("rpdl-f-7", "oldValue", FactorType::Object));
+    factors.push(create_factor("rpdl-f-8", "newValue", FactorType::Object));
+    factors.push(create_factor("rpdl-f-9", "error", FactorType::Text));
+    factors.push(create_factor("rpdl-f-10", "attempts", FactorType::Unsigned));
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpdl-f-11"))
+            .name(String::from("createdAt"))
+            .r#type(FactorType::FullDatetime)
+            .flatten(true),
+    );
+
+    Topic::new()
+        .name(String::from("raw_pipeline_dead_letter"))
+        .kind(TopicKind::System)
+        .r#type(TopicType::Raw)
+        .factors(factors)
+        .description(String::from("Pipeline dead letter raw topic"))
+        .build()
+}
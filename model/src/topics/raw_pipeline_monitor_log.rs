@@ -11,6 +11,15 @@ pub fn ask_raw_pipeline_monitor_topic() -> Topic {
             .index_group(FactorIndexGroup::UniqueIndex2)
             .precision(String::from("50")),
     );
+    factors.push(
+        Factor::new()
+            .factor_id(String::from("rpml-f-49"))
+            .name(String::from("retryOf"))
+            .r#type(FactorType::Text)
+            .flatten(true)
+            .index_group(FactorIndexGroup::Index5)
+            .precision(String::from("50")),
+    );
     factors.push(
         Factor::new()
             .factor_id(String::from("rpml-f-2"))
@@ -208,6 +217,11 @@ pub fn ask_raw_pipeline_monitor_topic() -> Topic {
         "stages.units.actions.deleteCount",
         FactorType::Unsigned,
     ));
+    factors.push(create_factor(
+        "rpml-f-48",
+        "stages.units.actions.conflictCount",
+        FactorType::Unsigned,
+    ));
     factors.push(create_factor(
         "rpml-f-40",
         "stages.units.actions.definedAs",
@@ -0,0 +1,72 @@
+use crate::{NoopAlarmSink, PipelineAlarm};
+use elf_base::{ErrorCode, StdErrCode, VoidR};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait AlarmSink: Send + Sync {
+    fn emit(&self, alarm: PipelineAlarm) -> VoidR;
+}
+
+static GLOBAL_ALARM_SINK: OnceLock<RwLock<Arc<dyn AlarmSink>>> = OnceLock::new();
+
+/// the default alarm sink is [NoopAlarmSink], alarms are discarded,
+/// [set] a new alarm sink to replace the default one.
+pub struct AlarmSinks;
+
+impl AlarmSinks {
+    fn init() -> RwLock<Arc<dyn AlarmSink>> {
+        RwLock::new(Arc::new(NoopAlarmSink {}) as Arc<dyn AlarmSink>)
+    }
+
+    /// get current sink, and release lock before emit
+    pub fn emit(alarm: PipelineAlarm) -> VoidR {
+        let sink = GLOBAL_ALARM_SINK
+            .get_or_init(AlarmSinks::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone();
+
+        sink.emit(alarm)
+    }
+
+    pub fn set(new_sink: Arc<dyn AlarmSink>) -> VoidR {
+        let mut guard = GLOBAL_ALARM_SINK
+            .get_or_init(AlarmSinks::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = new_sink;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AlarmSinks, InMemoryAlarmSink, PipelineAlarm};
+    use elf_model::AlarmActionSeverity;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_in_memory_sink() {
+        let sink = Arc::new(InMemoryAlarmSink::new());
+        AlarmSinks::set(sink.clone()).unwrap();
+
+        AlarmSinks::emit(PipelineAlarm {
+            tenant_id: "Tenant-1".to_string(),
+            trace_id: Arc::new("trace-1".to_string()),
+            action_id: Some(Arc::new("action-1".to_string())),
+            severity: Arc::new(AlarmActionSeverity::High),
+            message: "Amount is 100".to_string(),
+        })
+        .unwrap();
+
+        let alarms = sink.alarms().unwrap();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].message, "Amount is 100");
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{AlarmSink, PipelineAlarm};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use std::sync::RwLock;
+
+/// keep alarms in memory, typically for test
+#[derive(Default)]
+pub struct InMemoryAlarmSink {
+    alarms: RwLock<Vec<PipelineAlarm>>,
+}
+
+impl InMemoryAlarmSink {
+    pub fn new() -> Self {
+        InMemoryAlarmSink {
+            alarms: RwLock::new(vec![]),
+        }
+    }
+
+    /// get all emitted alarms, in emitted order
+    pub fn alarms(&self) -> StdR<Vec<PipelineAlarm>> {
+        let guard = self.alarms.read().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+        })?;
+        Ok(guard.clone())
+    }
+}
+
+impl AlarmSink for InMemoryAlarmSink {
+    fn emit(&self, alarm: PipelineAlarm) -> VoidR {
+        let mut guard = self.alarms.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.push(alarm);
+        Ok(())
+    }
+}
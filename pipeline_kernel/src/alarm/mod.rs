@@ -0,0 +1,9 @@
+mod alarm_sink;
+mod in_memory_alarm_sink;
+mod noop_alarm_sink;
+mod pipeline_alarm;
+
+pub use alarm_sink::*;
+pub use in_memory_alarm_sink::*;
+pub use noop_alarm_sink::*;
+pub use pipeline_alarm::*;
@@ -0,0 +1,11 @@
+use crate::{AlarmSink, PipelineAlarm};
+use elf_base::VoidR;
+
+/// discard alarm, the default sink until a real one is set by [crate::AlarmSinks::set]
+pub struct NoopAlarmSink;
+
+impl AlarmSink for NoopAlarmSink {
+    fn emit(&self, _alarm: PipelineAlarm) -> VoidR {
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+use elf_model::{AlarmActionSeverity, PipelineActionId, PipelineTriggerTraceId, TenantId};
+use std::sync::Arc;
+
+/// alarm raised by alarm action, or by pipeline kernel itself
+#[derive(Debug, Clone)]
+pub struct PipelineAlarm {
+    pub tenant_id: TenantId,
+    pub trace_id: Arc<PipelineTriggerTraceId>,
+    /// none when raised by pipeline kernel, e.g. monitor log cannot be written
+    pub action_id: Option<Arc<PipelineActionId>>,
+    pub severity: Arc<AlarmActionSeverity>,
+    /// rendered message
+    pub message: String,
+}
@@ -0,0 +1,52 @@
+use crate::RawTopicBackfillCheckpointStore;
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineBackfillCheckpoint, PipelineBackfillId, TenantId};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait BackfillCheckpointStore: Send + Sync {
+    fn find(
+        &self,
+        tenant_id: &TenantId,
+        backfill_id: &PipelineBackfillId,
+    ) -> StdR<Option<PipelineBackfillCheckpoint>>;
+    /// insert, or replace the saved one of same backfill id
+    fn save(&self, tenant_id: &TenantId, checkpoint: PipelineBackfillCheckpoint) -> VoidR;
+}
+
+static GLOBAL_BACKFILL_CHECKPOINT_STORE: OnceLock<RwLock<Arc<dyn BackfillCheckpointStore>>> =
+    OnceLock::new();
+
+/// the default checkpoint store is [RawTopicBackfillCheckpointStore],
+/// [set] a new store to replace the default one.
+pub struct BackfillCheckpointStores;
+
+impl BackfillCheckpointStores {
+    fn init() -> RwLock<Arc<dyn BackfillCheckpointStore>> {
+        RwLock::new(Arc::new(RawTopicBackfillCheckpointStore {}) as Arc<dyn BackfillCheckpointStore>)
+    }
+
+    /// get current store, and release lock before use
+    pub fn store() -> StdR<Arc<dyn BackfillCheckpointStore>> {
+        Ok(GLOBAL_BACKFILL_CHECKPOINT_STORE
+            .get_or_init(BackfillCheckpointStores::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone())
+    }
+
+    pub fn set(new_store: Arc<dyn BackfillCheckpointStore>) -> VoidR {
+        let mut guard = GLOBAL_BACKFILL_CHECKPOINT_STORE
+            .get_or_init(BackfillCheckpointStores::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = new_store;
+
+        Ok(())
+    }
+}
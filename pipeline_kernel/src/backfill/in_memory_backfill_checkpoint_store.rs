@@ -0,0 +1,61 @@
+use crate::{BackfillCheckpointStore, PipelineKernelErrorCode};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineBackfillCheckpoint, PipelineBackfillId, TenantId};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// keep checkpoints in memory, typically for test.
+/// checkpoints are kept as json, and deserialized on each find.
+#[derive(Default)]
+pub struct InMemoryBackfillCheckpointStore {
+    checkpoints: RwLock<HashMap<(TenantId, PipelineBackfillId), Value>>,
+}
+
+impl InMemoryBackfillCheckpointStore {
+    pub fn new() -> Self {
+        InMemoryBackfillCheckpointStore {
+            checkpoints: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl BackfillCheckpointStore for InMemoryBackfillCheckpointStore {
+    fn find(
+        &self,
+        tenant_id: &TenantId,
+        backfill_id: &PipelineBackfillId,
+    ) -> StdR<Option<PipelineBackfillCheckpoint>> {
+        let guard = self.checkpoints.read().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+        })?;
+        match guard.get(&(tenant_id.clone(), backfill_id.clone())) {
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|e| {
+                    PipelineKernelErrorCode::BackfillCheckpointNotSerializable.err_with_msg(
+                        format!(
+                            "Failed to convert json to pipeline backfill checkpoint, caused by {}.",
+                            e
+                        ),
+                    )
+                }),
+            _ => Ok(None),
+        }
+    }
+
+    fn save(&self, tenant_id: &TenantId, checkpoint: PipelineBackfillCheckpoint) -> VoidR {
+        let backfill_id = checkpoint.backfill_id.clone().unwrap_or_default();
+        let value = serde_json::to_value(checkpoint).map_err(|e| {
+            PipelineKernelErrorCode::BackfillCheckpointNotSerializable.err_with_msg(format!(
+                "Failed to convert pipeline backfill checkpoint to json, caused by {}.",
+                e
+            ))
+        })?;
+        let mut guard = self.checkpoints.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.insert((tenant_id.clone(), backfill_id), value);
+        Ok(())
+    }
+}
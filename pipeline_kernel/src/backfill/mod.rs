@@ -0,0 +1,9 @@
+mod backfill_checkpoint_store;
+mod in_memory_backfill_checkpoint_store;
+mod pipeline_backfill_service;
+mod raw_topic_backfill_checkpoint_store;
+
+pub use backfill_checkpoint_store::*;
+pub use in_memory_backfill_checkpoint_store::*;
+pub use pipeline_backfill_service::*;
+pub use raw_topic_backfill_checkpoint_store::*;
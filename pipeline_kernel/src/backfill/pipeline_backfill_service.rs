@@ -0,0 +1,464 @@
+use crate::{
+	BackfillCheckpointStores, CompiledCriteriaJoint, PipelineEntrypoint,
+	PipelineExecutionVariables, PipelineKernelErrorCode, PipelineTriggerOutcome, TopicTrigger,
+};
+use chrono::Local;
+use elf_auth::Principal;
+use elf_base::{ErrorCode, StdR, VoidR};
+use elf_model::{
+	PipelineBackfill, PipelineBackfillCheckpoint, PipelineBackfillTimeColumn, PipelineTriggerData,
+	PipelineTriggerType, TopicData, TopicDataColumnNames,
+};
+use elf_runtime_model_kernel::{
+	ArcParameterJoint, PipelineSchema, PipelineSchemaProvider, PipelineService, TopicDataProvider,
+	TopicDataScan, TopicSchema, TopicSchemaProvider, TopicService,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// prepared backfill, rows are scanned page by page from the checkpoint
+struct PipelineBackfillRound {
+    pipeline_schema: Arc<PipelineSchema>,
+    topic_schema: Arc<TopicSchema>,
+    scan: TopicDataScan,
+    checkpoint: PipelineBackfillCheckpoint,
+    /// last data id and size of the page scanned but not checkpointed yet
+    scanned: Option<(Option<String>, usize)>,
+}
+
+/// re-run pipeline over existing rows of its topic, in tenant of given principal.
+/// each row is fed as an insert trigger into the pipeline, and row is not saved again.
+/// failed row is counted and skipped, checkpoint is saved after each page,
+/// so backfill with same id resumes after the last processed row.
+pub struct PipelineBackfillService {
+    principal: Principal,
+}
+
+impl PipelineBackfillService {
+    /// default count of rows scanned in one page
+    const DEFAULT_PAGE_SIZE: u32 = 1000;
+
+    pub fn new(principal: Principal) -> Self {
+        Self { principal }
+    }
+
+    fn pipeline_schema_of(&self, backfill: &PipelineBackfill) -> StdR<Arc<PipelineSchema>> {
+        let pipeline_id = match &backfill.pipeline_id {
+            Some(pipeline_id) if !pipeline_id.trim().is_empty() => pipeline_id,
+            _ => {
+                return PipelineKernelErrorCode::BackfillPipelineIdMissed
+                    .msg("Pipeline id of backfill is missed.");
+            }
+        };
+        let pipeline_schema = PipelineService::schema()?
+            .by_pipeline_id(pipeline_id, &self.principal.tenant_id)?
+            .ok_or_else(|| {
+                PipelineKernelErrorCode::BackfillPipelineNotFound
+                    .err_with_msg(format!("Pipeline[{}] of backfill not found.", pipeline_id))
+            })?;
+        let r#type = pipeline_schema.r#type();
+        if !Self::is_type_supported(r#type.deref()) {
+            return PipelineKernelErrorCode::BackfillPipelineTypeNotSupported.msg(format!(
+                "Pipeline[{}]'s trigger type[{}] is not supported by backfill, only insert and insert-or-merge are supported.",
+                pipeline_id, r#type
+            ));
+        }
+        Ok(pipeline_schema)
+    }
+
+    fn is_type_supported(r#type: &PipelineTriggerType) -> bool {
+        matches!(
+            r#type,
+            PipelineTriggerType::Insert | PipelineTriggerType::InsertOrMerge
+        )
+    }
+
+    /// fingerprint of filter, time column and time range, by 64-bit FNV-1a
+    fn fingerprint_of(backfill: &PipelineBackfill) -> StdR<String> {
+        let filter = serde_json::to_string(&backfill.filter).map_err(|e| {
+            PipelineKernelErrorCode::BackfillCheckpointNotSerializable.err_with_msg(format!(
+                "Filter of backfill not serializable, caused by {}.",
+                e
+            ))
+        })?;
+        let source = format!(
+            "{}|{:?}|{:?}|{:?}",
+            filter,
+            backfill
+                .time_column
+                .as_ref()
+                .map(|column| column.to_string()),
+            backfill.start_time,
+            backfill.end_time
+        );
+        let hash = source.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Ok(format!("{:016x}", hash))
+    }
+
+    /// saved checkpoint is resumed only when it is created by same filter, time column and time range
+    fn check_resumable(checkpoint: &PipelineBackfillCheckpoint, fingerprint: &str) -> VoidR {
+        if checkpoint.fingerprint.as_deref() == Some(fingerprint) {
+            Ok(())
+        } else {
+            PipelineKernelErrorCode::BackfillCheckpointMismatched.msg(format!(
+                "Checkpoint of backfill[{}] is created by another filter or time range, use a new backfill id instead.",
+                checkpoint.backfill_id.as_deref().unwrap_or_default()
+            ))
+        }
+    }
+
+    fn scan_of(
+        &self,
+        backfill: &mut PipelineBackfill,
+        topic_schema: &Arc<TopicSchema>,
+    ) -> StdR<TopicDataScan> {
+        let criteria = match backfill.filter.take() {
+            Some(filter) => {
+                let joint = ArcParameterJoint::new(filter)?;
+                let tenant_id = Arc::new(self.principal.tenant_id.clone());
+                Some(
+                    CompiledCriteriaJoint::new(&joint, topic_schema, &tenant_id)?
+                        .to_criteria(&PipelineExecutionVariables::new(None, None))?,
+                )
+            }
+            _ => None,
+        };
+        let time_column = match &backfill.time_column {
+            Some(PipelineBackfillTimeColumn::InsertTime) => Some(TopicDataColumnNames::InsertTime),
+            Some(PipelineBackfillTimeColumn::UpdateTime) => Some(TopicDataColumnNames::UpdateTime),
+            _ => None,
+        };
+        Ok(TopicDataScan {
+            criteria,
+            time_column,
+            start_time: backfill.start_time,
+            end_time: backfill.end_time,
+            after_id: None,
+            limit: backfill
+                .page_size
+                .filter(|size| *size > 0)
+                .unwrap_or(Self::DEFAULT_PAGE_SIZE),
+        })
+    }
+
+    /// returns none when backfill finished already, filter of backfill is taken
+    fn prepare(&self, backfill: &mut PipelineBackfill) -> StdR<Option<PipelineBackfillRound>> {
+        let backfill_id = match &backfill.backfill_id {
+            Some(backfill_id) if !backfill_id.trim().is_empty() => backfill_id,
+            _ => {
+                return PipelineKernelErrorCode::BackfillIdMissed.msg("Backfill id is missed.");
+            }
+        };
+        let pipeline_schema = self.pipeline_schema_of(backfill)?;
+        let fingerprint = Self::fingerprint_of(backfill)?;
+
+        let checkpoint = match BackfillCheckpointStores::store()?
+            .find(&self.principal.tenant_id, backfill_id)?
+        {
+            Some(checkpoint) => {
+                Self::check_resumable(&checkpoint, &fingerprint)?;
+                checkpoint
+            }
+            _ => PipelineBackfillCheckpoint::new()
+                .backfill_id(backfill_id.clone())
+                .pipeline_id(pipeline_schema.pipeline_id().deref().clone())
+                .fingerprint(fingerprint)
+                .processed(0)
+                .failed(0)
+                .finished(false),
+        };
+        if checkpoint.finished.unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let topic_schema = TopicService::schema()?.by_id(
+            &pipeline_schema.pipeline().topic_id,
+            &self.principal.tenant_id,
+        )?;
+        let mut scan = self.scan_of(backfill, &topic_schema)?;
+        scan.after_id = checkpoint.last_data_id.clone();
+        Ok(Some(PipelineBackfillRound {
+            pipeline_schema,
+            topic_schema,
+            scan,
+            checkpoint,
+            scanned: None,
+        }))
+    }
+
+    /// trigger type is the one of pipeline, so the pipeline is matched by entrypoint.
+    /// insert-or-merge is accepted on existing data by entrypoint, row is triggered as inserted.
+    fn trigger_data_of(
+        &self,
+        round: &PipelineBackfillRound,
+        data: TopicData,
+    ) -> PipelineTriggerData {
+        PipelineTriggerData::new()
+            .code(round.topic_schema.name().deref().clone())
+            .trigger_type(round.pipeline_schema.r#type().deref().clone())
+            .tenant_id(self.principal.tenant_id.clone())
+            .data(data)
+    }
+
+    fn entrypoint_of(&self, round: &PipelineBackfillRound) -> StdR<PipelineEntrypoint> {
+        PipelineEntrypoint::with(self.principal.clone())
+            .pipeline(round.pipeline_schema.pipeline_id().deref().clone())
+    }
+
+    /// count the row, failed when error raised or any pipeline failed
+    fn count(checkpoint: &mut PipelineBackfillCheckpoint, outcome: StdR<PipelineTriggerOutcome>) {
+        checkpoint.processed = Some(checkpoint.processed.unwrap_or(0) + 1);
+        let failed = match outcome {
+            Ok(outcome) => !outcome.summary.failed.is_empty(),
+            _ => true,
+        };
+        if failed {
+            checkpoint.failed = Some(checkpoint.failed.unwrap_or(0) + 1);
+        }
+    }
+
+    /// save checkpoint after page processed, page smaller than limit is the last one
+    fn save_checkpoint(
+        &self,
+        round: &mut PipelineBackfillRound,
+        last_data_id: Option<String>,
+        page_size: usize,
+    ) -> StdR<bool> {
+        if last_data_id.is_some() {
+            round.checkpoint.last_data_id = last_data_id.clone();
+            round.scan.after_id = last_data_id;
+        }
+        let finished = page_size < round.scan.limit as usize;
+        round.checkpoint.finished = Some(finished);
+        round.checkpoint.last_modified_at = Some(Local::now().naive_local());
+        BackfillCheckpointStores::store()?
+            .save(&self.principal.tenant_id, round.checkpoint.clone())?;
+        Ok(finished)
+    }
+
+    /// save checkpoint of the processed page, and scan the next page.
+    /// returns trigger data of rows in page, or none when the processed page is the last one.
+    fn next_page(
+        &self,
+        round: &mut PipelineBackfillRound,
+    ) -> StdR<Option<Vec<PipelineTriggerData>>> {
+        if let Some((last_data_id, page_size)) = round.scanned.take()
+            && self.save_checkpoint(round, last_data_id, page_size)?
+        {
+            return Ok(None);
+        }
+
+        let page = TopicService::data()?.scan(&round.topic_schema, &round.scan)?;
+        let page_size = page.len();
+        let mut last_data_id = None;
+        let mut trigger_data = Vec::with_capacity(page_size);
+        for data in page {
+            last_data_id = Some(TopicTrigger::get_data_id(&data)?.deref().clone());
+            trigger_data.push(self.trigger_data_of(round, data));
+        }
+        round.scanned = Some((last_data_id, page_size));
+        Ok(Some(trigger_data))
+    }
+
+    /// returns the final checkpoint.
+    /// blocks current thread, do not call it in async context, use [run_async] instead.
+    pub fn run(&self, mut backfill: PipelineBackfill) -> StdR<PipelineBackfillCheckpoint> {
+        let mut round = match self.prepare(&mut backfill)? {
+            Some(round) => round,
+            _ => return self.finished(&backfill),
+        };
+        let entrypoint = self.entrypoint_of(&round)?;
+        while let Some(page) = self.next_page(&mut round)? {
+            for trigger_data in page {
+                let outcome = entrypoint.execute_existing_and_wait(trigger_data);
+                Self::count(&mut round.checkpoint, outcome);
+            }
+        }
+        Ok(round.checkpoint)
+    }
+
+    pub async fn run_async(
+        &self,
+        mut backfill: PipelineBackfill,
+    ) -> StdR<PipelineBackfillCheckpoint> {
+        let mut round = match self.prepare(&mut backfill)? {
+            Some(round) => round,
+            _ => return self.finished(&backfill),
+        };
+        let entrypoint = self.entrypoint_of(&round)?;
+        while let Some(page) = self.next_page(&mut round)? {
+            for trigger_data in page {
+                let outcome = entrypoint
+                    .execute_existing_and_wait_async(trigger_data)
+                    .await;
+                Self::count(&mut round.checkpoint, outcome);
+            }
+        }
+        Ok(round.checkpoint)
+    }
+
+    /// checkpoint of finished backfill, as it is saved
+    fn finished(&self, backfill: &PipelineBackfill) -> StdR<PipelineBackfillCheckpoint> {
+        let backfill_id = backfill.backfill_id.clone().unwrap_or_default();
+        BackfillCheckpointStores::store()?
+            .find(&self.principal.tenant_id, &backfill_id)?
+            .ok_or_else(|| {
+                PipelineKernelErrorCode::BackfillIdMissed.err_with_msg(format!(
+                    "Checkpoint of backfill[{}] not found.",
+                    backfill_id
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PipelineBackfillRound;
+    use crate::{PipelineBackfillService, PipelineRunSummary, PipelineTriggerOutcome};
+    use chrono::NaiveDateTime;
+    use elf_auth::Principal;
+    use elf_base::{ErrorCode, StdErrCode};
+    use elf_model::{
+        Factor, FactorType, Pipeline, PipelineBackfill, PipelineBackfillCheckpoint,
+        PipelineBackfillTimeColumn, PipelineStage, PipelineTriggerResult, PipelineTriggerType,
+        PipelineUnit, Topic, TopicDataColumnNames, TopicDataValue, TopicKind, TopicType,
+        WriteToExternalAction,
+    };
+    use elf_runtime_model_kernel::{PipelineSchema, TopicDataScan, TopicSchema};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_count() {
+        let mut checkpoint = PipelineBackfillCheckpoint::new().processed(0).failed(0);
+        PipelineBackfillService::count(
+            &mut checkpoint,
+            Ok(PipelineTriggerOutcome {
+                result: PipelineTriggerResult::new(),
+                summary: PipelineRunSummary::default(),
+            }),
+        );
+        assert_eq!(checkpoint.processed, Some(1));
+        assert_eq!(checkpoint.failed, Some(0));
+
+        let mut summary = PipelineRunSummary::default();
+        summary.failed.push((
+            Arc::new(String::from("p1")),
+            StdErrCode::Unknown.err_with_msg("failed"),
+        ));
+        PipelineBackfillService::count(
+            &mut checkpoint,
+            Ok(PipelineTriggerOutcome {
+                result: PipelineTriggerResult::new(),
+                summary,
+            }),
+        );
+        PipelineBackfillService::count(&mut checkpoint, StdErrCode::Unknown.msg("failed"));
+        assert_eq!(checkpoint.processed, Some(3));
+        assert_eq!(checkpoint.failed, Some(2));
+    }
+
+    #[test]
+    fn test_type_supported() {
+        assert!(PipelineBackfillService::is_type_supported(
+            &PipelineTriggerType::Insert
+        ));
+        assert!(PipelineBackfillService::is_type_supported(
+            &PipelineTriggerType::InsertOrMerge
+        ));
+        assert!(!PipelineBackfillService::is_type_supported(
+            &PipelineTriggerType::Merge
+        ));
+    }
+
+    #[test]
+    fn test_resumable() {
+        let backfill = PipelineBackfill::new()
+            .backfill_id(String::from("bf-1"))
+            .time_column(PipelineBackfillTimeColumn::InsertTime)
+            .start_time(NaiveDateTime::default());
+        let fingerprint = PipelineBackfillService::fingerprint_of(&backfill).unwrap();
+        assert_eq!(
+            PipelineBackfillService::fingerprint_of(&backfill).unwrap(),
+            fingerprint
+        );
+        let checkpoint = PipelineBackfillCheckpoint::new()
+            .backfill_id(String::from("bf-1"))
+            .fingerprint(fingerprint.clone());
+        assert!(PipelineBackfillService::check_resumable(&checkpoint, &fingerprint).is_ok());
+
+        // time range changed
+        let backfill = backfill.end_time(NaiveDateTime::default());
+        let fingerprint = PipelineBackfillService::fingerprint_of(&backfill).unwrap();
+        let error =
+            PipelineBackfillService::check_resumable(&checkpoint, &fingerprint).unwrap_err();
+        assert_eq!(error.code, "PLKN-00905");
+    }
+
+    #[test]
+    fn test_trigger_insert_or_merge() {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("factor_1"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let pipeline = Pipeline::new()
+            .pipeline_id(String::from("p1"))
+            .topic_id(String::from("topic-1"))
+            .name(String::from("p1"))
+            .r#type(PipelineTriggerType::InsertOrMerge)
+            .stages(vec![PipelineStage::new().units(vec![
+                PipelineUnit::new().r#do(vec![
+                    WriteToExternalAction::init()
+                        .external_writer_id(String::from("writer-1"))
+                        .event_code(String::from("event-1"))
+                        .to_action(),
+                ]),
+            ])])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let round = PipelineBackfillRound {
+            pipeline_schema: Arc::new(PipelineSchema::new(pipeline).unwrap()),
+            topic_schema: Arc::new(TopicSchema::new(topic).unwrap()),
+            scan: TopicDataScan {
+                criteria: None,
+                time_column: None,
+                start_time: None,
+                end_time: None,
+                after_id: None,
+                limit: 10,
+            },
+            checkpoint: PipelineBackfillCheckpoint::new(),
+            scanned: None,
+        };
+        let data = HashMap::from([(
+            TopicDataColumnNames::Id.column_name().to_string(),
+            TopicDataValue::Str(String::from("1")),
+        )]);
+
+        // row of insert-or-merge pipeline is accepted as existing data
+        let service = PipelineBackfillService::new(Principal::fake_super_admin());
+        let trigger_data = service.trigger_data_of(&round, data);
+        let entrypoint = service.entrypoint_of(&round).unwrap();
+        assert!(
+            entrypoint
+                .check_existing_trigger_type(&trigger_data.trigger_type)
+                .is_ok()
+        );
+        assert!(
+            entrypoint
+                .check_existing_trigger_type(&Some(PipelineTriggerType::Merge))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{BackfillCheckpointStore, PipelineKernelErrorCode, RawTopicModel};
+use elf_base::{StdR, VoidR};
+use elf_model::{
+	PipelineBackfillCheckpoint, PipelineBackfillId, TenantId, TopicCode, TopicData,
+	TopicDataColumnNames,
+};
+use elf_runtime_model_kernel::{TopicDataProvider, TopicSchema, TopicSchemaProvider, TopicService};
+use std::sync::Arc;
+
+/// persist checkpoints into raw pipeline backfill checkpoint topic of tenant,
+/// one row for each backfill.
+pub struct RawTopicBackfillCheckpointStore;
+
+impl RawTopicBackfillCheckpointStore {
+    /// code of raw topic which checkpoints are persisted into
+    const RAW_PIPELINE_BACKFILL_CHECKPOINT_TOPIC: &'static str = "raw_pipeline_backfill_checkpoint";
+    const CHECKPOINT: &'static str = "pipeline backfill checkpoint";
+
+    fn topic_schema(tenant_id: &TenantId) -> StdR<Arc<TopicSchema>> {
+        TopicService::schema()?.by_code(
+            &TopicCode::from(Self::RAW_PIPELINE_BACKFILL_CHECKPOINT_TOPIC),
+            tenant_id,
+        )
+    }
+
+    fn find_row(
+        topic_schema: &Arc<TopicSchema>,
+        backfill_id: &PipelineBackfillId,
+    ) -> StdR<Option<TopicData>> {
+        let criteria = RawTopicModel::criteria_of(topic_schema, "backfillId", backfill_id)?;
+        TopicService::data()?.find_one(topic_schema, &criteria)
+    }
+}
+
+impl BackfillCheckpointStore for RawTopicBackfillCheckpointStore {
+    fn find(
+        &self,
+        tenant_id: &TenantId,
+        backfill_id: &PipelineBackfillId,
+    ) -> StdR<Option<PipelineBackfillCheckpoint>> {
+        let topic_schema = Self::topic_schema(tenant_id)?;
+        match Self::find_row(&topic_schema, backfill_id)? {
+            Some(data) => Ok(Some(RawTopicModel::from_topic_data(
+                &topic_schema,
+                data,
+                Self::CHECKPOINT,
+                PipelineKernelErrorCode::BackfillCheckpointNotSerializable,
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// merge into the saved row when it exists, id of saved row is kept
+    fn save(&self, tenant_id: &TenantId, checkpoint: PipelineBackfillCheckpoint) -> VoidR {
+        let topic_schema = Self::topic_schema(tenant_id)?;
+        let backfill_id = checkpoint.backfill_id.clone().unwrap_or_default();
+        let mut data = RawTopicModel::to_topic_data(
+            &checkpoint,
+            Self::CHECKPOINT,
+            PipelineKernelErrorCode::BackfillCheckpointNotSerializable,
+        )?;
+        topic_schema.prepare(&mut data)?;
+
+        let data_service = TopicService::data()?;
+        match Self::find_row(&topic_schema, &backfill_id)? {
+            Some(saved) => {
+                let id_column = TopicDataColumnNames::Id.column_name();
+                if let Some(id) = saved.get(id_column) {
+                    data.insert(id_column.to_string(), id.clone());
+                }
+                data_service.merge(&topic_schema, data)?;
+            }
+            _ => {
+                data_service.insert(&topic_schema, data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PipelineKernelErrorCode, RawTopicModel};
+    use chrono::NaiveDateTime;
+    use elf_model::topics::ask_raw_pipeline_backfill_checkpoint_topic;
+    use elf_model::{PipelineBackfillCheckpoint, TopicData, TopicDataValue};
+    use elf_runtime_model_kernel::TopicSchema;
+
+    #[test]
+    fn test_convert() {
+        let topic = ask_raw_pipeline_backfill_checkpoint_topic()
+            .topic_id(String::from("rpbc"))
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let topic_schema = TopicSchema::new(topic).unwrap();
+
+        let checkpoint = PipelineBackfillCheckpoint::new()
+            .backfill_id(String::from("bf-1"))
+            .pipeline_id(String::from("p1"))
+            .last_data_id(String::from("100"))
+            .processed(20)
+            .failed(2)
+            .finished(false)
+            .last_modified_at(NaiveDateTime::default());
+        let mut data: TopicData = RawTopicModel::to_topic_data(
+            &checkpoint,
+            "pipeline backfill checkpoint",
+            PipelineKernelErrorCode::BackfillCheckpointNotSerializable,
+        )
+        .unwrap();
+        assert!(matches!(data.get("backfillId"), Some(TopicDataValue::Str(v)) if v == "bf-1"));
+        // columns of raw topic are ignored
+        data.insert(String::from("id_"), TopicDataValue::Str(String::from("1")));
+
+        let checkpoint: PipelineBackfillCheckpoint = RawTopicModel::from_topic_data(
+            &topic_schema,
+            data,
+            "pipeline backfill checkpoint",
+            PipelineKernelErrorCode::BackfillCheckpointNotSerializable,
+        )
+        .unwrap();
+        assert_eq!(checkpoint.last_data_id.unwrap(), "100");
+        assert_eq!(checkpoint.processed.unwrap(), 20);
+        assert_eq!(checkpoint.failed.unwrap(), 2);
+        assert!(!checkpoint.finished.unwrap());
+    }
+}
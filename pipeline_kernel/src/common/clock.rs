@@ -0,0 +1,71 @@
+use chrono::{Local, NaiveDateTime};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// local system time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// always returns the given time
+pub struct FixedClock {
+    now: NaiveDateTime,
+}
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        FixedClock { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.now
+    }
+}
+
+static GLOBAL_CLOCK: OnceLock<RwLock<Arc<dyn Clock>>> = OnceLock::new();
+
+/// the default clock is [SystemClock],
+/// [set] a new clock to replace the default one.
+pub struct Clocks;
+
+impl Clocks {
+    fn init() -> RwLock<Arc<dyn Clock>> {
+        RwLock::new(Arc::new(SystemClock {}) as Arc<dyn Clock>)
+    }
+
+    pub fn now() -> StdR<NaiveDateTime> {
+        let clock = GLOBAL_CLOCK
+            .get_or_init(Clocks::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone();
+
+        Ok(clock.now())
+    }
+
+    pub fn set(new_clock: Arc<dyn Clock>) -> VoidR {
+        let mut guard = GLOBAL_CLOCK
+            .get_or_init(Clocks::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = new_clock;
+
+        Ok(())
+    }
+}
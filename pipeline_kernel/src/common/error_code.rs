@@ -16,6 +16,9 @@ pub enum PipelineKernelErrorCode {
     TriggerTraceIdIsBlank,
     TriggerTypeMismatchPipeline,
     TriggerPipelineNotFound,
+    TriggerTypeNotSupportedOnBatch,
+    TriggerBatchChunkNotSaved,
+    TriggerTopicMismatchPipeline,
     // topic data
     TopicDataIdNotFound,
     TopicDataIdTypeNotSupported,
@@ -23,9 +26,58 @@ pub enum PipelineKernelErrorCode {
     ValuesNotComparable,
     VariableFuncNotSupported,
     IncorrectDataPath,
+    TopicDataVersionTypeNotSupported,
+    ValueNotConcatenatable,
+    ValueDivideByZero,
+    ValueNotDateRelated,
+    VariableFuncParamNotSupported,
+    IncorrectDateMovement,
+    SequenceTenantMissed,
+    SequenceStoreNotConfigured,
     // schema
     FactorNotFound,
     TopicDataPropertySegmentMissed,
+    // action
+    AccumulateModeNotSupported,
+    TopicDataNotFound,
+    FactorValueNotWritable,
+    TopicDataMatchedMoreThanOne,
+    TopicDataVersionConflicted,
+    MergeRetriesAlreadyInitialized,
+    // execution pool
+    ExecutionPoolCreate,
+    ExecutionPoolAlreadyInitialized,
+    ExecutionPoolQueueFull,
+    ExecutionPoolShutdown,
+    PipelineRunAborted,
+    // cascade
+    CascadeLimitsAlreadyInitialized,
+    CascadeCycleDetected,
+    CascadeDepthExceeded,
+    CascadeExecutionsExceeded,
+    // monitor
+    MonitorLogNotSerializable,
+    // unit of work
+    UnitOfWorkNotCompensated,
+    UnitOfWorkVersionConflicted,
+    UnitOfWorkNotRolledBack,
+    UnitOfWorkNotDiscarded,
+    // dead letter
+    DeadLetterNotSerializable,
+    DeadLetterPipelineNotFound,
+    DeadLetterTriggerDataMissed,
+    DeadLetterNotSaved,
+    // backfill
+    BackfillIdMissed,
+    BackfillPipelineIdMissed,
+    BackfillPipelineNotFound,
+    BackfillPipelineTypeNotSupported,
+    BackfillCheckpointNotSerializable,
+    BackfillCheckpointMismatched,
+    // timeout and cancellation
+    PipelineTimeout,
+    TraceTimeout,
+    PipelineRunCancelled,
 }
 
 impl ErrorCode for PipelineKernelErrorCode {
@@ -45,6 +97,9 @@ impl ErrorCode for PipelineKernelErrorCode {
             Self::TriggerTraceIdIsBlank => "PLKN-00012",
             Self::TriggerTypeMismatchPipeline => "PLKN-00013",
             Self::TriggerPipelineNotFound => "PLKN-00014",
+            Self::TriggerTypeNotSupportedOnBatch => "PLKN-00015",
+            Self::TriggerBatchChunkNotSaved => "PLKN-00016",
+            Self::TriggerTopicMismatchPipeline => "PLKN-00017",
 
             Self::TopicDataIdNotFound => "PLKN-00100",
             Self::TopicDataIdTypeNotSupported => "PLKN-00101",
@@ -52,9 +107,58 @@ impl ErrorCode for PipelineKernelErrorCode {
             Self::ValuesNotComparable => "PLKN-00103",
             Self::VariableFuncNotSupported => "PLKN-00104",
             Self::IncorrectDataPath => "PLKN-00105",
+            Self::TopicDataVersionTypeNotSupported => "PLKN-00106",
+            Self::ValueNotConcatenatable => "PLKN-00107",
+            Self::ValueDivideByZero => "PLKN-00108",
+            Self::ValueNotDateRelated => "PLKN-00109",
+            Self::VariableFuncParamNotSupported => "PLKN-00110",
+            Self::IncorrectDateMovement => "PLKN-00111",
+            Self::SequenceTenantMissed => "PLKN-00112",
+            Self::SequenceStoreNotConfigured => "PLKN-00113",
 
             Self::FactorNotFound => "PLKN-00200",
             Self::TopicDataPropertySegmentMissed => "PLKN-00201",
+
+            Self::AccumulateModeNotSupported => "PLKN-00300",
+            Self::TopicDataNotFound => "PLKN-00301",
+            Self::FactorValueNotWritable => "PLKN-00302",
+            Self::TopicDataMatchedMoreThanOne => "PLKN-00303",
+            Self::TopicDataVersionConflicted => "PLKN-00304",
+            Self::MergeRetriesAlreadyInitialized => "PLKN-00305",
+
+            Self::ExecutionPoolCreate => "PLKN-00400",
+            Self::ExecutionPoolAlreadyInitialized => "PLKN-00401",
+            Self::ExecutionPoolQueueFull => "PLKN-00402",
+            Self::ExecutionPoolShutdown => "PLKN-00403",
+            Self::PipelineRunAborted => "PLKN-00404",
+
+            Self::CascadeLimitsAlreadyInitialized => "PLKN-00500",
+            Self::CascadeCycleDetected => "PLKN-00501",
+            Self::CascadeDepthExceeded => "PLKN-00502",
+            Self::CascadeExecutionsExceeded => "PLKN-00503",
+
+            Self::MonitorLogNotSerializable => "PLKN-00600",
+
+            Self::UnitOfWorkNotCompensated => "PLKN-00700",
+            Self::UnitOfWorkVersionConflicted => "PLKN-00701",
+            Self::UnitOfWorkNotRolledBack => "PLKN-00702",
+            Self::UnitOfWorkNotDiscarded => "PLKN-00703",
+
+            Self::DeadLetterNotSerializable => "PLKN-00800",
+            Self::DeadLetterPipelineNotFound => "PLKN-00801",
+            Self::DeadLetterTriggerDataMissed => "PLKN-00802",
+            Self::DeadLetterNotSaved => "PLKN-00803",
+
+            Self::BackfillIdMissed => "PLKN-00900",
+            Self::BackfillPipelineIdMissed => "PLKN-00901",
+            Self::BackfillPipelineNotFound => "PLKN-00902",
+            Self::BackfillPipelineTypeNotSupported => "PLKN-00903",
+            Self::BackfillCheckpointNotSerializable => "PLKN-00904",
+            Self::BackfillCheckpointMismatched => "PLKN-00905",
+
+            Self::PipelineTimeout => "PLKN-01000",
+            Self::TraceTimeout => "PLKN-01001",
+            Self::PipelineRunCancelled => "PLKN-01002",
         }
     }
 }
@@ -1,3 +1,7 @@
+mod clock;
 mod error_code;
+mod raw_topic_model;
 
+pub use clock::*;
 pub use error_code::*;
+pub use raw_topic_model::*;
@@ -0,0 +1,98 @@
+use crate::PipelineKernelErrorCode;
+use bigdecimal::ToPrimitive;
+use elf_base::{ErrorCode, StdR};
+use elf_model::{ParameterExpressionOperator, TopicData, TopicDataValue};
+use elf_runtime_model_kernel::{
+	TopicDataCriteria, TopicDataCriteriaExpression, TopicDataCriteriaOperand, TopicSchema,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use std::sync::Arc;
+
+/// convert model to row of raw topic, and back.
+pub struct RawTopicModel;
+
+impl RawTopicModel {
+    pub fn to_topic_data<T: Serialize>(
+        model: &T,
+        name: &str,
+        code: PipelineKernelErrorCode,
+    ) -> StdR<TopicData> {
+        match serde_json::to_value(model).and_then(serde_json::from_value) {
+            Ok(data) => Ok(data),
+            Err(e) => code.msg(format!(
+                "Failed to convert {} to topic data, caused by {}.",
+                name, e
+            )),
+        }
+    }
+
+    /// decimal is serialized as string, convert it to json number when it is integer
+    fn to_json(value: TopicDataValue) -> Value {
+        match value {
+            TopicDataValue::Num(num) if num.is_integer() => match num.to_i64() {
+                Some(num) => Value::Number(Number::from(num)),
+                _ => Value::String(num.to_string()),
+            },
+            TopicDataValue::Map(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, Self::to_json(value)))
+                    .collect(),
+            ),
+            TopicDataValue::Vec(vec) => Value::Array(vec.into_iter().map(Self::to_json).collect()),
+            other => serde_json::to_value(other).unwrap_or(Value::Null),
+        }
+    }
+
+    /// columns of raw topic, such as id and audit columns, are not part of model.
+    /// datetime is deserialized from borrowed string, so convert by json string.
+    pub fn from_topic_data<T: DeserializeOwned>(
+        topic_schema: &TopicSchema,
+        data: TopicData,
+        name: &str,
+        code: PipelineKernelErrorCode,
+    ) -> StdR<T> {
+        let object: Map<String, Value> = data
+            .into_iter()
+            .filter(|(key, _)| topic_schema.factor_by_name(key).is_some())
+            .map(|(key, value)| (key, Self::to_json(value)))
+            .collect();
+        let model = serde_json::to_string(&Value::Object(object))
+            .and_then(|json| serde_json::from_str::<T>(&json));
+        match model {
+            Ok(model) => Ok(model),
+            Err(e) => code.msg(format!(
+                "Failed to convert topic data to {}, caused by {}.",
+                name, e
+            )),
+        }
+    }
+
+    /// criteria of given factor equals to given value
+    pub fn criteria_of(
+        topic_schema: &TopicSchema,
+        factor_name: &str,
+        value: &str,
+    ) -> StdR<TopicDataCriteria> {
+        let factor = topic_schema
+            .topic()
+            .factors
+            .iter()
+            .find(|factor| factor.name.as_str() == factor_name)
+            .ok_or_else(|| {
+                PipelineKernelErrorCode::FactorNotFound.err_with_msg(format!(
+                    "Factor[{}] not found in topic[{}].",
+                    factor_name,
+                    topic_schema.name()
+                ))
+            })?;
+        Ok(TopicDataCriteria::Expression(TopicDataCriteriaExpression {
+            left: TopicDataCriteriaOperand::Factor(factor.clone()),
+            operator: Arc::new(ParameterExpressionOperator::Equals),
+            right: Some(TopicDataCriteriaOperand::Value(TopicDataValue::Str(
+                value.to_string(),
+            ))),
+        }))
+    }
+}
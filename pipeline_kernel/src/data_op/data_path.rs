@@ -50,6 +50,42 @@ impl PathStr {
             .collect()
     }
 
+    /// to string, with escape chars resolved, see [DataPath]
+    pub fn to_unescaped_string(&self) -> String {
+        Self::unescape(&self.to_string())
+    }
+
+    /// resolve escape chars of given string, see [DataPath]
+    pub fn unescape(str: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = str.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char != '\\' {
+                unescaped.push(char);
+                continue;
+            }
+            match chars.peek() {
+                Some('.' | ',' | '(' | ')' | '{' | '}' | '&') => {
+                    unescaped.push(chars.next().unwrap())
+                }
+                Some('t') => {
+                    chars.next();
+                    unescaped.push('\t')
+                }
+                Some('r') => {
+                    chars.next();
+                    unescaped.push('\r')
+                }
+                Some('n') => {
+                    chars.next();
+                    unescaped.push('\n')
+                }
+                _ => unescaped.push('\\'),
+            }
+        }
+        unescaped
+    }
+
     pub fn start_index(&self) -> usize {
         self.start_index
     }
@@ -97,6 +133,15 @@ pub struct FuncDataPath {
     pub params: Option<Vec<FuncDataPathParam>>,
 }
 
+impl FuncDataPath {
+    /// concat function which parsed from literal concat syntax, e.g. [a{b}c],
+    /// its path never starts with [&]
+    pub fn is_literal_concat(&self) -> bool {
+        matches!(self.func, VariablePredefineFunctions::Concat)
+            && !self.path.to_string().starts_with('&')
+    }
+}
+
 pub enum DataPathSegment {
     Plain(PlainDataPath),
     Func(FuncDataPath),
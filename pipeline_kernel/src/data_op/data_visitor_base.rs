@@ -1,6 +1,6 @@
 use crate::{
 	ArcTopicData, ArcTopicDataValue, DataPath, DataPathSegment, PipelineKernelErrorCode,
-	PlainDataPath, VariablePredefineFunctionCaller,
+	PlainDataPath, TenantSequences, VariablePredefineFunctionCaller,
 };
 use elf_base::{ErrorCode, StdR};
 use std::ops::Deref;
@@ -14,7 +14,31 @@ pub trait DataVisitorBase {
         full_path: &String,
     ) -> StdR<Arc<ArcTopicDataValue>>;
 
+    /// get value from given data by given property name
+    fn value_of_property(
+        &self,
+        data: &Arc<ArcTopicDataValue>,
+        current_path: &str,
+        current_is_vec: bool,
+        full_path: &str,
+    ) -> StdR<Arc<ArcTopicDataValue>>;
+
     fn value_of_path(&self, parsed_path: &DataPath) -> StdR<Arc<ArcTopicDataValue>>;
+
+    /// get value by given path, tenant sequences are used by [&nextSeq(name)]
+    fn value_of_path_in_tenant(
+        &self,
+        parsed_path: &DataPath,
+        sequences: Option<&TenantSequences>,
+    ) -> StdR<Arc<ArcTopicDataValue>>;
+
+    /// get value by given segments, which are part of full path
+    fn value_of_segments(
+        &self,
+        full_path: &str,
+        segments: &[DataPathSegment],
+        sequences: Option<&TenantSequences>,
+    ) -> StdR<Arc<ArcTopicDataValue>>;
 }
 
 impl DataVisitorBase for ArcTopicData {
@@ -46,9 +70,22 @@ impl DataVisitorBase for ArcTopicData {
         segment: &PlainDataPath,
         full_path: &String,
     ) -> StdR<Arc<ArcTopicDataValue>> {
-        let current_path = &segment.path.to_string();
-        let current_is_vec = &segment.is_vec.unwrap_or(false);
+        self.value_of_property(
+            data,
+            &segment.path.to_unescaped_string(),
+            segment.is_vec.unwrap_or(false),
+            full_path,
+        )
+    }
 
+    /// refer to [value_of_plain_segment]
+    fn value_of_property(
+        &self,
+        data: &Arc<ArcTopicDataValue>,
+        current_path: &str,
+        current_is_vec: bool,
+        full_path: &str,
+    ) -> StdR<Arc<ArcTopicDataValue>> {
         match data.deref() {
             ArcTopicDataValue::Map(map) => {
                 if let Some(value) = map.get(current_path) {
@@ -103,13 +140,60 @@ impl DataVisitorBase for ArcTopicData {
     }
 
     fn value_of_path(&self, parsed_path: &DataPath) -> StdR<Arc<ArcTopicDataValue>> {
-        let path = &parsed_path.path.to_string();
+        self.value_of_path_in_tenant(parsed_path, None)
+    }
+
+    fn value_of_path_in_tenant(
+        &self,
+        parsed_path: &DataPath,
+        sequences: Option<&TenantSequences>,
+    ) -> StdR<Arc<ArcTopicDataValue>> {
+        self.value_of_segments(
+            &parsed_path.path.to_string(),
+            &parsed_path.segments,
+            sequences,
+        )
+    }
+
+    /// returns self as a map when no segment given
+    fn value_of_segments(
+        &self,
+        full_path: &str,
+        segments: &[DataPathSegment],
+        sequences: Option<&TenantSequences>,
+    ) -> StdR<Arc<ArcTopicDataValue>> {
+        let path = &full_path.to_string();
         let mut data = Arc::new(ArcTopicDataValue::Map(self.clone()));
-        for segment in &parsed_path.segments {
+        for (index, segment) in segments.iter().enumerate() {
             let current_is_vec = match segment {
+                DataPathSegment::Func(segment)
+                    if segment.is_literal_concat() && segments.len() > 1 =>
+                {
+                    // literal concat is a property name when it is not the only segment
+                    let name = VariablePredefineFunctionCaller::prepare(
+                        self, path, segment, false, sequences,
+                    )
+                    .value_of(&data)?;
+                    let name = name.try_to_str(|| {
+                        PipelineKernelErrorCode::IncorrectDataPath.err_with_msg(format!(
+                            "Cannot retrieve[key={}, current={}] from [{:?}], caused by property name is not a string.",
+                            full_path,
+                            segment.path.to_string(),
+                            &self
+                        ))
+                    })?;
+                    data = self.value_of_property(&data, &name.unwrap_or_default(), false, path)?;
+                    false
+                }
                 DataPathSegment::Func(segment) => {
-                    data = VariablePredefineFunctionCaller::prepare(&self, path, segment)
-                        .value_of(&data)?;
+                    data = VariablePredefineFunctionCaller::prepare(
+                        self,
+                        path,
+                        segment,
+                        index > 0,
+                        sequences,
+                    )
+                    .value_of(&data)?;
                     // never mind, just keep the value which returned, no need to do post transforming
                     false
                 }
@@ -1,24 +1,37 @@
-use crate::{ArcTopicDataMap, ArcTopicDataValue, FuncDataPath, PipelineKernelErrorCode};
+use crate::{
+	ArcTopicData, ArcTopicDataValue, Clocks, DataVisitorBase, FuncDataPath, FuncDataPathParam,
+	FuncParamValue, PathStr, PipelineKernelErrorCode, TenantSequences,
+};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDateTime;
 use elf_base::{ErrorCode, StdErr, StdErrCode, StdR};
 use elf_model::VariablePredefineFunctions;
+use elf_runtime_model_kernel::IdGen;
 use std::sync::Arc;
 
 pub struct VariablePredefineFunctionCaller<'a> {
-    root: &'a ArcTopicDataMap,
+    root: &'a ArcTopicData,
     full_path: &'a String,
     segment: &'a FuncDataPath,
+    /// function follows a segment, or it is the first segment of path
+    with_context: bool,
+    sequences: Option<&'a TenantSequences>,
 }
 
 impl<'a> VariablePredefineFunctionCaller<'a> {
     pub fn prepare(
-        root: &'a ArcTopicDataMap,
+        root: &'a ArcTopicData,
         full_path: &'a String,
         segment: &'a FuncDataPath,
+        with_context: bool,
+        sequences: Option<&'a TenantSequences>,
     ) -> Self {
         VariablePredefineFunctionCaller {
             root,
             full_path,
             segment,
+            with_context,
+            sequences,
         }
     }
 
@@ -40,45 +53,238 @@ impl<'a> VariablePredefineFunctionCaller<'a> {
         ))
     }
 
+    fn err_param_not_supported(&self, param_index: usize) -> StdErr {
+        PipelineKernelErrorCode::VariableFuncParamNotSupported.err_with_msg(format!(
+            "Cannot retrieve[key={}, current={}] from [{:?}], caused by parameter[index={}] not supported.",
+            self.full_path, self.segment.path, self.root, param_index
+        ))
+    }
+
+    /// value of given param, which refers to data of root
+    fn data_of_param(&self, param: &FuncDataPathParam) -> StdR<Arc<ArcTopicDataValue>> {
+        match param {
+            FuncDataPathParam::Value(value) => Ok(Arc::new(match &value.value {
+                FuncParamValue::Str(str) => ArcTopicDataValue::Str(Arc::new(str.clone())),
+                FuncParamValue::Num(decimal) => ArcTopicDataValue::Num(Arc::new(decimal.clone())),
+                FuncParamValue::Bool(bool) => ArcTopicDataValue::Bool(*bool),
+                FuncParamValue::DateTime(datetime) => {
+                    ArcTopicDataValue::DateTime(Arc::new(*datetime))
+                }
+                FuncParamValue::Date(date) => ArcTopicDataValue::Date(Arc::new(*date)),
+                FuncParamValue::Time(time) => ArcTopicDataValue::Time(Arc::new(*time)),
+                FuncParamValue::None => ArcTopicDataValue::None,
+            })),
+            FuncDataPathParam::Plain(plain) => self.root.value_of_plain_segment(
+                &Arc::new(ArcTopicDataValue::Map(self.root.clone())),
+                plain,
+                self.full_path,
+            ),
+            FuncDataPathParam::Func(func) => VariablePredefineFunctionCaller::prepare(
+                self.root,
+                self.full_path,
+                func,
+                false,
+                self.sequences,
+            )
+            .value_of(&Arc::new(ArcTopicDataValue::Map(self.root.clone()))),
+            FuncDataPathParam::Path(path) => {
+                self.root.value_of_path_in_tenant(path, self.sequences)
+            }
+        }
+    }
+
+    /// value of given param, plain param is a literal string (escaped and trimmed),
+    /// use [{x}] to refer to data of root
+    fn value_of_param(&self, param: &FuncDataPathParam) -> StdR<Arc<ArcTopicDataValue>> {
+        match param {
+            FuncDataPathParam::Plain(plain) => Ok(Arc::new(ArcTopicDataValue::Str(Arc::new(
+                PathStr::unescape(plain.path.to_string().trim()),
+            )))),
+            _ => self.data_of_param(param),
+        }
+    }
+
+    /// get context and parameters of function.
+    /// when there is no context, and function requires context, the first parameter is the context.
+    fn context_and_params(
+        &self,
+        value: &Arc<ArcTopicDataValue>,
+    ) -> StdR<(Arc<ArcTopicDataValue>, Vec<Arc<ArcTopicDataValue>>)> {
+        let params: Vec<&FuncDataPathParam> = match &self.segment.params {
+            Some(params) => params.iter().collect(),
+            _ => vec![],
+        };
+        if self.segment.is_literal_concat() {
+            // all parameters are the parts of string, no context
+            let mut values = vec![];
+            for param in params {
+                values.push(self.data_of_param(param)?);
+            }
+            return Ok((Arc::new(ArcTopicDataValue::None), values));
+        }
+
+        let (context, params) =
+            if !self.with_context && self.segment.func.require_context() && !params.is_empty() {
+                (self.data_of_param(params[0])?, &params[1..])
+            } else {
+                (value.clone(), &params[..])
+            };
+        let mut values = vec![];
+        for param in params {
+            values.push(self.value_of_param(param)?);
+        }
+        Ok((context, values))
+    }
+
+    /// get string of parameter at given index, none when parameter not exists or is none
+    fn str_param(&self, params: &[Arc<ArcTopicDataValue>], index: usize) -> StdR<Option<String>> {
+        match params.get(index) {
+            Some(param) => param.try_to_str(|| self.err_param_not_supported(index)),
+            _ => Ok(None),
+        }
+    }
+
+    /// get date (or datetime) of parameter at given index, none when parameter not exists or is none
+    fn date_param(
+        &self,
+        params: &[Arc<ArcTopicDataValue>],
+        index: usize,
+    ) -> StdR<Option<NaiveDateTime>> {
+        match params.get(index) {
+            Some(param) => Ok(param
+                .try_to_date_or_datetime(|| self.err_param_not_supported(index))?
+                .map(|(datetime, _)| datetime)),
+            _ => Ok(None),
+        }
+    }
+
+    /// get strings of parameters from given index
+    fn str_params_from(
+        &self,
+        params: &[Arc<ArcTopicDataValue>],
+        start_index: usize,
+    ) -> StdR<Vec<Option<String>>> {
+        let mut values = vec![];
+        for index in start_index..params.len() {
+            values.push(self.str_param(params, index)?);
+        }
+        Ok(values)
+    }
+
+    /// get non-negative integer of parameter at given index,
+    /// none when parameter not exists, is none or blank string
+    fn index_param(&self, params: &[Arc<ArcTopicDataValue>], index: usize) -> StdR<Option<usize>> {
+        match params.get(index) {
+            Some(param) if !param.is_none_or_empty_str() => param
+                .try_to_decimal()
+                .ok()
+                .and_then(|decimal| {
+                    if decimal.is_integer() {
+                        decimal.to_usize()
+                    } else {
+                        None
+                    }
+                })
+                .map(Some)
+                .ok_or_else(|| self.err_param_not_supported(index)),
+            _ => Ok(None),
+        }
+    }
+
+    /// next value of named sequence, tenant is required
+    fn next_seq_of(&self, name: &str) -> StdR<Arc<ArcTopicDataValue>> {
+        match self.sequences {
+            Some(sequences) => {
+                let value = sequences.next_value(name)?;
+                Ok(Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(
+                    value,
+                )))))
+            }
+            _ => PipelineKernelErrorCode::SequenceTenantMissed.msg(format!(
+                "Cannot retrieve[key={}, current={}] from [{:?}], caused by tenant of sequence[{}] is missed.",
+                self.full_path, self.segment.path, self.root, name
+            )),
+        }
+    }
+
     pub fn value_of(&self, value: &Arc<ArcTopicDataValue>) -> StdR<Arc<ArcTopicDataValue>> {
         let decimal_parse_err = || self.decimal_parse_error();
         let not_support = || self.function_not_supported();
         let not_support_e = || self.err_function_not_supported();
+        let (context, params) = self.context_and_params(value)?;
+        let value = &context;
+        let params = &params;
 
         match self.segment.func {
-            VariablePredefineFunctions::NextSeq => todo!("variable predefine function[&nextSeq]"),
+            VariablePredefineFunctions::NextSeq => match self.str_param(params, 0)? {
+                Some(name) => self.next_seq_of(&name),
+                _ => Ok(Arc::new(ArcTopicDataValue::Str(Arc::new(
+                    IdGen::next_id()?.to_string(),
+                )))),
+            },
             VariablePredefineFunctions::Count => value.count(decimal_parse_err, not_support),
             VariablePredefineFunctions::Length | VariablePredefineFunctions::Len => {
                 value.length(decimal_parse_err, not_support)
             }
-            VariablePredefineFunctions::Slice | VariablePredefineFunctions::Substr => {
-                todo!("variable predefine function[&slice], [&substr]")
-            }
-            VariablePredefineFunctions::Find | VariablePredefineFunctions::Index => {
-                todo!("variable predefine function[&find], [&index]")
-            }
+            VariablePredefineFunctions::Slice | VariablePredefineFunctions::Substr => value.slice(
+                self.index_param(params, 0)?.unwrap_or(0),
+                self.index_param(params, 1)?,
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Find | VariablePredefineFunctions::Index => value.find(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                not_support_e,
+            ),
             VariablePredefineFunctions::StartsWith | VariablePredefineFunctions::Startswith => {
-                todo!("variable predefine function[&startsWith], [&startswith]")
-            }
-            VariablePredefineFunctions::EndsWith | VariablePredefineFunctions::Endswith => {
-                todo!("variable predefine function[&endsWith], [&endswith]")
+                value.starts_with(
+                    &self.str_param(params, 0)?.unwrap_or_default(),
+                    not_support_e,
+                )
             }
+            VariablePredefineFunctions::EndsWith | VariablePredefineFunctions::Endswith => value
+                .ends_with(
+                    &self.str_param(params, 0)?.unwrap_or_default(),
+                    not_support_e,
+                ),
             VariablePredefineFunctions::Strip | VariablePredefineFunctions::Trim => {
-                todo!("variable predefine function[&strip], [$trim]")
-            }
-            VariablePredefineFunctions::Replace => todo!("variable predefine function[&replace]"),
-            VariablePredefineFunctions::ReplaceFirst => {
-                todo!("variable predefine function[&replaceFirst]")
+                value.strip(self.str_param(params, 0)?.as_deref(), not_support_e)
             }
-            VariablePredefineFunctions::Upper => todo!("variable predefine function[&upper]"),
-            VariablePredefineFunctions::Lower => todo!("variable predefine function[&lower]"),
-            VariablePredefineFunctions::Contains => todo!("variable predefine function[&contains]"),
-            VariablePredefineFunctions::Split => todo!("variable predefine function[&split]"),
-            VariablePredefineFunctions::Concat => todo!("variable predefine function[&concat]"),
-            VariablePredefineFunctions::ConcatWith => {
-                todo!("variable predefine function[&concatWith]")
+            VariablePredefineFunctions::Replace => value.replace(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                &self.str_param(params, 1)?.unwrap_or_default(),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::ReplaceFirst => value.replace_first(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                &self.str_param(params, 1)?.unwrap_or_default(),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Upper => value.upper(not_support_e),
+            VariablePredefineFunctions::Lower => value.lower(not_support_e),
+            VariablePredefineFunctions::Contains => value.contains_str(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Split => value.split(
+                &self
+                    .str_param(params, 0)?
+                    .unwrap_or_else(|| String::from(",")),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Concat => {
+                value.concat(&self.str_params_from(params, 0)?, not_support_e)
             }
-            VariablePredefineFunctions::Join => value.join(",", not_support),
+            VariablePredefineFunctions::ConcatWith => value.concat_with(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                &self.str_params_from(params, 1)?,
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Join => value.join(
+                &self
+                    .str_param(params, 0)?
+                    .unwrap_or_else(|| String::from(",")),
+                not_support,
+            ),
             VariablePredefineFunctions::Distinct => value.distinct(not_support),
             VariablePredefineFunctions::Sum => value.sum(not_support),
             VariablePredefineFunctions::Avg => value.avg(not_support),
@@ -102,16 +308,195 @@ impl<'a> VariablePredefineFunctionCaller<'a> {
             VariablePredefineFunctions::FromPreviousTriggerData => {
                 todo!("variable predefine function[&old]")
             }
-            VariablePredefineFunctions::DayDiff => todo!("variable predefine function[&dayDiff]"),
+            VariablePredefineFunctions::DayDiff => {
+                value.day_diff(self.date_param(params, 0)?.as_ref(), not_support_e)
+            }
             VariablePredefineFunctions::MonthDiff => {
-                todo!("variable predefine function[&monthDiff]")
+                value.month_diff(self.date_param(params, 0)?.as_ref(), not_support_e)
             }
-            VariablePredefineFunctions::YearDiff => todo!("variable predefine function[&yearDiff]"),
-            VariablePredefineFunctions::MoveDate => todo!("variable predefine function[&moveDate]"),
-            VariablePredefineFunctions::DateFormat => {
-                todo!("variable predefine function[&fmtDate]")
+            VariablePredefineFunctions::YearDiff => {
+                value.year_diff(self.date_param(params, 0)?.as_ref(), not_support_e)
             }
-            VariablePredefineFunctions::Now => todo!("variable predefine function[&now]"),
+            VariablePredefineFunctions::MoveDate => value.move_date(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::DateFormat => value.date_format(
+                &self.str_param(params, 0)?.unwrap_or_default(),
+                not_support_e,
+            ),
+            VariablePredefineFunctions::Now => Ok(Arc::new(ArcTopicDataValue::DateTime(Arc::new(
+                Clocks::now()?,
+            )))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcTopicDataValue, Clocks, DataPath, DataVisitor, FixedClock, InMemorySequenceStore,
+        PipelineExecutionVariables, TenantSequences,
+    };
+    use bigdecimal::BigDecimal;
+    use chrono::NaiveDate;
+    use elf_runtime_model_kernel::{IdGen, IdGenerator, SnowflakeIdGenerator};
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    fn str(value: &str) -> Arc<ArcTopicDataValue> {
+        Arc::new(ArcTopicDataValue::Str(Arc::new(value.to_string())))
+    }
+
+    fn value_of(path: &str) -> Arc<ArcTopicDataValue> {
+        let data = Arc::new(HashMap::from([
+            ("name".to_string(), str(" Abc-Def ")),
+            ("sep".to_string(), str("-")),
+            ("birth".to_string(), str("2000-01-31")),
+            ("code".to_string(), str("x.y,z")),
+            (
+                "items".to_string(),
+                Arc::new(ArcTopicDataValue::Vec(Arc::new(vec![str("a"), str("b")]))),
+            ),
+            (
+                "order".to_string(),
+                Arc::new(ArcTopicDataValue::Map(Arc::new(HashMap::from([
+                    ("-1".to_string(), str("o1")),
+                    (
+                        "amount".to_string(),
+                        Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(12345)))),
+                    ),
+                ])))),
+            ),
+        ]));
+        data.value_of(&DataPath::from_str(path).unwrap()).unwrap()
+    }
+
+    fn assert_str(path: &str, expected: &str) {
+        let value = value_of(path);
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == expected),
+            "[{}] expected [{}], got [{:?}]",
+            path,
+            expected,
+            value
+        );
+    }
+
+    fn assert_num(path: &str, expected: i32) {
+        let value = value_of(path);
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from(expected)),
+            "[{}] expected [{}], got [{:?}]",
+            path,
+            expected,
+            value
+        );
+    }
+
+    fn assert_bool(path: &str, expected: bool) {
+        let value = value_of(path);
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Bool(b) if *b == expected),
+            "[{}] expected [{}], got [{:?}]",
+            path,
+            expected,
+            value
+        );
+    }
+
+    #[test]
+    fn test_str_functions() {
+        assert_str("name.&trim", "Abc-Def");
+        assert_str("&strip(name)", "Abc-Def");
+        assert_str("name.&trim.&slice(1, 3)", "bc");
+        assert_str("name.&trim.&substr(, 3)", "Abc");
+        assert_str("order.amount.&slice(2)", "345");
+        assert_num("name.&find(D)", 5);
+        assert_num("&index(name, x)", -1);
+        assert_bool("name.&trim.&startsWith(Abc)", true);
+        assert_bool("name.&trim.&endswith(Abc)", false);
+        assert_str("name.&replace(-, +)", " Abc+Def ");
+        assert_str("name.&replaceFirst( , _)", "_Abc-Def ");
+        assert_str("name.&trim.&upper", "ABC-DEF");
+        assert_str("&lower(name)", " abc-def ");
+        assert_bool("name.&contains(c-D)", true);
+        assert_str("name.&trim.&split({sep}).&join(+)", "Abc+Def");
+        assert_str("name.&trim.&concat(!,{sep})", "Abc-Def!-");
+        assert_str("&concatWith(items,{sep}, c).&join", "a-c,b-c");
+        // escape chars in parameter
+        assert_str("code.&replace(\\., \\,)", "x,y,z");
+        assert_str("code.&split(\\,).&join(\\t)", "x.y\tz");
+        // literal concat, as a string or as a property name
+        assert_str("a{sep}b", "a-b");
+        assert_str("order.{sep}1", "o1");
+        // element-wise
+        assert_str("items.&upper.&join", "A,B");
+    }
+
+    #[test]
+    fn test_date_functions() {
+        assert_num("birth.&dayDiff(2000-01-01)", 30);
+        assert_num("&monthDiff(birth, 2000-03-01)", -1);
+        assert_num("birth.&yearDiff(1990-02-01)", 9);
+        assert_str("birth.&moveDate(M+1 D-1).&fmtDate(%Y/%M/%D)", "2000/02/28");
+        assert_str("birth.&moveDate(h=8).&fmtDate(%D %H%p)", "31 08AM");
+
+        let now = NaiveDate::from_ymd_opt(2020, 2, 29)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        Clocks::set(Arc::new(FixedClock::new(now))).unwrap();
+        assert_num("&now.&dayDiff({birth})", 7334);
+        assert_num("&yearDiff(&now, {birth})", 20);
+    }
+
+    #[test]
+    fn test_next_seq() {
+        let generator = SnowflakeIdGenerator::new(1).unwrap();
+        IdGen::set(Box::new(generator) as Box<dyn IdGenerator>).unwrap();
+        let id = value_of("&nextSeq");
+        assert!(
+            matches!(id.deref(), ArcTopicDataValue::Str(s) if s.chars().all(|c| c.is_ascii_digit())),
+            "got [{:?}]",
+            id
+        );
+
+        let data = Arc::new(HashMap::new());
+        let variables = PipelineExecutionVariables::new(None, Some(data.clone())).in_tenant(
+            Arc::new(TenantSequences {
+                tenant_id: Arc::new("Tenant-Seq".to_string()),
+                store: Some(Arc::new(InMemorySequenceStore::new())),
+            }),
+        );
+        let next = |path: &str| {
+            variables
+                .value_of(&DataPath::from_str(path).unwrap())
+                .unwrap()
+        };
+        assert!(
+            matches!(next("&nextSeq(order)").deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from(1))
+        );
+        assert!(
+            matches!(next("ORD-{&nextSeq(order)}").deref(), ArcTopicDataValue::Str(s) if s.as_str() == "ORD-2")
+        );
+        // named sequence requires tenant
+        assert!(
+            data.value_of(&DataPath::from_str("&nextSeq(order)").unwrap())
+                .is_err()
+        );
+        // no default store, global store is not set in tests
+        let variables = PipelineExecutionVariables::new(None, Some(data)).in_tenant(Arc::new(
+            TenantSequences {
+                tenant_id: Arc::new("Tenant-Seq".to_string()),
+                store: None,
+            },
+        ));
+        let error = variables
+            .value_of(&DataPath::from_str("&nextSeq(order)").unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(error.code, "PLKN-00113");
+    }
+}
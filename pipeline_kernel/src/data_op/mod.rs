@@ -7,7 +7,9 @@ mod func_data_visitor;
 mod minmax;
 mod parser;
 mod topic_data_value;
+mod topic_data_value_date_functions;
 mod topic_data_value_functions;
+mod topic_data_value_str_functions;
 
 pub use data_path::*;
 pub use data_visitor::*;
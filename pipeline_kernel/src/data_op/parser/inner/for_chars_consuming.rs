@@ -20,22 +20,25 @@ impl ParserInnerState {
     /// otherwise append char '\' to given str, move char index to index after [\].
     pub fn consume_potential_escape_char(&mut self) {
         // current char index is point to the char "\", move to next
+        let index_of_backslash = self.current_char_index();
         self.move_char_index_to_next();
 
         if let Some(next_c) = self.current_char() {
-            match next_c {
-                '.' | ',' | '(' | ')' | '{' | '}' | '&' => {
-                    self.consume_char_into_memory_and_move_char_index_to_next(*next_c);
-                }
-                't' => self.consume_char_into_memory_and_move_char_index_to_next('\t'),
-                'r' => self.consume_char_into_memory_and_move_char_index_to_next('\r'),
-                'n' => self.consume_char_into_memory_and_move_char_index_to_next('\n'),
-                _ => {
-                    self.consume_char_into_memory_and_keep_char_index('\\');
-                }
+            let escaped = match next_c {
+                '.' | ',' | '(' | ')' | '{' | '}' | '&' => Some(*next_c),
+                't' => Some('\t'),
+                'r' => Some('\r'),
+                'n' => Some('\n'),
+                _ => None,
+            };
+            if let Some(escaped) = escaped {
+                self.collect_char_into_memory_from(escaped, index_of_backslash);
+                self.move_char_index_to_next();
+            } else {
+                self.collect_char_into_memory_from('\\', index_of_backslash);
             }
         } else {
-            self.consume_char_into_memory_and_keep_char_index('\\');
+            self.collect_char_into_memory_from('\\', index_of_backslash);
         }
     }
 }
@@ -8,6 +8,9 @@ pub struct ParserInnerState {
     char_index: usize,
     /// in-memory chars, not consumed yet
     in_memory_chars: String,
+    /// char index of the first in-memory char in full path,
+    /// escaped chars take 2 chars in full path but 1 char in memory
+    in_memory_chars_start_index: usize,
 }
 
 /// create
@@ -18,6 +21,7 @@ impl ParserInnerState {
             all_chars,
             char_index: 0,
             in_memory_chars: String::new(),
+            in_memory_chars_start_index: 0,
         }
     }
 
@@ -27,6 +31,7 @@ impl ParserInnerState {
             all_chars: other.all_chars.clone(),
             char_index: other.char_index,
             in_memory_chars: String::new(),
+            in_memory_chars_start_index: other.char_index,
         }
     }
 
@@ -44,6 +49,7 @@ impl ParserInnerState {
                 other.clear_in_memory_chars();
                 chars
             },
+            in_memory_chars_start_index: other.in_memory_chars_start_index,
         }
     }
 
@@ -53,6 +59,7 @@ impl ParserInnerState {
             all_chars: other.all_chars.clone(),
             char_index: other.char_index + 1,
             in_memory_chars: String::new(),
+            in_memory_chars_start_index: other.char_index + 1,
         }
     }
 }
@@ -160,7 +167,26 @@ impl ParserInnerState {
         self.in_memory_chars.clone()
     }
 
+    /// get char index of the first in-memory char in full path,
+    /// or current char index when there is no in-memory char
+    pub fn in_memory_chars_start_index(&self) -> usize {
+        if self.in_memory_chars.is_empty() {
+            self.char_index
+        } else {
+            self.in_memory_chars_start_index
+        }
+    }
+
     pub fn collect_char_into_memory(&mut self, char: char) {
+        self.collect_char_into_memory_from(char, self.char_index)
+    }
+
+    /// append given char to in-memory chars,
+    /// given char index is where the char starts in full path, used when it is the first in-memory char
+    pub fn collect_char_into_memory_from(&mut self, char: char, char_index: usize) {
+        if self.in_memory_chars.is_empty() {
+            self.in_memory_chars_start_index = char_index;
+        }
         self.in_memory_chars.push(char)
     }
 
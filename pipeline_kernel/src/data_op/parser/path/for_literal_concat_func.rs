@@ -15,8 +15,7 @@ impl PathParser {
     fn get_index_of_char_before_literal_concat_function(&self) -> i64 {
         if self.inner.in_memory_chars_is_not_empty() {
             // get char before in-memory chars
-            self.inner
-                .char_index_before_current(self.inner.in_memory_chars_count() + 1)
+            self.inner.in_memory_chars_start_index() as i64 - 1
         } else {
             // get char before "{"
             self.inner.previous_char_index()
@@ -107,12 +106,8 @@ impl PathParser {
         if self.inner.in_memory_chars_is_not_empty() {
             // create a value path
             let value = FuncParamValue::Str(self.inner.clone_in_memory_chars());
-            let start_char_index = self
-                .inner
-                .char_index_before_current(self.inner.in_memory_chars_count())
-                as usize;
             params.push(FuncDataPathParam::Value(FuncParamValuePath {
-                path: self.inner.create_path_str_exclude_current(start_char_index),
+                path: self.inner.create_path_str_of_in_memory_chars(),
                 value,
             }));
             self.inner.clear_in_memory_chars();
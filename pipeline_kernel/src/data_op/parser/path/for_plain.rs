@@ -1,5 +1,5 @@
 use crate::{DataPathSegment, PathParser, PlainDataPath};
-use elf_base::VoidR;
+use elf_base::{StringUtils, VoidR};
 
 /// consume plain path
 impl PathParser {
@@ -11,7 +11,13 @@ impl PathParser {
         &mut self,
         move_char_index_to_next: bool,
     ) -> VoidR {
-        if self.inner.in_memory_chars_is_blank() {
+        // escaped whitespaces are not blank
+        if self
+            .inner
+            .create_path_str_of_in_memory_chars()
+            .to_string()
+            .is_blank()
+        {
             return self.incorrect_blank_segment();
         }
 
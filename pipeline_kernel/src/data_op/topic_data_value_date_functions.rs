@@ -0,0 +1,519 @@
+use crate::{ArcTopicDataValue, PipelineKernelErrorCode};
+use bigdecimal::BigDecimal;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use elf_base::{DateTimeUtils, ErrorCode, StdErr, StdR};
+use std::sync::Arc;
+
+enum DateMoveKind {
+    Set,
+    Plus,
+    Minus,
+}
+
+/// single movement of [moveDate], e.g. [Y2000], [M+1], [D-1], [h=23]
+struct DateMovement {
+    unit: char,
+    kind: DateMoveKind,
+    value: u32,
+}
+
+impl DateMovement {
+    fn incorrect<R>(movement: &str) -> StdR<R> {
+        PipelineKernelErrorCode::IncorrectDateMovement
+            .msg(format!("Date movement[{}] is incorrect.", movement))
+    }
+
+    fn out_of_range<R>(movement: &str, datetime: &NaiveDateTime) -> StdR<R> {
+        PipelineKernelErrorCode::IncorrectDateMovement.msg(format!(
+            "Date movement[{}] on [{}] is out of range.",
+            movement, datetime
+        ))
+    }
+
+    /// parse movements, unit is one of [YMDhms],
+    /// followed by an optional [+], [-] or [=], and digits.
+    /// whitespaces between parts are ignored.
+    fn parse(movement: &str) -> StdR<Vec<DateMovement>> {
+        let mut movements = vec![];
+        let mut chars = movement.chars().filter(|c| !c.is_whitespace()).peekable();
+        while let Some(unit) = chars.next() {
+            if !"YMDhms".contains(unit) {
+                return Self::incorrect(movement);
+            }
+            let kind = match chars.peek() {
+                Some('+') => DateMoveKind::Plus,
+                Some('-') => DateMoveKind::Minus,
+                _ => DateMoveKind::Set,
+            };
+            if matches!(chars.peek(), Some('+' | '-' | '=')) {
+                chars.next();
+            }
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            match digits.parse::<u32>() {
+                Ok(value) => movements.push(DateMovement { unit, kind, value }),
+                _ => return Self::incorrect(movement),
+            }
+        }
+        if movements.is_empty() {
+            Self::incorrect(movement)
+        } else {
+            Ok(movements)
+        }
+    }
+
+    fn moves_time(&self) -> bool {
+        matches!(self.unit, 'h' | 'm' | 's')
+    }
+
+    fn days_of_month(year: i32, month: u32) -> u32 {
+        let (next_year, next_month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+        NaiveDate::from_ymd_opt(next_year, next_month, 1)
+            .and_then(|date| date.pred_opt())
+            .map(|date| date.day())
+            .unwrap_or(31)
+    }
+
+    /// set year/month/day, day is normalized to end of month when exceeds
+    fn with_ymd(
+        datetime: &NaiveDateTime,
+        year: i32,
+        month: u32,
+        day: u32,
+    ) -> Option<NaiveDateTime> {
+        let day = day.min(Self::days_of_month(year, month));
+        NaiveDate::from_ymd_opt(year, month, day).map(|date| date.and_time(datetime.time()))
+    }
+
+    fn apply(&self, datetime: &NaiveDateTime) -> Option<NaiveDateTime> {
+        let (year, month, day) = (datetime.year(), datetime.month(), datetime.day());
+        match (&self.kind, self.unit) {
+            (DateMoveKind::Set, 'Y') => Self::with_ymd(datetime, self.value as i32, month, day),
+            (DateMoveKind::Set, 'M') => {
+                Self::with_ymd(datetime, year, self.value.clamp(1, 12), day)
+            }
+            (DateMoveKind::Set, 'D') => {
+                let days = Self::days_of_month(year, month);
+                let day = if self.value == 99 {
+                    days
+                } else {
+                    self.value.clamp(1, days)
+                };
+                Self::with_ymd(datetime, year, month, day)
+            }
+            (DateMoveKind::Set, 'h') => datetime.with_hour(self.value.min(23)),
+            (DateMoveKind::Set, 'm') => datetime.with_minute(self.value.min(59)),
+            (DateMoveKind::Set, 's') => datetime.with_second(self.value.min(59)),
+            (DateMoveKind::Plus, 'Y') => datetime.checked_add_months(Months::new(self.value * 12)),
+            (DateMoveKind::Minus, 'Y') => datetime.checked_sub_months(Months::new(self.value * 12)),
+            (DateMoveKind::Plus, 'M') => datetime.checked_add_months(Months::new(self.value)),
+            (DateMoveKind::Minus, 'M') => datetime.checked_sub_months(Months::new(self.value)),
+            (DateMoveKind::Plus, 'D') => datetime.checked_add_days(Days::new(self.value as u64)),
+            (DateMoveKind::Minus, 'D') => datetime.checked_sub_days(Days::new(self.value as u64)),
+            (kind, unit) => {
+                let delta = match unit {
+                    'h' => TimeDelta::try_hours(self.value as i64),
+                    'm' => TimeDelta::try_minutes(self.value as i64),
+                    _ => TimeDelta::try_seconds(self.value as i64),
+                }?;
+                match kind {
+                    DateMoveKind::Plus => datetime.checked_add_signed(delta),
+                    _ => datetime.checked_sub_signed(delta),
+                }
+            }
+        }
+    }
+}
+
+/// date functions, applied on
+/// - date/datetime, or string which can be parsed as date/datetime by loose formats,
+/// - none or blank string, result is none,
+/// - vec, applied on each element, and returns a vec,
+/// - others are not supported.
+impl ArcTopicDataValue {
+    /// convert to datetime, returns none when self is none or blank string.
+    /// the second of tuple is true when self is a date (no time part),
+    /// string is a date when it can be parsed by date formats.
+    /// vec and map are not supported
+    pub fn try_to_date_or_datetime<NotSupport>(
+        &self,
+        not_support: NotSupport,
+    ) -> StdR<Option<(NaiveDateTime, bool)>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        match self {
+            Self::Date(date) => Ok(Some((date.and_time(Default::default()), true))),
+            Self::DateTime(datetime) => Ok(Some((**datetime, false))),
+            Self::Str(str) if str.trim().is_empty() => Ok(None),
+            Self::Str(str) => {
+                let str = str.trim().to_string();
+                if let Ok(date) = str.to_date() {
+                    Ok(Some((date.and_time(Default::default()), true)))
+                } else if let Ok(datetime) = str.to_datetime_loose() {
+                    Ok(Some((datetime, false)))
+                } else {
+                    Err(not_support())
+                }
+            }
+            Self::None => Ok(None),
+            _ => Err(not_support()),
+        }
+    }
+
+    fn map_date<OnDate, NotSupport>(
+        &self,
+        on_date: &OnDate,
+        not_support: &NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        OnDate: Fn(&NaiveDateTime, bool) -> StdR<ArcTopicDataValue>,
+        NotSupport: Fn() -> StdErr,
+    {
+        match self {
+            Self::Vec(vec) => {
+                let mut values = vec![];
+                for value in vec.iter() {
+                    values.push(value.map_date(on_date, not_support)?);
+                }
+                Ok(Arc::new(Self::Vec(Arc::new(values))))
+            }
+            _ => match self.try_to_date_or_datetime(not_support)? {
+                Some((datetime, is_date)) => Ok(Arc::new(on_date(&datetime, is_date)?)),
+                None => Ok(Arc::new(Self::None)),
+            },
+        }
+    }
+
+    fn is_end_of_month(date: &NaiveDate) -> bool {
+        date.day() == DateMovement::days_of_month(date.year(), date.month())
+    }
+
+    /// full months from start to end, end of month is treated as full month.
+    /// e.g. from 2000-01-31 to 2000-02-29 is 1 month.
+    fn months_between(end: &NaiveDate, start: &NaiveDate) -> i64 {
+        let months =
+            (end.year() - start.year()) as i64 * 12 + end.month() as i64 - start.month() as i64;
+        if months > 0 && end.day() < start.day() && !Self::is_end_of_month(end) {
+            months - 1
+        } else if months < 0 && end.day() > start.day() && !Self::is_end_of_month(start) {
+            months + 1
+        } else {
+            months
+        }
+    }
+
+    fn of_diff(diff: i64) -> ArcTopicDataValue {
+        Self::Num(Arc::new(BigDecimal::from(diff)))
+    }
+
+    /// days from other to self, time part is ignored.
+    /// result is none when other is none
+    pub fn day_diff<NotSupport>(
+        &self,
+        other: Option<&NaiveDateTime>,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_date(
+            &|datetime, _| {
+                Ok(match other {
+                    Some(other) => Self::of_diff((datetime.date() - other.date()).num_days()),
+                    None => Self::None,
+                })
+            },
+            &not_support,
+        )
+    }
+
+    /// full months from other to self, time part is ignored.
+    /// result is none when other is none
+    pub fn month_diff<NotSupport>(
+        &self,
+        other: Option<&NaiveDateTime>,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_date(
+            &|datetime, _| {
+                Ok(match other {
+                    Some(other) => {
+                        Self::of_diff(Self::months_between(&datetime.date(), &other.date()))
+                    }
+                    None => Self::None,
+                })
+            },
+            &not_support,
+        )
+    }
+
+    /// full years from other to self, time part is ignored.
+    /// result is none when other is none
+    pub fn year_diff<NotSupport>(
+        &self,
+        other: Option<&NaiveDateTime>,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_date(
+            &|datetime, _| {
+                Ok(match other {
+                    Some(other) => {
+                        Self::of_diff(Self::months_between(&datetime.date(), &other.date()) / 12)
+                    }
+                    None => Self::None,
+                })
+            },
+            &not_support,
+        )
+    }
+
+    /// move date by given movement, see [elf_model::VariablePredefineFunctions::MoveDate].
+    /// date is upgraded to datetime when time moved
+    pub fn move_date<NotSupport>(
+        &self,
+        movement: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        let movements = DateMovement::parse(movement)?;
+        let moves_time = movements.iter().any(DateMovement::moves_time);
+        self.map_date(
+            &|datetime, is_date| {
+                let mut moved = *datetime;
+                for m in &movements {
+                    moved = match m.apply(&moved) {
+                        Some(moved) => moved,
+                        None => return DateMovement::out_of_range(movement, datetime),
+                    };
+                }
+                if is_date && !moves_time {
+                    Ok(Self::Date(Arc::new(moved.date())))
+                } else {
+                    Ok(Self::DateTime(Arc::new(moved)))
+                }
+            },
+            &not_support,
+        )
+    }
+
+    /// translate format to chrono format, [%] follows one of [YyMDhHmsWwBbp].
+    /// [%%] is [%], any other chars are kept as they are.
+    fn translate_date_format(format: &str) -> String {
+        let mut translated = String::new();
+        let mut chars = format.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                translated.push(char);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => translated.push_str("%Y"),
+                Some('y') => translated.push_str("%y"),
+                Some('M') => translated.push_str("%m"),
+                Some('D') => translated.push_str("%d"),
+                Some('h') => translated.push_str("%H"),
+                Some('H') => translated.push_str("%I"),
+                Some('m') => translated.push_str("%M"),
+                Some('s') => translated.push_str("%S"),
+                Some('W') => translated.push_str("%A"),
+                Some('w') => translated.push_str("%a"),
+                Some('B') => translated.push_str("%B"),
+                Some('b') => translated.push_str("%b"),
+                Some('p') => translated.push_str("%p"),
+                Some('%') => translated.push_str("%%"),
+                Some(other) => {
+                    translated.push_str("%%");
+                    translated.push(other);
+                }
+                None => translated.push_str("%%"),
+            }
+        }
+        translated
+    }
+
+    /// format date to string, see [elf_model::VariablePredefineFunctions::DateFormat].
+    /// time of date is [00:00:00]
+    pub fn date_format<NotSupport>(
+        &self,
+        format: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        let format = Self::translate_date_format(format);
+        self.map_date(
+            &|datetime, _| Ok(Self::Str(Arc::new(datetime.format(&format).to_string()))),
+            &not_support,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcTopicDataValue, PipelineKernelErrorCode};
+    use bigdecimal::BigDecimal;
+    use chrono::{NaiveDate, NaiveDateTime};
+    use elf_base::{ErrorCode, StdErr};
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    fn str(value: &str) -> ArcTopicDataValue {
+        ArcTopicDataValue::Str(Arc::new(value.to_string()))
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> ArcTopicDataValue {
+        ArcTopicDataValue::Date(Arc::new(NaiveDate::from_ymd_opt(year, month, day).unwrap()))
+    }
+
+    fn datetime(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn not_support() -> StdErr {
+        PipelineKernelErrorCode::VariableFuncNotSupported.err_with_msg("not supported")
+    }
+
+    fn assert_num(value: Arc<ArcTopicDataValue>, expected: i32) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from(expected)),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    fn assert_str(value: Arc<ArcTopicDataValue>, expected: &str) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == expected),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let start = datetime("2000-01-31 12:00:00");
+        assert_num(
+            date(2000, 3, 1)
+                .day_diff(Some(&start), not_support)
+                .unwrap(),
+            30,
+        );
+        assert_num(
+            str("2000-01-01")
+                .day_diff(Some(&start), not_support)
+                .unwrap(),
+            -30,
+        );
+        assert_num(
+            date(2000, 2, 29)
+                .month_diff(Some(&start), not_support)
+                .unwrap(),
+            1,
+        );
+        assert_num(
+            date(2000, 2, 28)
+                .month_diff(Some(&start), not_support)
+                .unwrap(),
+            0,
+        );
+        assert_num(
+            date(1999, 12, 31)
+                .month_diff(Some(&start), not_support)
+                .unwrap(),
+            -1,
+        );
+        assert_num(
+            date(2001, 1, 30)
+                .year_diff(Some(&start), not_support)
+                .unwrap(),
+            0,
+        );
+        assert_num(
+            date(2001, 1, 31)
+                .year_diff(Some(&start), not_support)
+                .unwrap(),
+            1,
+        );
+        assert_num(
+            str("1998-01-31")
+                .year_diff(Some(&start), not_support)
+                .unwrap(),
+            -2,
+        );
+        assert!(
+            date(2000, 1, 1)
+                .day_diff(None, not_support)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            ArcTopicDataValue::None
+                .day_diff(Some(&start), not_support)
+                .unwrap()
+                .is_none()
+        );
+        assert!(str("x").day_diff(Some(&start), not_support).is_err());
+    }
+
+    #[test]
+    fn test_move_date() {
+        let moved = date(1999, 11, 30)
+            .move_date("Y2000M+1D-1h23m+5s-6", not_support)
+            .unwrap();
+        assert!(
+            matches!(moved.deref(), ArcTopicDataValue::DateTime(dt) if dt.deref() == &datetime("2000-12-29 23:04:54"))
+        );
+        let moved = date(2000, 1, 31).move_date("M=2 D99", not_support).unwrap();
+        assert!(
+            matches!(moved.deref(), ArcTopicDataValue::Date(d) if d.deref() == &NaiveDate::from_ymd_opt(2000, 2, 29).unwrap())
+        );
+        let moved = str("2000-02-29 10:00:00")
+            .move_date("Y+1 M-2 D=0", not_support)
+            .unwrap();
+        assert!(
+            matches!(moved.deref(), ArcTopicDataValue::DateTime(dt) if dt.deref() == &datetime("2000-12-01 10:00:00"))
+        );
+        assert!(date(2000, 1, 1).move_date("X+1", not_support).is_err());
+        assert!(date(2000, 1, 1).move_date("Y+", not_support).is_err());
+        assert!(date(2000, 1, 1).move_date("", not_support).is_err());
+    }
+
+    #[test]
+    fn test_date_format() {
+        let value = ArcTopicDataValue::DateTime(Arc::new(datetime("2000-12-29 13:04:05")));
+        assert_str(
+            value.date_format("%Y/%M/%D %h:%m:%s", not_support).unwrap(),
+            "2000/12/29 13:04:05",
+        );
+        assert_str(
+            value
+                .date_format("%y %H%p %w %b, 100%%", not_support)
+                .unwrap(),
+            "00 01PM Fri Dec, 100%",
+        );
+        assert_str(
+            date(2000, 1, 2)
+                .date_format("%W %B %X", not_support)
+                .unwrap(),
+            "Sunday January %X",
+        );
+    }
+}
@@ -0,0 +1,534 @@
+use crate::ArcTopicDataValue;
+use bigdecimal::BigDecimal;
+use elf_base::{StdErr, StdR, StringConverter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// string functions, applied on
+/// - string, or decimal/bool/datetime/date/time which converted to string,
+/// - none, result is decided by function,
+/// - vec, applied on each element, and returns a vec,
+/// - map is not supported.
+impl ArcTopicDataValue {
+    /// convert to string, returns none when self is none.
+    /// vec and map are not supported
+    pub fn try_to_str<NotSupport>(&self, not_support: NotSupport) -> StdR<Option<String>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        match self {
+            Self::Str(str) => Ok(Some(str.to_string())),
+            Self::Num(decimal) => Ok(Some(String::from_decimal(decimal.deref()))),
+            Self::Bool(bool) => Ok(Some(String::from_bool(bool))),
+            Self::DateTime(datetime) => Ok(Some(String::from_datetime(datetime))),
+            Self::Date(date) => Ok(Some(String::from_date(date))),
+            Self::Time(time) => Ok(Some(String::from_time(time))),
+            Self::None => Ok(None),
+            _ => Err(not_support()),
+        }
+    }
+
+    fn map_str<OnNone, OnStr, NotSupport>(
+        &self,
+        on_none: &OnNone,
+        on_str: &OnStr,
+        not_support: &NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        OnNone: Fn() -> ArcTopicDataValue,
+        OnStr: Fn(&str) -> ArcTopicDataValue,
+        NotSupport: Fn() -> StdErr,
+    {
+        match self {
+            Self::Vec(vec) => {
+                let mut values = vec![];
+                for value in vec.iter() {
+                    values.push(value.map_str(on_none, on_str, not_support)?);
+                }
+                Ok(Arc::new(Self::Vec(Arc::new(values))))
+            }
+            _ => match self.try_to_str(not_support)? {
+                Some(str) => Ok(Arc::new(on_str(&str))),
+                None => Ok(Arc::new(on_none())),
+            },
+        }
+    }
+
+    fn of_str(str: String) -> ArcTopicDataValue {
+        Self::Str(Arc::new(str))
+    }
+
+    fn of_index(index: i64) -> ArcTopicDataValue {
+        Self::Num(Arc::new(BigDecimal::from(index)))
+    }
+
+    /// chars from start (included) to end (excluded), end is the chars count when not given.
+    /// returns empty string when start is out of range, or self is none
+    pub fn slice<NotSupport>(
+        &self,
+        start: usize,
+        end: Option<usize>,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| {
+                let count = str.chars().count();
+                let end = end.unwrap_or(count).min(count);
+                let start = start.min(end);
+                Self::of_str(str.chars().skip(start).take(end - start).collect())
+            },
+            &not_support,
+        )
+    }
+
+    /// index of the first char of given substring, -1 if not found.
+    /// none is treated as empty string
+    pub fn find<NotSupport>(
+        &self,
+        sub: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        let find = |str: &str| match str.find(sub) {
+            Some(index) => Self::of_index(str[..index].chars().count() as i64),
+            None => Self::of_index(-1),
+        };
+        self.map_str(&|| find(""), &find, &not_support)
+    }
+
+    /// none is always true
+    pub fn starts_with<NotSupport>(
+        &self,
+        sub: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::Bool(true),
+            &|str| Self::Bool(str.starts_with(sub)),
+            &not_support,
+        )
+    }
+
+    /// none is always true
+    pub fn ends_with<NotSupport>(
+        &self,
+        sub: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::Bool(true),
+            &|str| Self::Bool(str.ends_with(sub)),
+            &not_support,
+        )
+    }
+
+    /// strip leading and trailing chars which in given chars, or whitespaces when chars not given.
+    /// none is treated as empty string
+    pub fn strip<NotSupport>(
+        &self,
+        chars: Option<&str>,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| match chars {
+                Some(chars) => Self::of_str(str.trim_matches(|c| chars.contains(c)).to_string()),
+                None => Self::of_str(str.trim().to_string()),
+            },
+            &not_support,
+        )
+    }
+
+    /// replace all occurrences, none is treated as empty string
+    pub fn replace<NotSupport>(
+        &self,
+        from: &str,
+        to: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| Self::of_str(str.replace(from, to)),
+            &not_support,
+        )
+    }
+
+    /// replace first occurrence, none is treated as empty string
+    pub fn replace_first<NotSupport>(
+        &self,
+        from: &str,
+        to: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| Self::of_str(str.replacen(from, to, 1)),
+            &not_support,
+        )
+    }
+
+    /// none is treated as empty string
+    pub fn upper<NotSupport>(&self, not_support: NotSupport) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| Self::of_str(str.to_uppercase()),
+            &not_support,
+        )
+    }
+
+    /// none is treated as empty string
+    pub fn lower<NotSupport>(&self, not_support: NotSupport) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::of_str(String::new()),
+            &|str| Self::of_str(str.to_lowercase()),
+            &not_support,
+        )
+    }
+
+    /// none is treated as empty string
+    pub fn contains_str<NotSupport>(
+        &self,
+        sub: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.map_str(
+            &|| Self::Bool(sub.is_empty()),
+            &|str| Self::Bool(str.contains(sub)),
+            &not_support,
+        )
+    }
+
+    /// split to vec of string, split to chars when separator is empty.
+    /// none is treated as empty string
+    pub fn split<NotSupport>(
+        &self,
+        separator: &str,
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        let split = |str: &str| {
+            let values: Vec<Arc<ArcTopicDataValue>> = if separator.is_empty() {
+                str.chars()
+                    .map(|c| Arc::new(Self::of_str(c.to_string())))
+                    .collect()
+            } else {
+                str.split(separator)
+                    .map(|s| Arc::new(Self::of_str(s.to_string())))
+                    .collect()
+            };
+            Self::Vec(Arc::new(values))
+        };
+        self.map_str(&|| split(""), &split, &not_support)
+    }
+
+    /// append the given strings, none is ignored
+    pub fn concat<NotSupport>(
+        &self,
+        others: &[Option<String>],
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        self.concat_with("", others, not_support)
+    }
+
+    /// join self and the given strings with separator, none is ignored
+    pub fn concat_with<NotSupport>(
+        &self,
+        separator: &str,
+        others: &[Option<String>],
+        not_support: NotSupport,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        NotSupport: Fn() -> StdErr,
+    {
+        let others: Vec<&str> = others.iter().flatten().map(|s| s.as_str()).collect();
+        self.map_str(
+            &|| Self::of_str(others.join(separator)),
+            &|str| {
+                let mut values = vec![str];
+                values.extend(others.iter());
+                Self::of_str(values.join(separator))
+            },
+            &not_support,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcTopicDataValue, PipelineKernelErrorCode};
+    use bigdecimal::BigDecimal;
+    use elf_base::{ErrorCode, StdErr};
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    fn str(value: &str) -> ArcTopicDataValue {
+        ArcTopicDataValue::Str(Arc::new(value.to_string()))
+    }
+
+    fn vec(values: Vec<ArcTopicDataValue>) -> ArcTopicDataValue {
+        ArcTopicDataValue::Vec(Arc::new(values.into_iter().map(Arc::new).collect()))
+    }
+
+    fn not_support() -> StdErr {
+        PipelineKernelErrorCode::VariableFuncNotSupported.err_with_msg("not supported")
+    }
+
+    fn assert_str(value: Arc<ArcTopicDataValue>, expected: &str) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == expected),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    fn assert_num(value: Arc<ArcTopicDataValue>, expected: i32) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from(expected)),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    fn assert_bool(value: Arc<ArcTopicDataValue>, expected: bool) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Bool(b) if *b == expected),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    fn assert_strs(value: Arc<ArcTopicDataValue>, expected: Vec<&str>) {
+        match value.deref() {
+            ArcTopicDataValue::Vec(values) => {
+                assert_eq!(values.len(), expected.len());
+                for (value, expected) in values.iter().zip(expected) {
+                    assert_str(value.clone(), expected);
+                }
+            }
+            _ => panic!("expected vec, got [{:?}]", value),
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        assert_str(str("abcde").slice(1, Some(3), not_support).unwrap(), "bc");
+        assert_str(str("abcde").slice(1, None, not_support).unwrap(), "bcde");
+        assert_str(str("abcde").slice(3, Some(10), not_support).unwrap(), "de");
+        assert_str(str("abcde").slice(4, Some(2), not_support).unwrap(), "");
+        assert_str(
+            str("中文字符").slice(1, Some(3), not_support).unwrap(),
+            "文字",
+        );
+        assert_str(
+            ArcTopicDataValue::None.slice(1, None, not_support).unwrap(),
+            "",
+        );
+        let value = ArcTopicDataValue::Num(Arc::new(BigDecimal::from(12345)));
+        assert_str(value.slice(0, Some(2), not_support).unwrap(), "12");
+    }
+
+    #[test]
+    fn test_find() {
+        assert_num(str("中文abc").find("b", not_support).unwrap(), 3);
+        assert_num(str("abc").find("x", not_support).unwrap(), -1);
+        assert_num(str("abc").find("", not_support).unwrap(), 0);
+        assert_num(ArcTopicDataValue::None.find("a", not_support).unwrap(), -1);
+        assert_num(ArcTopicDataValue::None.find("", not_support).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_starts_ends_with() {
+        assert_bool(str("abc").starts_with("ab", not_support).unwrap(), true);
+        assert_bool(str("abc").starts_with("bc", not_support).unwrap(), false);
+        assert_bool(str("abc").ends_with("bc", not_support).unwrap(), true);
+        assert_bool(str("abc").ends_with("ab", not_support).unwrap(), false);
+        assert_bool(
+            ArcTopicDataValue::None
+                .starts_with("a", not_support)
+                .unwrap(),
+            true,
+        );
+        assert_bool(
+            ArcTopicDataValue::None.ends_with("a", not_support).unwrap(),
+            true,
+        );
+    }
+
+    #[test]
+    fn test_strip() {
+        assert_str(str(" \tabc\n ").strip(None, not_support).unwrap(), "abc");
+        assert_str(
+            str("-=abc=-").strip(Some("=-"), not_support).unwrap(),
+            "abc",
+        );
+        assert_str(
+            ArcTopicDataValue::None.strip(None, not_support).unwrap(),
+            "",
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        assert_str(
+            str("a-b-c").replace("-", "+", not_support).unwrap(),
+            "a+b+c",
+        );
+        assert_str(
+            str("a-b-c").replace_first("-", "+", not_support).unwrap(),
+            "a+b-c",
+        );
+        assert_str(str("a\tb").replace("\t", " ", not_support).unwrap(), "a b");
+        assert_str(
+            ArcTopicDataValue::None
+                .replace("-", "+", not_support)
+                .unwrap(),
+            "",
+        );
+        assert_str(
+            ArcTopicDataValue::None
+                .replace_first("-", "+", not_support)
+                .unwrap(),
+            "",
+        );
+    }
+
+    #[test]
+    fn test_upper_lower() {
+        assert_str(str("aBc").upper(not_support).unwrap(), "ABC");
+        assert_str(str("aBc").lower(not_support).unwrap(), "abc");
+        assert_str(
+            ArcTopicDataValue::Bool(true).upper(not_support).unwrap(),
+            "TRUE",
+        );
+        assert_str(ArcTopicDataValue::None.upper(not_support).unwrap(), "");
+        assert_str(ArcTopicDataValue::None.lower(not_support).unwrap(), "");
+    }
+
+    #[test]
+    fn test_contains() {
+        assert_bool(str("abc").contains_str("b", not_support).unwrap(), true);
+        assert_bool(str("abc").contains_str("x", not_support).unwrap(), false);
+        assert_bool(
+            ArcTopicDataValue::None
+                .contains_str("x", not_support)
+                .unwrap(),
+            false,
+        );
+        assert_bool(
+            ArcTopicDataValue::None
+                .contains_str("", not_support)
+                .unwrap(),
+            true,
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        assert_strs(
+            str("a,b,,c").split(",", not_support).unwrap(),
+            vec!["a", "b", "", "c"],
+        );
+        assert_strs(
+            str("a\nb").split("\n", not_support).unwrap(),
+            vec!["a", "b"],
+        );
+        assert_strs(
+            str("abc").split("", not_support).unwrap(),
+            vec!["a", "b", "c"],
+        );
+        assert_strs(
+            ArcTopicDataValue::None.split(",", not_support).unwrap(),
+            vec![""],
+        );
+        assert_strs(
+            ArcTopicDataValue::None.split("", not_support).unwrap(),
+            vec![],
+        );
+    }
+
+    #[test]
+    fn test_concat() {
+        let others = vec![Some("b".to_string()), None, Some("c".to_string())];
+        assert_str(str("a").concat(&others, not_support).unwrap(), "abc");
+        assert_str(
+            ArcTopicDataValue::None
+                .concat(&others, not_support)
+                .unwrap(),
+            "bc",
+        );
+        assert_str(
+            str("a").concat_with("-", &others, not_support).unwrap(),
+            "a-b-c",
+        );
+        assert_str(
+            ArcTopicDataValue::None
+                .concat_with("-", &others, not_support)
+                .unwrap(),
+            "b-c",
+        );
+        assert_str(
+            ArcTopicDataValue::None
+                .concat_with("-", &[None], not_support)
+                .unwrap(),
+            "",
+        );
+    }
+
+    #[test]
+    fn test_element_wise() {
+        let value = vec(vec![str("a-b"), ArcTopicDataValue::None, str("c")]);
+        assert_strs(value.upper(not_support).unwrap(), vec!["A-B", "", "C"]);
+        match value.split("-", not_support).unwrap().deref() {
+            ArcTopicDataValue::Vec(values) => {
+                assert_eq!(values.len(), 3);
+                assert_strs(values[0].clone(), vec!["a", "b"]);
+                assert_strs(values[1].clone(), vec![""]);
+                assert_strs(values[2].clone(), vec!["c"]);
+            }
+            other => panic!("expected vec, got [{:?}]", other),
+        }
+
+        let map = ArcTopicDataValue::Map(Arc::new(HashMap::new()));
+        assert!(map.upper(not_support).is_err());
+        assert!(vec(vec![map]).upper(not_support).is_err());
+    }
+}
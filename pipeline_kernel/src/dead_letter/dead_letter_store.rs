@@ -0,0 +1,53 @@
+use crate::RawTopicDeadLetterStore;
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineDeadLetter, PipelineDeadLetterId, PipelineTriggerTraceId, TenantId};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait DeadLetterStore: Send + Sync {
+    fn save(&self, tenant_id: &TenantId, dead_letter: PipelineDeadLetter) -> VoidR;
+    /// dead letters of given trace, in saved order
+    fn find_by_trace_id(
+        &self,
+        tenant_id: &TenantId,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetter>>;
+    /// remove the given dead letter, nothing happens when it is not found
+    fn discard(&self, tenant_id: &TenantId, dead_letter_id: &PipelineDeadLetterId) -> VoidR;
+}
+
+static GLOBAL_DEAD_LETTER_STORE: OnceLock<RwLock<Arc<dyn DeadLetterStore>>> = OnceLock::new();
+
+/// the default dead letter store is [RawTopicDeadLetterStore],
+/// [set] a new store to replace the default one.
+pub struct DeadLetterStores;
+
+impl DeadLetterStores {
+    fn init() -> RwLock<Arc<dyn DeadLetterStore>> {
+        RwLock::new(Arc::new(RawTopicDeadLetterStore {}) as Arc<dyn DeadLetterStore>)
+    }
+
+    /// get current store, and release lock before use
+    pub fn store() -> StdR<Arc<dyn DeadLetterStore>> {
+        Ok(GLOBAL_DEAD_LETTER_STORE
+            .get_or_init(DeadLetterStores::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone())
+    }
+
+    pub fn set(new_store: Arc<dyn DeadLetterStore>) -> VoidR {
+        let mut guard = GLOBAL_DEAD_LETTER_STORE
+            .get_or_init(DeadLetterStores::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = new_store;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{DeadLetterStore, PipelineKernelErrorCode};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineDeadLetter, PipelineDeadLetterId, PipelineTriggerTraceId, TenantId};
+use serde_json::Value;
+use std::sync::RwLock;
+
+/// keep dead letters in memory, typically for test.
+/// dead letters are kept as json, and deserialized on each find.
+#[derive(Default)]
+pub struct InMemoryDeadLetterStore {
+    dead_letters: RwLock<Vec<(TenantId, Value)>>,
+}
+
+impl InMemoryDeadLetterStore {
+    pub fn new() -> Self {
+        InMemoryDeadLetterStore {
+            dead_letters: RwLock::new(vec![]),
+        }
+    }
+
+    fn is_string(value: &Value, name: &str, expected: &str) -> bool {
+        value.get(name).and_then(Value::as_str) == Some(expected)
+    }
+}
+
+impl DeadLetterStore for InMemoryDeadLetterStore {
+    fn save(&self, tenant_id: &TenantId, dead_letter: PipelineDeadLetter) -> VoidR {
+        let value = serde_json::to_value(dead_letter).map_err(|e| {
+            PipelineKernelErrorCode::DeadLetterNotSerializable.err_with_msg(format!(
+                "Failed to convert pipeline dead letter to json, caused by {}.",
+                e
+            ))
+        })?;
+        let mut guard = self.dead_letters.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.push((tenant_id.clone(), value));
+        Ok(())
+    }
+
+    fn find_by_trace_id(
+        &self,
+        tenant_id: &TenantId,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetter>> {
+        let guard = self.dead_letters.read().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+        })?;
+        guard
+            .iter()
+            .filter(|(tenant, value)| {
+                tenant == tenant_id && Self::is_string(value, "traceId", trace_id)
+            })
+            .map(|(_, value)| {
+                serde_json::from_value(value.clone()).map_err(|e| {
+                    PipelineKernelErrorCode::DeadLetterNotSerializable.err_with_msg(format!(
+                        "Failed to convert json to pipeline dead letter, caused by {}.",
+                        e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn discard(&self, tenant_id: &TenantId, dead_letter_id: &PipelineDeadLetterId) -> VoidR {
+        let mut guard = self.dead_letters.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.retain(|(tenant, value)| {
+            tenant != tenant_id || !Self::is_string(value, "deadLetterId", dead_letter_id)
+        });
+        Ok(())
+    }
+}
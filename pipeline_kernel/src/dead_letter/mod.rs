@@ -0,0 +1,7 @@
+mod dead_letter_store;
+mod in_memory_dead_letter_store;
+mod raw_topic_dead_letter_store;
+
+pub use dead_letter_store::*;
+pub use in_memory_dead_letter_store::*;
+pub use raw_topic_dead_letter_store::*;
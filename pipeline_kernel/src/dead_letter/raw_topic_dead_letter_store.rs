@@ -0,0 +1,133 @@
+use crate::{DeadLetterStore, PipelineKernelErrorCode, RawTopicModel};
+use elf_base::{StdR, VoidR};
+use elf_model::{
+	PipelineDeadLetter, PipelineDeadLetterId, PipelineTriggerTraceId, TenantId, TopicCode,
+	TopicData,
+};
+use elf_runtime_model_kernel::{
+	TopicDataProvider, TopicSchema, TopicSchemaProvider, TopicService,
+};
+use std::sync::Arc;
+
+/// persist dead letters into raw pipeline dead letter topic of tenant
+pub struct RawTopicDeadLetterStore;
+
+impl RawTopicDeadLetterStore {
+    /// code of raw topic which dead letters are persisted into
+    const RAW_PIPELINE_DEAD_LETTER_TOPIC: &'static str = "raw_pipeline_dead_letter";
+
+    fn topic_schema(tenant_id: &TenantId) -> StdR<Arc<TopicSchema>> {
+        TopicService::schema()?.by_code(
+            &TopicCode::from(Self::RAW_PIPELINE_DEAD_LETTER_TOPIC),
+            tenant_id,
+        )
+    }
+
+    fn to_topic_data(dead_letter: &PipelineDeadLetter) -> StdR<TopicData> {
+        RawTopicModel::to_topic_data(
+            dead_letter,
+            "pipeline dead letter",
+            PipelineKernelErrorCode::DeadLetterNotSerializable,
+        )
+    }
+
+    fn from_topic_data(topic_schema: &TopicSchema, data: TopicData) -> StdR<PipelineDeadLetter> {
+        RawTopicModel::from_topic_data(
+            topic_schema,
+            data,
+            "pipeline dead letter",
+            PipelineKernelErrorCode::DeadLetterNotSerializable,
+        )
+    }
+}
+
+impl DeadLetterStore for RawTopicDeadLetterStore {
+    fn save(&self, tenant_id: &TenantId, dead_letter: PipelineDeadLetter) -> VoidR {
+        let mut data = Self::to_topic_data(&dead_letter)?;
+        let topic_schema = Self::topic_schema(tenant_id)?;
+        topic_schema.prepare(&mut data)?;
+        TopicService::data()?.insert(&topic_schema, data)?;
+        Ok(())
+    }
+
+    fn find_by_trace_id(
+        &self,
+        tenant_id: &TenantId,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetter>> {
+        let topic_schema = Self::topic_schema(tenant_id)?;
+        let criteria = RawTopicModel::criteria_of(&topic_schema, "traceId", trace_id)?;
+        TopicService::data()?
+            .find(&topic_schema, &criteria)?
+            .into_iter()
+            .map(|data| Self::from_topic_data(&topic_schema, data))
+            .collect()
+    }
+
+    fn discard(&self, tenant_id: &TenantId, dead_letter_id: &PipelineDeadLetterId) -> VoidR {
+        let topic_schema = Self::topic_schema(tenant_id)?;
+        let criteria = RawTopicModel::criteria_of(&topic_schema, "deadLetterId", dead_letter_id)?;
+        let data_service = TopicService::data()?;
+        if let Some(data) = data_service.find_one(&topic_schema, &criteria)? {
+            data_service.delete(&topic_schema, data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RawTopicDeadLetterStore;
+    use chrono::NaiveDateTime;
+    use elf_model::topics::ask_raw_pipeline_dead_letter_topic;
+    use elf_model::{PipelineDeadLetter, PipelineTriggerType, TopicDataValue};
+    use elf_runtime_model_kernel::TopicSchema;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_convert() {
+        let topic = ask_raw_pipeline_dead_letter_topic()
+            .topic_id(String::from("rpdl"))
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let topic_schema = TopicSchema::new(topic).unwrap();
+
+        let dead_letter = PipelineDeadLetter::new()
+            .dead_letter_id(String::from("dl-1"))
+            .trace_id(String::from("trace-1"))
+            .pipeline_id(String::from("p1"))
+            .topic_id(String::from("topic-1"))
+            .data_id(String::from("1"))
+            .trigger_type(PipelineTriggerType::Merge)
+            .old_value(HashMap::from([(
+                String::from("name"),
+                TopicDataValue::Str(String::from("a")),
+            )]))
+            .new_value(HashMap::from([(
+                String::from("name"),
+                TopicDataValue::Str(String::from("b")),
+            )]))
+            .error(String::from("failed"))
+            .attempts(3)
+            .created_at(NaiveDateTime::default());
+        let mut data = RawTopicDeadLetterStore::to_topic_data(&dead_letter).unwrap();
+        assert!(matches!(data.get("traceId"), Some(TopicDataValue::Str(v)) if v == "trace-1"));
+        // columns of raw topic are ignored
+        data.insert(
+            String::from("id_"),
+            TopicDataValue::Str(String::from("100")),
+        );
+
+        let dead_letter = RawTopicDeadLetterStore::from_topic_data(&topic_schema, data).unwrap();
+        assert_eq!(dead_letter.dead_letter_id.unwrap(), "dl-1");
+        assert_eq!(
+            dead_letter.trigger_type.unwrap(),
+            PipelineTriggerType::Merge
+        );
+        assert_eq!(dead_letter.attempts.unwrap(), 3);
+        assert!(matches!(
+            dead_letter.new_value.unwrap().get("name"),
+            Some(TopicDataValue::Str(v)) if v == "b"
+        ));
+    }
+}
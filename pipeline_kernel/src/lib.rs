@@ -1,9 +1,21 @@
+mod alarm;
+mod backfill;
 mod common;
 mod data_op;
+mod dead_letter;
+mod monitor;
 mod pipeline;
+mod sequence;
+mod simulation;
 mod topic;
 
+pub use alarm::*;
+pub use backfill::*;
 pub use common::*;
 pub use data_op::*;
+pub use dead_letter::*;
+pub use monitor::*;
 pub use pipeline::*;
+pub use sequence::*;
+pub use simulation::*;
 pub use topic::*;
@@ -0,0 +1,178 @@
+use elf_model::{
+	MonitorLogAction, MonitorLogParameterCondition, MonitorLogParameterExpression,
+	MonitorLogParameterJoint, MonitorLogParameterOperand, ParameterJointType, TopicData,
+	TopicDataValue,
+};
+use elf_runtime_model_kernel::{
+	TopicDataCriteria, TopicDataCriteriaExpression, TopicDataCriteriaJoint,
+	TopicDataCriteriaOperand,
+};
+use std::ops::Deref;
+
+/// runtime monitor log of action, the touched value, the find by criteria and the counts of changed rows.
+/// version conflicts of merge are counted as well.
+/// - read actions touch the read value,
+/// - write and delete actions touch the changed rows, as a vec of maps,
+/// - find by is the criteria with resolved values, for actions which find rows from topic.
+#[derive(Default)]
+pub struct ActionMonitorLog {
+    insert_count: u32,
+    update_count: u32,
+    delete_count: u32,
+    conflict_count: u32,
+    touched: Option<TopicDataValue>,
+    find_by: Option<MonitorLogParameterJoint>,
+}
+
+impl ActionMonitorLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// record the read value, replace the existing one
+    pub fn touch(&mut self, value: TopicDataValue) {
+        self.touched = Some(value);
+    }
+
+    /// record the criteria which used to find rows, replace the existing one
+    pub fn find_by(&mut self, criteria: &TopicDataCriteria) {
+        self.find_by = Some(match criteria {
+            TopicDataCriteria::Joint(joint) => Self::criteria_joint(joint),
+            TopicDataCriteria::Expression(expression) => MonitorLogParameterJoint {
+                joint_type: ParameterJointType::And,
+                filters: vec![MonitorLogParameterCondition::Expression(
+                    Self::criteria_expression(expression),
+                )],
+            },
+        });
+    }
+
+    fn criteria_joint(joint: &TopicDataCriteriaJoint) -> MonitorLogParameterJoint {
+        MonitorLogParameterJoint {
+            joint_type: joint.joint_type.deref().clone(),
+            filters: joint
+                .conditions
+                .iter()
+                .map(|condition| match condition {
+                    TopicDataCriteria::Expression(expression) => {
+                        MonitorLogParameterCondition::Expression(Self::criteria_expression(
+                            expression,
+                        ))
+                    }
+                    TopicDataCriteria::Joint(joint) => {
+                        MonitorLogParameterCondition::Joint(Self::criteria_joint(joint))
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn criteria_expression(
+        expression: &TopicDataCriteriaExpression,
+    ) -> MonitorLogParameterExpression {
+        MonitorLogParameterExpression {
+            left: Self::criteria_operand(&expression.left),
+            operator: expression.operator.deref().clone(),
+            right: expression.right.as_ref().map(Self::criteria_operand),
+        }
+    }
+
+    fn criteria_operand(operand: &TopicDataCriteriaOperand) -> MonitorLogParameterOperand {
+        match operand {
+            TopicDataCriteriaOperand::Factor(factor) => MonitorLogParameterOperand::factor(
+                factor.factor_id.deref().clone(),
+                factor.name.deref().clone(),
+            ),
+            TopicDataCriteriaOperand::Value(value) => {
+                MonitorLogParameterOperand::value(value.clone())
+            }
+        }
+    }
+
+    pub fn inserted(&mut self, row: &TopicData) {
+        self.insert_count += 1;
+        self.touch_row(row);
+    }
+
+    pub fn updated(&mut self, row: &TopicData) {
+        self.update_count += 1;
+        self.touch_row(row);
+    }
+
+    pub fn deleted(&mut self, row: &TopicData) {
+        self.delete_count += 1;
+        self.touch_row(row);
+    }
+
+    pub fn conflicted(&mut self) {
+        self.conflict_count += 1;
+    }
+
+    fn touch_row(&mut self, row: &TopicData) {
+        let row = TopicDataValue::Map(row.clone());
+        match &mut self.touched {
+            Some(TopicDataValue::Vec(rows)) => rows.push(row),
+            _ => self.touched = Some(TopicDataValue::Vec(vec![row])),
+        }
+    }
+
+    /// write counts, touched and find by into given action log
+    pub fn fill(self, mut log: MonitorLogAction) -> MonitorLogAction {
+        log.insert_count = Some(self.insert_count);
+        log.update_count = Some(self.update_count);
+        log.delete_count = Some(self.delete_count);
+        log.conflict_count = Some(self.conflict_count);
+        log.touched = self.touched;
+        log.find_by = self.find_by;
+        log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ActionMonitorLog;
+    use elf_model::{
+        MonitorLogAction, MonitorLogParameterCondition, ParameterExpressionOperator,
+        ParameterJointType, TopicDataValue,
+    };
+    use elf_runtime_model_kernel::{
+        TopicDataCriteria, TopicDataCriteriaExpression, TopicDataCriteriaOperand,
+    };
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_fill() {
+        let mut monitor = ActionMonitorLog::new();
+        let row = HashMap::from([("a".to_string(), TopicDataValue::Bool(true))]);
+        monitor.inserted(&row);
+        monitor.deleted(&row);
+        monitor.conflicted();
+        let log = monitor.fill(MonitorLogAction::new());
+        assert_eq!(log.insert_count, Some(1));
+        assert_eq!(log.update_count, Some(0));
+        assert_eq!(log.delete_count, Some(1));
+        assert_eq!(log.conflict_count, Some(1));
+        assert!(matches!(log.touched, Some(TopicDataValue::Vec(rows)) if rows.len() == 2));
+    }
+
+    #[test]
+    fn test_find_by() {
+        let mut monitor = ActionMonitorLog::new();
+        monitor.find_by(&TopicDataCriteria::Expression(
+            TopicDataCriteriaExpression {
+                left: TopicDataCriteriaOperand::Value(TopicDataValue::Bool(true)),
+                operator: Arc::new(ParameterExpressionOperator::NotEmpty),
+                right: None,
+            },
+        ));
+        let log = monitor.fill(MonitorLogAction::new());
+        let find_by = log.find_by.unwrap();
+        assert!(matches!(find_by.joint_type, ParameterJointType::And));
+        assert!(matches!(
+            &find_by.filters[..],
+            [MonitorLogParameterCondition::Expression(e)]
+                if matches!(e.left.value, Some(TopicDataValue::Bool(true))) && e.right.is_none()
+        ));
+    }
+}
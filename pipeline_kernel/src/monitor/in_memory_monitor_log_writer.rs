@@ -0,0 +1,36 @@
+use crate::MonitorLogWriter;
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineMonitorLog, TenantId};
+use std::sync::RwLock;
+
+/// keep monitor logs in memory, typically for test
+#[derive(Default)]
+pub struct InMemoryMonitorLogWriter {
+    logs: RwLock<Vec<PipelineMonitorLog>>,
+}
+
+impl InMemoryMonitorLogWriter {
+    pub fn new() -> Self {
+        InMemoryMonitorLogWriter {
+            logs: RwLock::new(vec![]),
+        }
+    }
+
+    /// take all written logs, in written order
+    pub fn take(&self) -> StdR<Vec<PipelineMonitorLog>> {
+        let mut guard = self.logs.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        Ok(std::mem::take(&mut *guard))
+    }
+}
+
+impl MonitorLogWriter for InMemoryMonitorLogWriter {
+    fn write(&self, _tenant_id: &TenantId, log: PipelineMonitorLog) -> VoidR {
+        let mut guard = self.logs.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.push(log);
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+mod action_monitor_log;
+mod in_memory_monitor_log_writer;
+mod monitor_log_stopwatch;
+mod monitor_log_writer;
+mod raw_topic_monitor_log_writer;
+
+pub use action_monitor_log::*;
+pub use in_memory_monitor_log_writer::*;
+pub use monitor_log_stopwatch::*;
+pub use monitor_log_writer::*;
+pub use raw_topic_monitor_log_writer::*;
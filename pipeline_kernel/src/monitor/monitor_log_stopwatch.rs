@@ -0,0 +1,26 @@
+use chrono::{Local, NaiveDateTime};
+use std::time::Instant;
+
+/// start time and elapsed time of a monitored step, e.g. pipeline, stage, unit and action.
+pub struct MonitorLogStopwatch {
+    start_time: NaiveDateTime,
+    start: Instant,
+}
+
+impl MonitorLogStopwatch {
+    pub fn start() -> Self {
+        MonitorLogStopwatch {
+            start_time: Local::now().naive_local(),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn start_time(&self) -> NaiveDateTime {
+        self.start_time
+    }
+
+    /// elapsed milliseconds since started, saturated to [u32::MAX]
+    pub fn spent_in_mills(&self) -> u32 {
+        u32::try_from(self.start.elapsed().as_millis()).unwrap_or(u32::MAX)
+    }
+}
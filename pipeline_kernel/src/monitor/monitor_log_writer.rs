@@ -0,0 +1,47 @@
+use crate::RawTopicMonitorLogWriter;
+use elf_base::{ErrorCode, StdErrCode, VoidR};
+use elf_model::{PipelineMonitorLog, TenantId};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait MonitorLogWriter: Send + Sync {
+    fn write(&self, tenant_id: &TenantId, log: PipelineMonitorLog) -> VoidR;
+}
+
+static GLOBAL_MONITOR_LOG_WRITER: OnceLock<RwLock<Arc<dyn MonitorLogWriter>>> = OnceLock::new();
+
+/// the default monitor log writer is [RawTopicMonitorLogWriter],
+/// [set] a new writer to replace the default one.
+pub struct MonitorLogWriters;
+
+impl MonitorLogWriters {
+    fn init() -> RwLock<Arc<dyn MonitorLogWriter>> {
+        RwLock::new(Arc::new(RawTopicMonitorLogWriter {}) as Arc<dyn MonitorLogWriter>)
+    }
+
+    /// get current writer, and release lock before write
+    pub fn write(tenant_id: &TenantId, log: PipelineMonitorLog) -> VoidR {
+        let writer = GLOBAL_MONITOR_LOG_WRITER
+            .get_or_init(MonitorLogWriters::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone();
+
+        writer.write(tenant_id, log)
+    }
+
+    pub fn set(new_writer: Arc<dyn MonitorLogWriter>) -> VoidR {
+        let mut guard = GLOBAL_MONITOR_LOG_WRITER
+            .get_or_init(MonitorLogWriters::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = new_writer;
+
+        Ok(())
+    }
+}
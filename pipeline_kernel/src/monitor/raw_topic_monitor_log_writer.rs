@@ -0,0 +1,35 @@
+use crate::{MonitorLogWriter, PipelineKernelErrorCode};
+use elf_base::{ErrorCode, StdR, VoidR};
+use elf_model::{PipelineMonitorLog, TenantId, TopicCode, TopicData};
+use elf_runtime_model_kernel::{TopicDataProvider, TopicSchemaProvider, TopicService};
+
+/// persist monitor log into raw pipeline monitor topic of tenant
+pub struct RawTopicMonitorLogWriter;
+
+impl RawTopicMonitorLogWriter {
+    /// code of raw topic which monitor logs are persisted into
+    const RAW_PIPELINE_MONITOR_TOPIC: &'static str = "raw_pipeline_monitor_log";
+
+    fn to_topic_data(log: &PipelineMonitorLog) -> StdR<TopicData> {
+        match serde_json::to_value(log).and_then(serde_json::from_value) {
+            Ok(data) => Ok(data),
+            Err(e) => PipelineKernelErrorCode::MonitorLogNotSerializable.msg(format!(
+                "Failed to convert pipeline monitor log to topic data, caused by {}.",
+                e
+            )),
+        }
+    }
+}
+
+impl MonitorLogWriter for RawTopicMonitorLogWriter {
+    fn write(&self, tenant_id: &TenantId, log: PipelineMonitorLog) -> VoidR {
+        let mut data = Self::to_topic_data(&log)?;
+        let topic_schema = TopicService::schema()?.by_code(
+            &TopicCode::from(Self::RAW_PIPELINE_MONITOR_TOPIC),
+            tenant_id,
+        )?;
+        topic_schema.prepare(&mut data)?;
+        TopicService::data()?.insert(&topic_schema, data)?;
+        Ok(())
+    }
+}
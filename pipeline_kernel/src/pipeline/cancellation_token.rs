@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+/// cancel the run of pipelines, shared by all executions triggered by one trigger.
+/// execution in progress is interrupted, and the remaining ones are not run.
+/// cancelled token cannot be restored.
+#[derive(Default)]
+pub struct PipelineCancellationToken {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl PipelineCancellationToken {
+    pub fn new() -> Self {
+        PipelineCancellationToken {
+            cancelled: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// completes when the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            // register before check, avoid missing the notification between them
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PipelineCancellationToken;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn test_cancel() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let token = Arc::new(PipelineCancellationToken::new());
+        assert!(!token.is_cancelled());
+
+        let cancelled = token.clone();
+        runtime.block_on(async move {
+            let waiting = tokio::spawn(async move { cancelled.cancelled().await });
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            token.cancel();
+            waiting.await.unwrap();
+            assert!(token.is_cancelled());
+            // completes at once when cancelled already
+            token.cancelled().await;
+        });
+    }
+}
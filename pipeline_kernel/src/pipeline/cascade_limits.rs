@@ -0,0 +1,38 @@
+use crate::PipelineKernelErrorCode;
+use elf_base::{ErrorCode, VoidR};
+use std::sync::OnceLock;
+
+/// limits of cascading pipeline executions in one trace
+pub struct PipelineCascadeLimits {
+    /// max depth of cascading, the pipelines triggered by trigger data are at depth 0
+    pub max_depth: u32,
+    /// max count of executions in one trace, including the pipelines triggered by trigger data
+    pub max_executions: u32,
+}
+
+impl Default for PipelineCascadeLimits {
+    /// max depth is 16, max executions is 1024
+    fn default() -> Self {
+        PipelineCascadeLimits {
+            max_depth: 16,
+            max_executions: 1024,
+        }
+    }
+}
+
+static GLOBAL_CASCADE_LIMITS: OnceLock<PipelineCascadeLimits> = OnceLock::new();
+
+/// the default global limits is created by default,
+/// [init] the global limits before first use to apply another one.
+impl PipelineCascadeLimits {
+    pub fn init(limits: PipelineCascadeLimits) -> VoidR {
+        GLOBAL_CASCADE_LIMITS.set(limits).or_else(|_| {
+            PipelineKernelErrorCode::CascadeLimitsAlreadyInitialized
+                .msg("Pipeline cascade limits is initialized already.")
+        })
+    }
+
+    pub fn global() -> &'static PipelineCascadeLimits {
+        GLOBAL_CASCADE_LIMITS.get_or_init(PipelineCascadeLimits::default)
+    }
+}
@@ -1,25 +1,79 @@
 use crate::CompiledPipeline;
-use elf_base::StdR;
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{PipelineId, TenantId};
 use elf_runtime_model_kernel::{PipelineSchema, PipelineService, TopicSchema};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock, RwLock};
+
+type CompiledPipelineCache = RwLock<HashMap<(TenantId, PipelineId), Arc<CompiledPipeline>>>;
+
+/// compiled pipelines, key is (tenant id, pipeline id).
+/// the version of compiled pipeline is checked when find from cache,
+/// and the stale one will be replaced by the newly compiled.
+static COMPILED_PIPELINES: OnceLock<CompiledPipelineCache> = OnceLock::new();
 
 pub struct PipelineCompileService;
 
 impl PipelineCompileService {
     fn new() -> StdR<Arc<Self>> {
-        // TODO maybe find from cache
         Ok(Arc::new(Self {}))
     }
 
+    fn cache_key(pipeline_schema: &PipelineSchema) -> (TenantId, PipelineId) {
+        (
+            pipeline_schema.tenant_id().deref().clone(),
+            pipeline_schema.pipeline_id().deref().clone(),
+        )
+    }
+
+    /// returns compiled pipeline only when its version is same as the given pipeline schema
+    fn find_from_cache(
+        &self,
+        pipeline_schema: &PipelineSchema,
+    ) -> StdR<Option<Arc<CompiledPipeline>>> {
+        let guard = COMPILED_PIPELINES
+            .get_or_init(|| RwLock::new(HashMap::new()))
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?;
+
+        Ok(guard
+            .get(&Self::cache_key(pipeline_schema))
+            .filter(|compiled| compiled.pipeline().version() == pipeline_schema.version())
+            .cloned())
+    }
+
+    /// put compiled pipeline into cache, the existing one (stale version) is evicted.
+    fn put_into_cache(&self, compiled: Arc<CompiledPipeline>) -> VoidR {
+        let mut guard = COMPILED_PIPELINES
+            .get_or_init(|| RwLock::new(HashMap::new()))
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+
+        guard.insert(Self::cache_key(compiled.pipeline()), compiled);
+
+        Ok(())
+    }
+
+    /// compile pipeline once, and reuse it until the version of pipeline changed.
     pub fn compile(
         &self,
         topic_schema: Arc<TopicSchema>,
         pipeline_schema: Arc<PipelineSchema>,
     ) -> StdR<Arc<CompiledPipeline>> {
-        Ok(Arc::new(CompiledPipeline::compile(
-            topic_schema,
-            pipeline_schema,
-        )?))
+        if let Some(compiled) = self.find_from_cache(&pipeline_schema)? {
+            return Ok(compiled);
+        }
+
+        let compiled = Arc::new(CompiledPipeline::compile(topic_schema, pipeline_schema)?);
+        self.put_into_cache(compiled.clone())?;
+        Ok(compiled)
     }
 }
 
@@ -30,3 +84,74 @@ pub trait PipelineCompilationProvider {
 }
 
 impl PipelineCompilationProvider for PipelineService {}
+
+#[cfg(test)]
+mod tests {
+    use crate::PipelineCompileService;
+    use elf_model::{
+        Factor, FactorType, Pipeline, PipelineStage, PipelineTriggerType, PipelineUnit, Topic,
+        TopicKind, TopicType, WriteToExternalAction,
+    };
+    use elf_runtime_model_kernel::{PipelineSchema, TopicSchema};
+    use std::sync::Arc;
+
+    fn create_topic_schema() -> Arc<TopicSchema> {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("factor_1"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        Arc::new(TopicSchema::new(topic).expect("failed to create topic schema"))
+    }
+
+    fn create_pipeline_schema(version: u32) -> Arc<PipelineSchema> {
+        let pipeline = Pipeline::new()
+            .pipeline_id(String::from("compile-cache-pipeline-1"))
+            .topic_id(String::from("topic-1"))
+            .name(String::from("Pipeline 1"))
+            .r#type(PipelineTriggerType::Insert)
+            .stages(vec![PipelineStage::new().units(vec![
+                PipelineUnit::new().r#do(vec![
+                    WriteToExternalAction::init()
+                        .external_writer_id(String::from("writer-1"))
+                        .event_code(String::from("event-1"))
+                        .to_action(),
+                ]),
+            ])])
+            .tenant_id(String::from("Tenant-1"))
+            .version(version);
+        Arc::new(PipelineSchema::new(pipeline).expect("failed to create pipeline schema"))
+    }
+
+    #[test]
+    fn test_compile_cache() {
+        let service = PipelineCompileService::new().unwrap();
+
+        let compiled_v1 = service
+            .compile(create_topic_schema(), create_pipeline_schema(1))
+            .expect("failed to compile pipeline");
+        let cached_v1 = service
+            .compile(create_topic_schema(), create_pipeline_schema(1))
+            .expect("failed to compile pipeline");
+        assert!(Arc::ptr_eq(&compiled_v1, &cached_v1));
+
+        // version bumped, stale one evicted
+        let compiled_v2 = service
+            .compile(create_topic_schema(), create_pipeline_schema(2))
+            .expect("failed to compile pipeline");
+        assert!(!Arc::ptr_eq(&compiled_v1, &compiled_v2));
+        assert_eq!(compiled_v2.pipeline().version(), 2);
+        let cached_v2 = service
+            .compile(create_topic_schema(), create_pipeline_schema(2))
+            .expect("failed to compile pipeline");
+        assert!(Arc::ptr_eq(&compiled_v2, &cached_v2));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{CompiledParameterJoint, InMemoryParameterCondition, PipelineExecutionVariables};
 use elf_base::StdR;
-use elf_model::TenantId;
+use elf_model::{MonitorLogParameterJoint, TenantId};
 use elf_runtime_model_kernel::ArcParameterJoint;
 use std::sync::Arc;
 
@@ -39,4 +39,15 @@ impl CompiledConditional {
             // returns false when no condition
             .unwrap_or(Ok(false))
     }
+
+    /// snapshot with resolved values, for monitor log. returns none when no condition
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<Option<MonitorLogParameterJoint>> {
+        self.inner
+            .as_ref()
+            .map(|inner| inner.snapshot(variables))
+            .transpose()
+    }
 }
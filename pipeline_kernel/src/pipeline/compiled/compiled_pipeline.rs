@@ -1,12 +1,18 @@
-use crate::{CompiledConditional, PipelineExecutable, PipelineExecution};
+use crate::{
+	ArcTopicDataConverter, CompiledConditional, CompiledStage, MonitorLogStopwatch,
+	PipelineExecutable, PipelineExecution,
+};
 use elf_base::StdR;
+use elf_model::{MonitorLogStage, MonitorLogStatus, PipelineMonitorLog, PipelineMonitorLogId};
 use elf_runtime_model_kernel::{PipelineSchema, TopicSchema};
+use std::ops::Deref;
 use std::sync::Arc;
 
 pub struct CompiledPipeline {
     topic: Arc<TopicSchema>,
     pipeline: Arc<PipelineSchema>,
     conditional: CompiledConditional,
+    stages: Vec<CompiledStage>,
 }
 
 impl CompiledPipeline {
@@ -16,24 +22,105 @@ impl CompiledPipeline {
     ) -> StdR<Self> {
         let pipeline = pipeline_schema.pipeline();
         let conditional = CompiledConditional::new(&pipeline.on, &pipeline.tenant_id)?;
+        let mut stages = vec![];
+        for stage in pipeline.stages.deref() {
+            stages.push(CompiledStage::compile(stage, &pipeline.tenant_id)?);
+        }
 
         Ok(Self {
             topic: topic_schema,
             pipeline: pipeline_schema,
             conditional,
+            stages,
         })
     }
 
+    pub fn pipeline(&self) -> &Arc<PipelineSchema> {
+        &self.pipeline
+    }
+
+    /// execute stages one by one,
+    /// returns the downstream executions created by write/delete actions,
+    /// or none when there is no downstream execution.
+    /// monitor log is written when pipeline finished, no matter it is successful or not.
+    /// writes are committed after all stages done when unit of work required.
     pub async fn execute(
         &self,
         executable: PipelineExecutable,
+        monitor_log_id: Arc<PipelineMonitorLogId>,
+        retry_of: Option<Arc<PipelineMonitorLogId>>,
     ) -> StdR<Option<Vec<PipelineExecution>>> {
-        let variables = executable.variables;
-        if self.conditional.is_true(&variables)? {
+        let mut executable = executable;
+        let stopwatch = MonitorLogStopwatch::start();
+        let mut log = PipelineMonitorLog::new()
+            .uid(monitor_log_id.deref().clone())
+            .trace_id(executable.trace_id.deref().clone())
+            .pipeline_id(self.pipeline.pipeline_id().deref().clone())
+            .topic_id(self.topic.topic_id().deref().clone())
+            .data_id(executable.topic_trigger.internal_data_id.deref().clone())
+            .start_time(stopwatch.start_time());
+        log.retry_of = retry_of.map(|retry_of| retry_of.deref().clone());
+        log.old_value = executable
+            .topic_trigger
+            .previous
+            .as_ref()
+            .map(|previous| previous.to_topic_data());
+        log.new_value = executable
+            .topic_trigger
+            .current
+            .as_ref()
+            .map(|current| current.to_topic_data());
+
+        let mut stage_logs = vec![];
+        let result = self
+            .execute_stages(&mut executable, &mut log, &mut stage_logs)
+            .await;
+        let result = executable.end_unit_of_work(result);
+
+        log.stages = Some(stage_logs);
+        log.spent_in_mills = Some(stopwatch.spent_in_mills());
+        match &result {
+            Ok(_) => {
+                log.status = Some(MonitorLogStatus::DONE);
+                executable.log_monitor.executed(
+                    self.pipeline.pipeline_id(),
+                    log.prerequisite.unwrap_or(true),
+                );
+            }
+            Err(error) => {
+                log.status = Some(MonitorLogStatus::ERROR);
+                log.error = Some(error.to_string());
+            }
+        }
+        executable.log_monitor.write(log);
+
+        result
+    }
+
+    async fn execute_stages(
+        &self,
+        executable: &mut PipelineExecutable,
+        log: &mut PipelineMonitorLog,
+        stage_logs: &mut Vec<MonitorLogStage>,
+    ) -> StdR<Option<Vec<PipelineExecution>>> {
+        let prerequisite = !self.conditional.is_false(&executable.variables)?;
+        log.prerequisite_defined_as = self.conditional.snapshot(&executable.variables)?;
+        log.prerequisite = Some(prerequisite);
+        if !prerequisite {
             // skip the execution because doesn't meet the prerequisite
+            return Ok(None);
+        }
+
+        executable.begin_unit_of_work()?;
+        let mut executions = vec![];
+        for stage in &self.stages {
+            executions.extend(stage.execute(executable, stage_logs).await?);
+        }
+
+        if executions.is_empty() {
             Ok(None)
         } else {
-            todo!("implement execute for CompiledPipeline")
+            Ok(Some(executions))
         }
     }
 }
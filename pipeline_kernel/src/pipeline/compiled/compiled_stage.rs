@@ -0,0 +1,85 @@
+use crate::{
+	CompiledConditional, CompiledUnit, MonitorLogStopwatch, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::{MonitorLogStage, MonitorLogStatus, MonitorLogUnit, TenantId};
+use elf_runtime_model_kernel::ArcPipelineStage;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledStage {
+    stage: Arc<ArcPipelineStage>,
+    conditional: CompiledConditional,
+    units: Vec<CompiledUnit>,
+}
+
+impl CompiledStage {
+    pub fn compile(stage: &Arc<ArcPipelineStage>, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let conditional = CompiledConditional::new(&stage.on, tenant_id)?;
+        let mut units = vec![];
+        for unit in stage.units.deref() {
+            units.push(CompiledUnit::compile(unit, tenant_id)?);
+        }
+
+        Ok(CompiledStage {
+            stage: stage.clone(),
+            conditional,
+            units,
+        })
+    }
+
+    /// execute units one by one, returns the downstream executions created by units.
+    /// returns empty when stage doesn't meet its condition.
+    /// monitor log of stage is appended into given logs, no matter it is successful or not.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        stage_logs: &mut Vec<MonitorLogStage>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let stopwatch = MonitorLogStopwatch::start();
+        let mut log = MonitorLogStage::new()
+            .stage_id(self.stage.stage_id.deref().clone())
+            .name(self.stage.name.deref().clone())
+            .start_time(stopwatch.start_time());
+
+        let mut unit_logs = vec![];
+        let result = self
+            .execute_units(executable, &mut log, &mut unit_logs)
+            .await;
+
+        log.units = Some(unit_logs);
+        log.spent_in_mills = Some(stopwatch.spent_in_mills());
+        match &result {
+            Ok(_) => log.status = Some(MonitorLogStatus::DONE),
+            Err(error) => {
+                log.status = Some(MonitorLogStatus::ERROR);
+                log.error = Some(error.to_string());
+            }
+        }
+        stage_logs.push(log);
+
+        result
+    }
+
+    async fn execute_units(
+        &self,
+        executable: &mut PipelineExecutable,
+        log: &mut MonitorLogStage,
+        unit_logs: &mut Vec<MonitorLogUnit>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let prerequisite = !self.conditional.is_false(&executable.variables)?;
+        log.prerequisite_defined_as = self.conditional.snapshot(&executable.variables)?;
+        log.prerequisite = Some(prerequisite);
+        if !prerequisite {
+            // skip the stage because doesn't meet the condition
+            return Ok(vec![]);
+        }
+
+        let mut executions = vec![];
+        for unit in &self.units {
+            executable.check_interrupted()?;
+            executions.extend(unit.execute(executable, unit_logs).await?);
+        }
+        Ok(executions)
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+	ArcTopicDataValue, CompiledAction, CompiledConditional, DataPath, DataVisitor,
+	MonitorLogStopwatch, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::{MonitorLogAction, MonitorLogStatus, MonitorLogUnit, PipelineActionId, TenantId};
+use elf_runtime_model_kernel::ArcPipelineUnit;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledUnit {
+    unit: Arc<ArcPipelineUnit>,
+    /// loop variable name and its parsed path
+    r#loop: Option<(Arc<String>, DataPath)>,
+    conditional: CompiledConditional,
+    /// action id and compiled action
+    actions: Vec<(Arc<PipelineActionId>, CompiledAction)>,
+}
+
+impl CompiledUnit {
+    pub fn compile(unit: &Arc<ArcPipelineUnit>, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let r#loop = match &unit.loop_variable_name {
+            Some(name) if !name.trim().is_empty() => {
+                Some((name.clone(), DataPath::from_str(name.as_str())?))
+            }
+            _ => None,
+        };
+        let conditional = CompiledConditional::new(&unit.on, tenant_id)?;
+        let mut actions = vec![];
+        for action in unit.r#do.deref() {
+            actions.push((
+                action.action_id().clone(),
+                CompiledAction::compile(action, tenant_id)?,
+            ));
+        }
+
+        Ok(CompiledUnit {
+            unit: unit.clone(),
+            r#loop,
+            conditional,
+            actions,
+        })
+    }
+
+    /// execute actions one by one, returns the downstream executions created by actions.
+    /// returns empty when unit doesn't meet its condition.
+    /// monitor log of unit is appended into given logs, no matter it is successful or not,
+    /// loop element is recorded when unit is executed on it.
+    async fn execute_once(
+        &self,
+        executable: &mut PipelineExecutable,
+        element: Option<&Arc<ArcTopicDataValue>>,
+        unit_logs: &mut Vec<MonitorLogUnit>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let stopwatch = MonitorLogStopwatch::start();
+        let mut log = MonitorLogUnit::new()
+            .unit_id(self.unit.unit_id.deref().clone())
+            .name(self.unit.name.deref().clone())
+            .start_time(stopwatch.start_time());
+        if let (Some((name, _)), Some(element)) = (&self.r#loop, element) {
+            log.loop_variable_name = Some(name.deref().clone());
+            log.loop_variable_value = Some(element.to_topic_data_value());
+        }
+
+        let mut action_logs = vec![];
+        let result = self
+            .execute_actions(executable, &mut log, &mut action_logs)
+            .await;
+
+        log.actions = Some(action_logs);
+        log.spent_in_mills = Some(stopwatch.spent_in_mills());
+        match &result {
+            Ok(_) => log.status = Some(MonitorLogStatus::DONE),
+            Err(error) => {
+                log.status = Some(MonitorLogStatus::ERROR);
+                log.error = Some(error.to_string());
+            }
+        }
+        unit_logs.push(log);
+
+        result
+    }
+
+    async fn execute_actions(
+        &self,
+        executable: &mut PipelineExecutable,
+        log: &mut MonitorLogUnit,
+        action_logs: &mut Vec<MonitorLogAction>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let prerequisite = !self.conditional.is_false(&executable.variables)?;
+        log.prerequisite_defined_as = self.conditional.snapshot(&executable.variables)?;
+        log.prerequisite = Some(prerequisite);
+        if !prerequisite {
+            // skip the unit because doesn't meet the condition
+            return Ok(vec![]);
+        }
+
+        let mut executions = vec![];
+        for (action_id, action) in &self.actions {
+            executable.check_interrupted()?;
+            executions.extend(action.execute(action_id, executable, action_logs).await?);
+        }
+        Ok(executions)
+    }
+
+    /// execute unit, when loop variable name declared, the unit is executed on each element of it.
+    /// each element is put into a child scope with loop variable name,
+    /// the condition of unit is checked on each child scope.
+    /// - vec: execute on each element,
+    /// - none: not executed,
+    /// - others: treated as a vec which contains only this value.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        unit_logs: &mut Vec<MonitorLogUnit>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let (name, path) = match &self.r#loop {
+            Some(r#loop) => r#loop,
+            _ => return self.execute_once(executable, None, unit_logs).await,
+        };
+
+        let value = executable.variables.value_of(path)?;
+        let elements = match value.deref() {
+            ArcTopicDataValue::Vec(vec) => vec.deref().clone(),
+            ArcTopicDataValue::None => vec![],
+            _ => vec![value.clone()],
+        };
+
+        let mut executions = vec![];
+        for element in elements {
+            let mut child = executable.create_child(name.deref().clone(), element.clone());
+            executions.extend(
+                self.execute_once(&mut child, Some(&element), unit_logs)
+                    .await?,
+            );
+        }
+        Ok(executions)
+    }
+}
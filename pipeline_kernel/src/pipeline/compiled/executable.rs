@@ -1,13 +1,39 @@
-use crate::{PipelineExecutionLogMonitor, PipelineExecutionVariables, TopicTrigger};
+use crate::{
+	ArcTopicDataValue, PipelineExecution, PipelineExecutionEnvironment,
+	PipelineExecutionLogMonitor, PipelineExecutionVariables, PipelineKernelErrorCode,
+	PipelineUnitOfWork, TenantSequences, TopicDataStore, TopicTrigger,
+};
 use elf_auth::Principal;
-use elf_model::PipelineTriggerTraceId;
+use elf_base::{ErrorCode, StdErr, StdR, VoidR};
+use elf_model::{PipelineTriggerTraceId, PipelineTriggerType};
+use elf_runtime_model_kernel::{PipelineSchemaProvider, PipelineService, TopicSchema};
 use std::sync::Arc;
+use std::time::Instant;
+
+/// deadline of pipeline execution, the error is raised when it is exceeded
+pub struct PipelineExecutionDeadline {
+    pub at: Instant,
+    pub code: PipelineKernelErrorCode,
+    pub message: String,
+}
+
+impl PipelineExecutionDeadline {
+    pub fn error(&self) -> StdErr {
+        self.code.err_with_msg(self.message.clone())
+    }
+}
 
 pub struct PipelineExecutable {
+    pub topic_trigger: Arc<TopicTrigger>,
     pub variables: PipelineExecutionVariables,
     pub principal: Arc<Principal>,
     pub trace_id: Arc<PipelineTriggerTraceId>,
     pub log_monitor: Arc<PipelineExecutionLogMonitor>,
+    pub environment: Arc<PipelineExecutionEnvironment>,
+    /// buffers writes of pipeline when unit of work required, otherwise none
+    pub unit_of_work: Option<Arc<PipelineUnitOfWork>>,
+    /// no deadline when none
+    pub deadline: Option<Arc<PipelineExecutionDeadline>>,
 }
 
 impl PipelineExecutable {
@@ -16,15 +42,186 @@ impl PipelineExecutable {
         principal: Arc<Principal>,
         trace_id: Arc<PipelineTriggerTraceId>,
         log_monitor: Arc<PipelineExecutionLogMonitor>,
+        environment: Arc<PipelineExecutionEnvironment>,
     ) -> Self {
         PipelineExecutable {
             variables: PipelineExecutionVariables::new(
                 topic_trigger.previous.clone(),
                 topic_trigger.current.clone(),
-            ),
+            )
+            .in_tenant(Arc::new(TenantSequences {
+                tenant_id: Arc::new(principal.tenant_id.clone()),
+                store: environment.sequence_store(),
+            })),
+            topic_trigger,
             principal,
             trace_id,
             log_monitor,
+            environment,
+            unit_of_work: None,
+            deadline: None,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Option<Arc<PipelineExecutionDeadline>>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// returns error when run is cancelled or deadline exceeded.
+    /// checked before each unit and action, since synchronous store calls cannot be interrupted.
+    pub fn check_interrupted(&self) -> VoidR {
+        if self.environment.cancellation.is_cancelled() {
+            return PipelineKernelErrorCode::PipelineRunCancelled.msg(format!(
+                "Pipeline run of trace[{}] cancelled.",
+                self.trace_id
+            ));
+        }
+        match &self.deadline {
+            Some(deadline) if Instant::now() >= deadline.at => Err(deadline.error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// start unit of work when it is required by environment
+    pub fn begin_unit_of_work(&mut self) -> VoidR {
+        if self.environment.unit_of_work {
+            self.unit_of_work = Some(Arc::new(PipelineUnitOfWork::new(
+                self.environment.topic_data()?,
+            )));
         }
+        Ok(())
+    }
+
+    /// commit the buffered writes when given result is ok, otherwise discard them.
+    /// returns error when commit or discard failed, the failure of discard is appended to given error.
+    pub fn end_unit_of_work<R>(&mut self, result: StdR<R>) -> StdR<R> {
+        let unit_of_work = match self.unit_of_work.take() {
+            Some(unit_of_work) => unit_of_work,
+            _ => return result,
+        };
+        match result {
+            Ok(r) => unit_of_work.commit().map(|_| r),
+            Err(error) => match unit_of_work.discard() {
+                Ok(_) => Err(error),
+                Err(e) => PipelineKernelErrorCode::UnitOfWorkNotDiscarded.msg(format!(
+                    "Pipeline failed, caused by {}, and unit of work not discarded, caused by {}.",
+                    error, e
+                )),
+            },
+        }
+    }
+
+    /// topic data store, the unit of work buffer when it is started
+    pub fn topic_data(&self) -> StdR<Arc<dyn TopicDataStore>> {
+        match &self.unit_of_work {
+            Some(unit_of_work) => Ok(unit_of_work.data()),
+            _ => self.environment.topic_data(),
+        }
+    }
+
+    /// create a child executable, which has a child scope variables,
+    /// and given variable is put into the child scope.
+    pub fn create_child(&self, name: String, value: Arc<ArcTopicDataValue>) -> Self {
+        let mut variables = self.variables.create_child();
+        variables.put_variable(name, value);
+
+        PipelineExecutable {
+            topic_trigger: self.topic_trigger.clone(),
+            variables,
+            principal: self.principal.clone(),
+            trace_id: self.trace_id.clone(),
+            log_monitor: self.log_monitor.clone(),
+            environment: self.environment.clone(),
+            unit_of_work: self.unit_of_work.clone(),
+            deadline: self.deadline.clone(),
+        }
+    }
+
+    /// pipeline of insert-or-merge type is triggered by both insert and merge,
+    /// otherwise requires the same type as trigger.
+    fn is_triggered_by(
+        pipeline_type: &PipelineTriggerType,
+        trigger_type: &PipelineTriggerType,
+    ) -> bool {
+        match pipeline_type {
+            PipelineTriggerType::InsertOrMerge => matches!(
+                trigger_type,
+                PipelineTriggerType::Insert
+                    | PipelineTriggerType::Merge
+                    | PipelineTriggerType::InsertOrMerge
+            ),
+            _ => pipeline_type == trigger_type,
+        }
+    }
+
+    /// create downstream executions for given topic trigger,
+    /// which is created by write/delete actions.
+    /// the pipelines are defined on given topic, and triggered by the type of topic trigger.
+    /// downstream executions share the principal, trace id, log monitor and environment of current execution.
+    /// the touched row is recorded by log monitor, no matter there is downstream execution or not.
+    pub fn create_next_executions(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        topic_trigger: Arc<TopicTrigger>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        self.log_monitor
+            .touched(topic_schema.topic_id(), &topic_trigger.r#type);
+
+        let pipelines = PipelineService::schema()?
+            .by_topic_id(topic_schema.topic_id(), &self.principal.tenant_id)?;
+
+        pipelines
+            .unwrap_or(vec![])
+            .into_iter()
+            .filter(|pipeline| {
+                Self::is_triggered_by(pipeline.r#type().as_ref(), &topic_trigger.r#type)
+            })
+            .map(|pipeline_schema| {
+                Ok(PipelineExecution {
+                    topic_schema: topic_schema.clone(),
+                    pipeline_schema,
+                    topic_trigger: topic_trigger.clone(),
+                    monitor_log_id: PipelineExecution::next_monitor_log_id()?,
+                    retry_of: None,
+                    // env
+                    principal: self.principal.clone(),
+                    trace_id: self.trace_id.clone(),
+                    execution_log_monitor: self.log_monitor.clone(),
+                    execution_environment: self.environment.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PipelineExecutable;
+    use elf_model::PipelineTriggerType;
+
+    #[test]
+    fn test_cascade_trigger_type() {
+        let insert_or_merge = PipelineTriggerType::InsertOrMerge;
+        assert!(PipelineExecutable::is_triggered_by(
+            &insert_or_merge,
+            &PipelineTriggerType::Insert
+        ));
+        assert!(PipelineExecutable::is_triggered_by(
+            &insert_or_merge,
+            &PipelineTriggerType::Merge
+        ));
+        assert!(!PipelineExecutable::is_triggered_by(
+            &insert_or_merge,
+            &PipelineTriggerType::Delete
+        ));
+        assert!(PipelineExecutable::is_triggered_by(
+            &PipelineTriggerType::Merge,
+            &PipelineTriggerType::Merge
+        ));
+        assert!(!PipelineExecutable::is_triggered_by(
+            &PipelineTriggerType::Insert,
+            &PipelineTriggerType::Merge
+        ));
     }
 }
@@ -1,15 +1,21 @@
-use crate::{ArcTopicData, PipelineKernelErrorCode};
+use crate::{
+	ArcTopicData, ArcTopicDataValue, DataPath, DataPathSegment, DataVisitor, DataVisitorBase,
+	PipelineKernelErrorCode, TenantSequences,
+};
 use elf_base::{ErrorCode, StdR};
-use elf_model::TopicData;
+use elf_model::VariablePredefineFunctions;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 pub struct PipelineExecutionVariables {
     pub previous_data: Option<ArcTopicData>,
     pub current_data: Option<ArcTopicData>,
-    pub variables: TopicData,
+    pub variables: ArcTopicData,
     // only variables from trigger data will record its factor name here
     // key is variable key, value is factor name
     pub variables_from: HashMap<String, String>,
+    /// sequences of pipeline tenant, used by [&nextSeq(name)]
+    pub sequences: Option<Arc<TenantSequences>>,
 }
 
 impl PipelineExecutionVariables {
@@ -17,11 +23,54 @@ impl PipelineExecutionVariables {
         PipelineExecutionVariables {
             previous_data: previous,
             current_data: current,
-            variables: HashMap::new(),
+            variables: Arc::new(HashMap::new()),
             variables_from: HashMap::new(),
+            sequences: None,
         }
     }
 
+    pub fn in_tenant(mut self, sequences: Arc<TenantSequences>) -> Self {
+        self.sequences = Some(sequences);
+        self
+    }
+
+    /// create a child scope, which shares the trigger data and variables of this.
+    /// variables put into child scope are invisible to this.
+    pub fn create_child(&self) -> Self {
+        PipelineExecutionVariables {
+            previous_data: self.previous_data.clone(),
+            current_data: self.current_data.clone(),
+            variables: self.variables.clone(),
+            variables_from: self.variables_from.clone(),
+            sequences: self.sequences.clone(),
+        }
+    }
+
+    /// create a backward scope, which treats previous trigger data as current.
+    /// variables from trigger data are read from previous trigger data again,
+    /// other variables are shared.
+    /// returns none when previous trigger data not exists.
+    pub fn backward(&self) -> StdR<Option<Self>> {
+        let previous_data = match &self.previous_data {
+            Some(previous_data) => previous_data,
+            _ => return Ok(None),
+        };
+
+        let mut variables = self.variables.clone();
+        for (name, factor_name) in self.variables_from.iter() {
+            let value = previous_data.value_of(&DataPath::from_str(factor_name)?)?;
+            Arc::make_mut(&mut variables).insert(name.clone(), value);
+        }
+
+        Ok(Some(PipelineExecutionVariables {
+            previous_data: None,
+            current_data: Some(previous_data.clone()),
+            variables,
+            variables_from: self.variables_from.clone(),
+            sequences: self.sequences.clone(),
+        }))
+    }
+
     /// get current topic data.
     /// raise error when current data not exists
     pub fn get_current_data(&self) -> StdR<&ArcTopicData> {
@@ -31,4 +80,110 @@ impl PipelineExecutionVariables {
                 .msg("Current trigger data is missed."),
         }
     }
+
+    pub fn get_variable(&self, name: &String) -> Option<&Arc<ArcTopicDataValue>> {
+        self.variables.get(name)
+    }
+
+    /// put variable into current scope, replace the existing one if exists
+    pub fn put_variable(&mut self, name: String, value: Arc<ArcTopicDataValue>) {
+        self.variables_from.remove(&name);
+        Arc::make_mut(&mut self.variables).insert(name, value);
+    }
+}
+
+impl PipelineExecutionVariables {
+    /// get value from given trigger data by the segments after the first one,
+    /// returns none when trigger data not exists
+    fn value_of_trigger_data(
+        &self,
+        data: &Option<ArcTopicData>,
+        path: &DataPath,
+    ) -> StdR<Arc<ArcTopicDataValue>> {
+        match data {
+            Some(data) => data.value_of_segments(
+                &path.path.to_string(),
+                &path.segments[1..],
+                self.sequences.as_deref(),
+            ),
+            _ => Ok(Arc::new(ArcTopicDataValue::None)),
+        }
+    }
+}
+
+impl DataVisitor for PipelineExecutionVariables {
+    /// - path starts with [&cur] or [&old], find value from current or previous trigger data,
+    /// - find value from variables first, when the first segment of path is a plain one,
+    ///   and it is not declared in variables, find value from current trigger data.
+    fn value_of(&self, path: &DataPath) -> StdR<Arc<ArcTopicDataValue>> {
+        let from_variables = match path.segments.first() {
+            Some(DataPathSegment::Plain(segment)) => self
+                .variables
+                .contains_key(&segment.path.to_unescaped_string()),
+            Some(DataPathSegment::Func(segment)) if segment.params.is_none() => {
+                match segment.func {
+                    VariablePredefineFunctions::FromCurrentContext => {
+                        return self.value_of_trigger_data(&self.current_data, path);
+                    }
+                    VariablePredefineFunctions::FromPreviousTriggerData => {
+                        return self.value_of_trigger_data(&self.previous_data, path);
+                    }
+                    _ => true,
+                }
+            }
+            _ => true,
+        };
+
+        let sequences = self.sequences.as_deref();
+        if from_variables {
+            self.variables.value_of_path_in_tenant(path, sequences)
+        } else if let Some(current_data) = &self.current_data {
+            current_data.value_of_path_in_tenant(path, sequences)
+        } else {
+            Ok(Arc::new(ArcTopicDataValue::None))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcTopicDataValue, DataPath, DataVisitor, PipelineExecutionVariables};
+    use bigdecimal::BigDecimal;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_child_scope() {
+        let mut current_data = HashMap::new();
+        current_data.insert(
+            "a".to_string(),
+            Arc::new(ArcTopicDataValue::Str(Arc::new("x".to_string()))),
+        );
+        let mut variables = PipelineExecutionVariables::new(None, Some(Arc::new(current_data)));
+        variables.put_variable(
+            "b".to_string(),
+            Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(1)))),
+        );
+
+        let mut child = variables.create_child();
+        child.put_variable(
+            "c".to_string(),
+            Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(2)))),
+        );
+
+        // from current data
+        let value = child.value_of(&DataPath::from_str("a").unwrap()).unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == "x"));
+        // from parent scope
+        let value = child.value_of(&DataPath::from_str("b").unwrap()).unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::Num(n) if **n == BigDecimal::from(1)));
+        // from child scope, invisible to parent scope
+        let value = child.value_of(&DataPath::from_str("c").unwrap()).unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::Num(n) if **n == BigDecimal::from(2)));
+        let value = variables
+            .value_of(&DataPath::from_str("c").unwrap())
+            .unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::None));
+    }
 }
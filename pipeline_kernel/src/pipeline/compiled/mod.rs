@@ -1,9 +1,13 @@
 mod compiled_conditional;
 mod compiled_pipeline;
+mod compiled_stage;
+mod compiled_unit;
 mod executable;
 mod execution_variables;
 
 pub use compiled_conditional::*;
 pub use compiled_pipeline::*;
+pub use compiled_stage::*;
+pub use compiled_unit::*;
 pub use executable::*;
 pub use execution_variables::*;
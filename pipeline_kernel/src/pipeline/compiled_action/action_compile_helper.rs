@@ -0,0 +1,29 @@
+use crate::PipelineKernelErrorCode;
+use elf_base::{ErrorCode, StdR};
+use elf_model::{FactorId, TenantId, TopicId};
+use elf_runtime_model_kernel::{ArcFactor, TopicSchema, TopicSchemaProvider, TopicService};
+use std::sync::Arc;
+
+pub trait ActionCompileHelper {
+    /// find topic schema by given topic id, which is the target topic of action
+    fn topic_schema(topic_id: &Arc<TopicId>, tenant_id: &Arc<TenantId>) -> StdR<Arc<TopicSchema>> {
+        TopicService::schema()?.by_id(topic_id, tenant_id)
+    }
+
+    /// find factor from given topic schema, raise error when not found
+    fn factor(topic_schema: &Arc<TopicSchema>, factor_id: &Arc<FactorId>) -> StdR<Arc<ArcFactor>> {
+        match topic_schema
+            .topic()
+            .factors
+            .iter()
+            .find(|factor| factor.factor_id == *factor_id)
+        {
+            Some(factor) => Ok(factor.clone()),
+            _ => PipelineKernelErrorCode::FactorNotFound.msg(format!(
+                "Factor[{}] not found in topic[{}].",
+                factor_id,
+                topic_schema.topic_id()
+            )),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+	ActionMonitorLog, CompiledAlarmMessage, CompiledConditional, PipelineAlarm, PipelineExecutable,
+	PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::{AlarmActionSeverity, PipelineActionId, TenantId, TopicDataValue};
+use elf_runtime_model_kernel::ArcAlarmAction;
+use std::sync::Arc;
+
+pub struct CompiledAlarmAction {
+    action_id: Arc<PipelineActionId>,
+    severity: Arc<AlarmActionSeverity>,
+    message: CompiledAlarmMessage,
+    conditional: CompiledConditional,
+}
+
+impl CompiledAlarmAction {
+    pub fn compile(action: &ArcAlarmAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        Ok(CompiledAlarmAction {
+            action_id: action.action_id.clone(),
+            severity: action.severity.clone(),
+            message: CompiledAlarmMessage::new(&action.message)?,
+            conditional: CompiledConditional::new(&action.on, tenant_id)?,
+        })
+    }
+
+    /// emit alarm to alarm sink when condition holds, captured when simulating.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        if self.conditional.is_false(&executable.variables)? {
+            return Ok(vec![]);
+        }
+
+        let message = self.message.render(&executable.variables)?;
+        monitor.touch(TopicDataValue::Str(message.clone()));
+        executable.environment.alarm(PipelineAlarm {
+            tenant_id: executable.principal.tenant_id.clone(),
+            trace_id: executable.trace_id.clone(),
+            action_id: Some(self.action_id.clone()),
+            severity: self.severity.clone(),
+            message,
+        })?;
+
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{ArcTopicDataValue, DataPath, DataVisitor, PipelineExecutionVariables};
+use elf_base::{StdR, StringConverter};
+use std::sync::Arc;
+
+enum CompiledAlarmMessageSegment {
+    Text(String),
+    Path(DataPath),
+}
+
+/// alarm message, the `{path}` in message is replaced by value of path from variables.
+/// unclosed `{` and blank `{}` are kept as text.
+pub struct CompiledAlarmMessage {
+    segments: Vec<CompiledAlarmMessageSegment>,
+}
+
+impl CompiledAlarmMessage {
+    pub fn new(message: &Option<Arc<String>>) -> StdR<Self> {
+        let mut segments = vec![];
+        if let Some(message) = message {
+            let mut text = String::new();
+            let mut rest = message.as_str();
+            while let Some(start) = rest.find('{') {
+                let (before, after) = rest.split_at(start);
+                text.push_str(before);
+                match after.find('}') {
+                    Some(end) if !after[1..end].trim().is_empty() => {
+                        if !text.is_empty() {
+                            segments.push(CompiledAlarmMessageSegment::Text(text));
+                            text = String::new();
+                        }
+                        segments.push(CompiledAlarmMessageSegment::Path(DataPath::from_str(
+                            after[1..end].trim(),
+                        )?));
+                        rest = &after[end + 1..];
+                    }
+                    _ => {
+                        text.push('{');
+                        rest = &after[1..];
+                    }
+                }
+            }
+            text.push_str(rest);
+            if !text.is_empty() {
+                segments.push(CompiledAlarmMessageSegment::Text(text));
+            }
+        }
+
+        Ok(CompiledAlarmMessage { segments })
+    }
+
+    pub fn render(&self, variables: &PipelineExecutionVariables) -> StdR<String> {
+        let mut message = String::new();
+        for segment in self.segments.iter() {
+            match segment {
+                CompiledAlarmMessageSegment::Text(text) => message.push_str(text),
+                CompiledAlarmMessageSegment::Path(path) => {
+                    message.push_str(&Self::to_text(variables.value_of(path)?.as_ref()))
+                }
+            }
+        }
+        Ok(message)
+    }
+
+    /// none is rendered as empty string, elements of vec are joined by comma
+    fn to_text(value: &ArcTopicDataValue) -> String {
+        match value {
+            ArcTopicDataValue::None => String::new(),
+            ArcTopicDataValue::Str(v) => v.to_string(),
+            ArcTopicDataValue::Num(v) => String::from_decimal(v),
+            ArcTopicDataValue::Bool(v) => String::from_bool(v),
+            ArcTopicDataValue::Date(v) => String::from_date(v),
+            ArcTopicDataValue::Time(v) => String::from_time(v),
+            ArcTopicDataValue::DateTime(v) => String::from_datetime(v),
+            ArcTopicDataValue::Vec(vec) => vec
+                .iter()
+                .map(|v| Self::to_text(v))
+                .collect::<Vec<String>>()
+                .join(","),
+            ArcTopicDataValue::Map(_) => String::from("map"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcTopicDataValue, CompiledAlarmMessage, PipelineExecutionVariables};
+    use bigdecimal::BigDecimal;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_render() {
+        let current_data = HashMap::from([
+            (
+                "name".to_string(),
+                Arc::new(ArcTopicDataValue::Str(Arc::new("x".to_string()))),
+            ),
+            (
+                "amount".to_string(),
+                Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(100)))),
+            ),
+        ]);
+        let variables = PipelineExecutionVariables::new(None, Some(Arc::new(current_data)));
+
+        let message = CompiledAlarmMessage::new(&Some(Arc::new(
+            "Order of {name} is {amount}, { } and {missing} and {unclosed".to_string(),
+        )))
+        .unwrap();
+        assert_eq!(
+            message.render(&variables).unwrap(),
+            "Order of x is 100, { } and  and {unclosed"
+        );
+        let message = CompiledAlarmMessage::new(&None).unwrap();
+        assert_eq!(message.render(&variables).unwrap(), "");
+    }
+}
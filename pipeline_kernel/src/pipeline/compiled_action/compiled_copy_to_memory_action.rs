@@ -0,0 +1,37 @@
+use crate::{
+	ActionMonitorLog, CompiledParameter, InMemoryParameter, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::ArcCopyToMemoryAction;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledCopyToMemoryAction {
+    source: CompiledParameter,
+    variable_name: Arc<String>,
+}
+
+impl CompiledCopyToMemoryAction {
+    pub fn compile(action: &ArcCopyToMemoryAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        Ok(CompiledCopyToMemoryAction {
+            source: CompiledParameter::new(&action.source, tenant_id)?,
+            variable_name: action.variable_name.clone(),
+        })
+    }
+
+    /// evaluate source, and put the value into variables.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let value = self.source.value_from(&executable.variables)?;
+        monitor.touch(value.to_topic_data_value());
+        executable
+            .variables
+            .put_variable(self.variable_name.deref().clone(), value);
+
+        Ok(vec![])
+    }
+}
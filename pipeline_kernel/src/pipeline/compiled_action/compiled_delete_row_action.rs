@@ -0,0 +1,55 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, CompiledCriteriaJoint, PipelineExecutable,
+	PipelineExecution, PipelineKernelErrorCode, TopicTrigger,
+};
+use elf_base::{ErrorCode, StdR};
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcDeleteRowAction, TopicSchema};
+use std::sync::Arc;
+
+pub struct CompiledDeleteRowAction {
+    topic_schema: Arc<TopicSchema>,
+    by: CompiledCriteriaJoint,
+}
+
+impl ActionCompileHelper for CompiledDeleteRowAction {}
+
+impl CompiledDeleteRowAction {
+    pub fn compile(action: &ArcDeleteRowAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+
+        Ok(CompiledDeleteRowAction { topic_schema, by })
+    }
+
+    /// delete the only row which matches criteria, and create downstream executions by delete trigger.
+    /// do nothing when no row matched, raise error when more than one row matched.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let data_service = executable.topic_data()?;
+        let mut rows = data_service.find(&self.topic_schema, &criteria)?;
+        if rows.len() > 1 {
+            return PipelineKernelErrorCode::TopicDataMatchedMoreThanOne.msg(format!(
+                "Topic data matched more than one[count={}] by criteria[{:?}] of topic[{}], cannot delete.",
+                rows.len(),
+                criteria,
+                self.topic_schema.topic_id()
+            ));
+        }
+
+        match rows.pop() {
+            Some(row) => {
+                let deleted = data_service.delete(&self.topic_schema, row)?;
+                monitor.deleted(&deleted);
+                executable
+                    .create_next_executions(&self.topic_schema, TopicTrigger::delete(deleted)?)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
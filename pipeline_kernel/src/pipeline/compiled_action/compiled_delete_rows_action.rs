@@ -0,0 +1,48 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, CompiledCriteriaJoint, PipelineExecutable,
+	PipelineExecution, TopicTrigger,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcDeleteRowsAction, TopicSchema};
+use std::sync::Arc;
+
+pub struct CompiledDeleteRowsAction {
+    topic_schema: Arc<TopicSchema>,
+    by: CompiledCriteriaJoint,
+}
+
+impl ActionCompileHelper for CompiledDeleteRowsAction {}
+
+impl CompiledDeleteRowsAction {
+    pub fn compile(action: &ArcDeleteRowsAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+
+        Ok(CompiledDeleteRowsAction { topic_schema, by })
+    }
+
+    /// delete all rows which match criteria, and create downstream executions by delete trigger for each row.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let data_service = executable.topic_data()?;
+        let rows = data_service.find(&self.topic_schema, &criteria)?;
+
+        let mut executions = vec![];
+        for row in rows {
+            let deleted = data_service.delete(&self.topic_schema, row)?;
+            monitor.deleted(&deleted);
+            executions.extend(
+                executable
+                    .create_next_executions(&self.topic_schema, TopicTrigger::delete(deleted)?)?,
+            );
+        }
+
+        Ok(executions)
+    }
+}
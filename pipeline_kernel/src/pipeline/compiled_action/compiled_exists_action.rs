@@ -0,0 +1,50 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, ArcTopicDataValue, CompiledCriteriaJoint,
+	PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::{TenantId, TopicDataValue};
+use elf_runtime_model_kernel::{ArcExistsAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledExistsAction {
+    topic_schema: Arc<TopicSchema>,
+    by: CompiledCriteriaJoint,
+    variable_name: Arc<String>,
+}
+
+impl ActionCompileHelper for CompiledExistsAction {}
+
+impl CompiledExistsAction {
+    pub fn compile(action: &ArcExistsAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+
+        Ok(CompiledExistsAction {
+            topic_schema,
+            by,
+            variable_name: action.variable_name.clone(),
+        })
+    }
+
+    /// check there is any row matches criteria, and put the result into variables as a bool.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let exists = executable
+            .topic_data()?
+            .exists(&self.topic_schema, &criteria)?;
+        monitor.touch(TopicDataValue::Bool(exists));
+        executable.variables.put_variable(
+            self.variable_name.deref().clone(),
+            Arc::new(ArcTopicDataValue::Bool(exists)),
+        );
+
+        Ok(vec![])
+    }
+}
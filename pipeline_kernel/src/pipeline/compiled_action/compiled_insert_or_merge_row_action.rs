@@ -0,0 +1,87 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, CompiledCriteriaJoint, CompiledMappingFactor,
+	PipelineExecutable, PipelineExecution, PipelineKernelErrorCode, TopicTrigger, VersionedMerge,
+};
+use elf_base::{ErrorCode, StdR};
+use elf_model::{AccumulateMode, TenantId};
+use elf_runtime_model_kernel::{ArcInsertOrMergeRowAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledInsertOrMergeRowAction {
+    topic_schema: Arc<TopicSchema>,
+    accumulate_mode: Arc<AccumulateMode>,
+    mapping: Vec<CompiledMappingFactor>,
+    by: CompiledCriteriaJoint,
+}
+
+impl ActionCompileHelper for CompiledInsertOrMergeRowAction {}
+
+impl CompiledInsertOrMergeRowAction {
+    /// reverse mode is not allowed on insert or merge action.
+    pub fn compile(action: &ArcInsertOrMergeRowAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        if let AccumulateMode::Reverse = action.accumulate_mode.deref() {
+            return PipelineKernelErrorCode::AccumulateModeNotSupported.msg(format!(
+                "Accumulate mode[{}] is not supported on insert or merge row action.",
+                action.accumulate_mode
+            ));
+        }
+
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+        let mapping =
+            CompiledMappingFactor::compile_all(&action.mapping, &topic_schema, tenant_id)?;
+
+        Ok(CompiledInsertOrMergeRowAction {
+            topic_schema,
+            accumulate_mode: action.accumulate_mode.clone(),
+            mapping,
+            by,
+        })
+    }
+
+    /// merge into the only row which matches criteria, or insert a new row when no row matched.
+    /// create downstream executions by merge or insert trigger.
+    /// merged with version checked, re-read and recompute the accumulation on conflict.
+    /// value in previous data is taken back from the row which matches criteria of previous data,
+    /// see [VersionedMerge::accumulate].
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let data_service = executable.topic_data()?;
+        let merged = VersionedMerge::accumulate(
+            &data_service,
+            &self.topic_schema,
+            &self.by,
+            &self.mapping,
+            &self.accumulate_mode,
+            &executable.variables,
+            monitor,
+        )?;
+        let topic_trigger = match merged.current {
+            Some((previous, current)) => {
+                monitor.updated(&current);
+                TopicTrigger::merge(previous, current)?
+            }
+            _ => {
+                let row = CompiledMappingFactor::insert_row(&self.mapping, &executable.variables)?;
+                let inserted = data_service.insert(&self.topic_schema, row)?;
+                monitor.inserted(&inserted);
+                TopicTrigger::insert(inserted)?
+            }
+        };
+
+        let mut executions =
+            executable.create_next_executions(&self.topic_schema, topic_trigger)?;
+        if let Some((previous, current)) = merged.previous {
+            monitor.updated(&current);
+            executions.extend(executable.create_next_executions(
+                &self.topic_schema,
+                TopicTrigger::merge(previous, current)?,
+            )?);
+        }
+        Ok(executions)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, CompiledMappingFactor, PipelineExecutable,
+	PipelineExecution, PipelineKernelErrorCode, TopicTrigger,
+};
+use elf_base::{ErrorCode, StdR};
+use elf_model::{AccumulateMode, TenantId};
+use elf_runtime_model_kernel::{ArcInsertRowAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledInsertRowAction {
+    topic_schema: Arc<TopicSchema>,
+    mapping: Vec<CompiledMappingFactor>,
+}
+
+impl ActionCompileHelper for CompiledInsertRowAction {}
+
+impl CompiledInsertRowAction {
+    /// reverse mode is not allowed on insert action,
+    /// and cumulate mode behaves same as standard mode.
+    pub fn compile(action: &ArcInsertRowAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        if let AccumulateMode::Reverse = action.accumulate_mode.deref() {
+            return PipelineKernelErrorCode::AccumulateModeNotSupported.msg(format!(
+                "Accumulate mode[{}] is not supported on insert row action.",
+                action.accumulate_mode
+            ));
+        }
+
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let mapping =
+            CompiledMappingFactor::compile_all(&action.mapping, &topic_schema, tenant_id)?;
+
+        Ok(CompiledInsertRowAction {
+            topic_schema,
+            mapping,
+        })
+    }
+
+    /// insert a new row, and create downstream executions by insert trigger.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let row = CompiledMappingFactor::insert_row(&self.mapping, &executable.variables)?;
+        let inserted = executable.topic_data()?.insert(&self.topic_schema, row)?;
+        monitor.inserted(&inserted);
+
+        executable.create_next_executions(&self.topic_schema, TopicTrigger::insert(inserted)?)
+    }
+}
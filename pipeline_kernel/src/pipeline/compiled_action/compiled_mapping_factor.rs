@@ -0,0 +1,427 @@
+use crate::{
+	ActionCompileHelper, ArcTopicData, ArcTopicDataBuilder, ArcTopicDataValue, CompiledParameter,
+	InMemoryParameter, PipelineExecutionVariables, PipelineKernelErrorCode,
+};
+use bigdecimal::{BigDecimal, Zero};
+use elf_base::{ErrorCode, StdR, StringUtils, VoidR};
+use elf_model::{
+	AccumulateMode, AggregateArithmetic, TenantId, TopicData, TopicDataColumnNames, TopicDataValue,
+};
+use elf_runtime_model_kernel::{ArcFactor, ArcMappingFactor, TopicSchema};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledMappingFactor {
+    source: CompiledParameter,
+    factor: Arc<ArcFactor>,
+    arithmetic: Arc<AggregateArithmetic>,
+}
+
+impl ActionCompileHelper for CompiledMappingFactor {}
+
+impl CompiledMappingFactor {
+    pub fn new(
+        source: CompiledParameter,
+        factor: Arc<ArcFactor>,
+        arithmetic: Arc<AggregateArithmetic>,
+    ) -> Self {
+        CompiledMappingFactor {
+            source,
+            factor,
+            arithmetic,
+        }
+    }
+
+    pub fn compile(
+        mapping: &Arc<ArcMappingFactor>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Self> {
+        Ok(CompiledMappingFactor {
+            source: CompiledParameter::new(&mapping.source, tenant_id)?,
+            factor: Self::factor(topic_schema, &mapping.factor_id)?,
+            arithmetic: mapping.arithmetic.clone(),
+        })
+    }
+
+    pub fn compile_all(
+        mapping: &Arc<Vec<Arc<ArcMappingFactor>>>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Vec<Self>> {
+        let mut factors = vec![];
+        for mapping_factor in mapping.iter() {
+            factors.push(Self::compile(mapping_factor, topic_schema, tenant_id)?);
+        }
+        Ok(factors)
+    }
+
+    /// build a new row by given mappings, value in current data is added only.
+    pub fn insert_row(mapping: &[Self], variables: &PipelineExecutionVariables) -> StdR<TopicData> {
+        let mut row = HashMap::new();
+        for mapping_factor in mapping.iter() {
+            mapping_factor.accumulate(&mut row, Some(variables), None)?;
+        }
+        Ok(row)
+    }
+
+    /// current and previous to accumulate, by given accumulate mode
+    /// - standard: subtract value in previous data, add value in current data,
+    /// - reverse: subtract value in previous data,
+    /// - cumulate: add value in current data.
+    pub fn accumulation_of<'a>(
+        accumulate_mode: &AccumulateMode,
+        variables: &'a PipelineExecutionVariables,
+    ) -> StdR<(
+        Option<&'a PipelineExecutionVariables>,
+        Option<PipelineExecutionVariables>,
+    )> {
+        match accumulate_mode {
+            AccumulateMode::Standard => Ok((Some(variables), variables.backward()?)),
+            AccumulateMode::Reverse => Ok((None, variables.backward()?)),
+            AccumulateMode::Cumulate => Ok((Some(variables), None)),
+        }
+    }
+
+    /// merge given mappings into existing row,
+    /// subtract value in previous data, add value in current data.
+    pub fn merge_row(
+        mapping: &[Self],
+        mut row: TopicData,
+        current: Option<&PipelineExecutionVariables>,
+        previous: Option<&PipelineExecutionVariables>,
+    ) -> StdR<TopicData> {
+        for mapping_factor in mapping.iter() {
+            mapping_factor.accumulate(&mut row, current, previous)?;
+        }
+        Ok(row)
+    }
+
+    /// write factor value into given row.
+    /// for no arithmetic, value in current data replaces the existing one, keep existing when no current.
+    /// for aggregate arithmetic, subtract contribution of previous, add contribution of current.
+    /// average count is kept in aggregate assist column, keyed by factor name.
+    fn accumulate(
+        &self,
+        row: &mut TopicData,
+        current: Option<&PipelineExecutionVariables>,
+        previous: Option<&PipelineExecutionVariables>,
+    ) -> VoidR {
+        match self.arithmetic.deref() {
+            AggregateArithmetic::None => match current {
+                Some(current) => {
+                    let value = self.source.value_from(current)?.to_topic_data_value();
+                    self.set_value(row, value)
+                }
+                _ => Ok(()),
+            },
+            AggregateArithmetic::Sum => {
+                let mut sum = self.get_existing_decimal(row)?;
+                if let Some(previous) = previous {
+                    sum -= self.get_decimal_from(previous)?;
+                }
+                if let Some(current) = current {
+                    sum += self.get_decimal_from(current)?;
+                }
+                self.set_value(row, TopicDataValue::Num(sum))
+            }
+            AggregateArithmetic::Count => {
+                let mut count = self.get_existing_decimal(row)?;
+                if previous.is_some() {
+                    count -= BigDecimal::from(1);
+                }
+                if current.is_some() {
+                    count += BigDecimal::from(1);
+                }
+                self.set_value(row, TopicDataValue::Num(count))
+            }
+            AggregateArithmetic::Avg => {
+                let mut count = self.get_avg_count(row)?;
+                let mut total = self.get_existing_decimal(row)? * &count;
+                if let Some(previous) = previous {
+                    total -= self.get_decimal_from(previous)?;
+                    count -= BigDecimal::from(1);
+                }
+                if let Some(current) = current {
+                    total += self.get_decimal_from(current)?;
+                    count += BigDecimal::from(1);
+                }
+                let avg = if count.is_zero() {
+                    BigDecimal::zero()
+                } else {
+                    total / &count
+                };
+                self.set_value(row, TopicDataValue::Num(avg))?;
+                self.set_avg_count(row, count);
+                Ok(())
+            }
+        }
+    }
+
+    /// none and blank string are treated as 0
+    fn to_decimal(value: &ArcTopicDataValue) -> StdR<BigDecimal> {
+        match value {
+            ArcTopicDataValue::None => Ok(BigDecimal::zero()),
+            ArcTopicDataValue::Str(str) if str.is_blank() => Ok(BigDecimal::zero()),
+            _ => Ok(value.try_to_decimal()?.deref().clone()),
+        }
+    }
+
+    fn get_decimal_from(&self, variables: &PipelineExecutionVariables) -> StdR<BigDecimal> {
+        Self::to_decimal(self.source.value_from(variables)?.deref())
+    }
+
+    fn get_existing_decimal(&self, row: &TopicData) -> StdR<BigDecimal> {
+        match self.get_value(row) {
+            Some(value) => Self::to_decimal(ArcTopicData::build_value(value.clone()).deref()),
+            _ => Ok(BigDecimal::zero()),
+        }
+    }
+
+    fn get_avg_count(&self, row: &TopicData) -> StdR<BigDecimal> {
+        let count = match row.get(TopicDataColumnNames::AggregateAssist.column_name()) {
+            Some(TopicDataValue::Map(assist)) => assist.get(self.factor.name.as_str()),
+            _ => None,
+        };
+        match count {
+            Some(count) => Self::to_decimal(ArcTopicData::build_value(count.clone()).deref()),
+            _ => Ok(BigDecimal::zero()),
+        }
+    }
+
+    fn set_avg_count(&self, row: &mut TopicData, count: BigDecimal) {
+        let assist = row
+            .entry(
+                TopicDataColumnNames::AggregateAssist
+                    .column_name()
+                    .to_string(),
+            )
+            .or_insert(TopicDataValue::None);
+        if !matches!(assist, TopicDataValue::Map(_)) {
+            *assist = TopicDataValue::Map(HashMap::new());
+        }
+        if let TopicDataValue::Map(assist) = assist {
+            assist.insert(self.factor.name.deref().clone(), TopicDataValue::Num(count));
+        }
+    }
+
+    /// get value of factor from given row, factor name is split by dot.
+    fn get_value<'a>(&self, row: &'a TopicData) -> Option<&'a TopicDataValue> {
+        let mut segments = self.factor.name.split('.');
+        let mut value = row.get(segments.next()?)?;
+        for segment in segments {
+            match value {
+                TopicDataValue::Map(map) => value = map.get(segment)?,
+                _ => return None,
+            }
+        }
+        Some(value)
+    }
+
+    /// set value of factor into given row, factor name is split by dot.
+    /// the missing parents are created as map,
+    /// raise error when any existing parent is not a map.
+    fn set_value(&self, row: &mut TopicData, value: TopicDataValue) -> VoidR {
+        let segments: Vec<&str> = self.factor.name.split('.').collect();
+        let (last, parents) = match segments.split_last() {
+            Some(split) => split,
+            _ => return Ok(()),
+        };
+
+        let mut map = row;
+        for segment in parents {
+            let parent = map
+                .entry(segment.to_string())
+                .or_insert(TopicDataValue::None);
+            if matches!(parent, TopicDataValue::None) {
+                *parent = TopicDataValue::Map(HashMap::new());
+            }
+            map = match parent {
+                TopicDataValue::Map(parent) => parent,
+                _ => {
+                    return PipelineKernelErrorCode::FactorValueNotWritable.msg(format!(
+                        "Cannot write value of factor[{}], since its parent[{}] is not a map.",
+                        self.factor.name, segment
+                    ));
+                }
+            };
+        }
+        map.insert(last.to_string(), value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcTopicDataValue, CompiledConstantParameter, CompiledMappingFactor, CompiledParameter,
+        DataPath, PipelineExecutionVariables,
+    };
+    use bigdecimal::{BigDecimal, One};
+    use elf_model::{
+        AccumulateMode, AggregateArithmetic, Factor, FactorType, TopicData, TopicDataColumnNames,
+        TopicDataValue,
+    };
+    use elf_runtime_model_kernel::ArcFactor;
+    use std::collections::HashMap;
+    use std::slice;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    /// value of mapping is read from factor "a" of trigger data
+    fn create_mapping(name: &str, arithmetic: AggregateArithmetic) -> CompiledMappingFactor {
+        let factor = Factor::new()
+            .factor_id(String::from("f1"))
+            .name(String::from(name))
+            .r#type(FactorType::Number);
+        CompiledMappingFactor::new(
+            CompiledParameter::Constant(CompiledConstantParameter::Path(
+                DataPath::from_str("a").unwrap(),
+            )),
+            ArcFactor::new(factor).unwrap(),
+            Arc::new(arithmetic),
+        )
+    }
+
+    /// value of factor "a" is 2 in previous data, and 5 in current data
+    fn create_variables(with_previous: bool) -> PipelineExecutionVariables {
+        let data_of = |value: i32| {
+            Arc::new(HashMap::from([(
+                "a".to_string(),
+                Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(value)))),
+            )]))
+        };
+        PipelineExecutionVariables::new(with_previous.then(|| data_of(2)), Some(data_of(5)))
+    }
+
+    fn merge(
+        mapping: &CompiledMappingFactor,
+        mode: AccumulateMode,
+        row: TopicData,
+        with_previous: bool,
+    ) -> TopicData {
+        let variables = create_variables(with_previous);
+        let (current, previous) =
+            CompiledMappingFactor::accumulation_of(&mode, &variables).unwrap();
+        CompiledMappingFactor::merge_row(slice::from_ref(mapping), row, current, previous.as_ref())
+            .unwrap()
+    }
+
+    fn decimal_of(value: Option<&TopicDataValue>) -> BigDecimal {
+        match value {
+            Some(TopicDataValue::Num(value)) => value.clone(),
+            _ => panic!("factor value not written"),
+        }
+    }
+
+    fn count_of(mode: AccumulateMode, with_previous: bool) -> BigDecimal {
+        let mapping = create_mapping("x.count", AggregateArithmetic::Count);
+        let row = HashMap::from([(
+            "x".to_string(),
+            TopicDataValue::Map(HashMap::from([(
+                "count".to_string(),
+                TopicDataValue::Num(BigDecimal::from(5)),
+            )])),
+        )]);
+        let row = merge(&mapping, mode, row, with_previous);
+        match row.get("x") {
+            Some(TopicDataValue::Map(x)) => decimal_of(x.get("count")),
+            _ => panic!("nested factor value not written"),
+        }
+    }
+
+    fn sum_of(mode: AccumulateMode, with_previous: bool) -> BigDecimal {
+        let mapping = create_mapping("sum", AggregateArithmetic::Sum);
+        let row = HashMap::from([("sum".to_string(), TopicDataValue::Num(BigDecimal::from(10)))]);
+        decimal_of(merge(&mapping, mode, row, with_previous).get("sum"))
+    }
+
+    /// existing average is 4 of 3 rows, returns average and count
+    fn avg_of(mode: AccumulateMode, with_previous: bool) -> (BigDecimal, BigDecimal) {
+        let mapping = create_mapping("avg", AggregateArithmetic::Avg);
+        let assist = TopicDataColumnNames::AggregateAssist.column_name();
+        let row = HashMap::from([
+            ("avg".to_string(), TopicDataValue::Num(BigDecimal::from(4))),
+            (
+                assist.to_string(),
+                TopicDataValue::Map(HashMap::from([(
+                    "avg".to_string(),
+                    TopicDataValue::Num(BigDecimal::from(3)),
+                )])),
+            ),
+        ]);
+        let row = merge(&mapping, mode, row, with_previous);
+        let count = match row.get(assist) {
+            Some(TopicDataValue::Map(assist)) => decimal_of(assist.get("avg")),
+            _ => panic!("average count not written"),
+        };
+        (decimal_of(row.get("avg")), count)
+    }
+
+    #[test]
+    fn test_accumulate_count() {
+        // previous is subtracted, current is added
+        assert_eq!(
+            count_of(AccumulateMode::Standard, true),
+            BigDecimal::from(5)
+        );
+        assert_eq!(
+            count_of(AccumulateMode::Standard, false),
+            BigDecimal::from(6)
+        );
+        assert_eq!(count_of(AccumulateMode::Reverse, true), BigDecimal::from(4));
+        assert_eq!(
+            count_of(AccumulateMode::Reverse, false),
+            BigDecimal::from(5)
+        );
+        assert_eq!(
+            count_of(AccumulateMode::Cumulate, true),
+            BigDecimal::from(6)
+        );
+
+        let mapping = vec![create_mapping("x.count", AggregateArithmetic::Count)];
+        let row = CompiledMappingFactor::insert_row(&mapping, &create_variables(true)).unwrap();
+        assert!(matches!(
+            row.get("x"),
+            Some(TopicDataValue::Map(x))
+                if matches!(x.get("count"), Some(TopicDataValue::Num(n)) if n.is_one())
+        ));
+    }
+
+    #[test]
+    fn test_accumulate_sum() {
+        // 10 - 2 + 5
+        assert_eq!(sum_of(AccumulateMode::Standard, true), BigDecimal::from(13));
+        assert_eq!(
+            sum_of(AccumulateMode::Standard, false),
+            BigDecimal::from(15)
+        );
+        assert_eq!(sum_of(AccumulateMode::Reverse, true), BigDecimal::from(8));
+        assert_eq!(sum_of(AccumulateMode::Reverse, false), BigDecimal::from(10));
+    }
+
+    #[test]
+    fn test_accumulate_avg() {
+        // total 12 of 3 rows, previous 2 is replaced by current 5
+        assert_eq!(
+            avg_of(AccumulateMode::Standard, true),
+            (BigDecimal::from(5), BigDecimal::from(3))
+        );
+        // total 17 of 4 rows
+        assert_eq!(
+            avg_of(AccumulateMode::Standard, false),
+            (BigDecimal::from_str("4.25").unwrap(), BigDecimal::from(4))
+        );
+        // total 10 of 2 rows
+        assert_eq!(
+            avg_of(AccumulateMode::Reverse, true),
+            (BigDecimal::from(5), BigDecimal::from(2))
+        );
+        assert_eq!(
+            avg_of(AccumulateMode::Reverse, false),
+            (BigDecimal::from(4), BigDecimal::from(3))
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{
+	ActionMonitorLog, CompiledAlarmAction, CompiledCopyToMemoryAction, CompiledDeleteRowAction,
+	CompiledDeleteRowsAction, CompiledExistsAction, CompiledInsertOrMergeRowAction,
+	CompiledInsertRowAction, CompiledMergeRowAction, CompiledReadFactorAction,
+	CompiledReadFactorsAction, CompiledReadRowAction, CompiledReadRowsAction,
+	CompiledWriteFactorAction, CompiledWriteToExternalAction, MonitorLogStopwatch,
+	PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::{
+	MonitorLogAction, MonitorLogStatus, PipelineActionId, PipelineActionType, TenantId,
+};
+use elf_runtime_model_kernel::{ArcPipelineAction, IdGen};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub enum CompiledAction {
+    Alarm(CompiledAlarmAction),
+    CopyToMemory(CompiledCopyToMemoryAction),
+    WriteToExternal(CompiledWriteToExternalAction),
+    ReadRow(CompiledReadRowAction),
+    ReadFactor(CompiledReadFactorAction),
+    Exists(CompiledExistsAction),
+    ReadRows(CompiledReadRowsAction),
+    ReadFactors(CompiledReadFactorsAction),
+    MergeRow(CompiledMergeRowAction),
+    InsertRow(CompiledInsertRowAction),
+    InsertOrMergeRow(CompiledInsertOrMergeRowAction),
+    WriteFactor(CompiledWriteFactorAction),
+    DeleteRow(CompiledDeleteRowAction),
+    DeleteRows(CompiledDeleteRowsAction),
+}
+
+impl CompiledAction {
+    pub fn compile(action: &Arc<ArcPipelineAction>, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        match action.deref() {
+            ArcPipelineAction::Alarm(v) => {
+                CompiledAlarmAction::compile(v, tenant_id).map(CompiledAction::Alarm)
+            }
+            ArcPipelineAction::CopyToMemory(v) => {
+                CompiledCopyToMemoryAction::compile(v, tenant_id).map(CompiledAction::CopyToMemory)
+            }
+            ArcPipelineAction::WriteToExternal(v) => {
+                CompiledWriteToExternalAction::compile(v, tenant_id)
+                    .map(CompiledAction::WriteToExternal)
+            }
+            ArcPipelineAction::ReadRow(v) => {
+                CompiledReadRowAction::compile(v, tenant_id).map(CompiledAction::ReadRow)
+            }
+            ArcPipelineAction::ReadFactor(v) => {
+                CompiledReadFactorAction::compile(v, tenant_id).map(CompiledAction::ReadFactor)
+            }
+            ArcPipelineAction::Exists(v) => {
+                CompiledExistsAction::compile(v, tenant_id).map(CompiledAction::Exists)
+            }
+            ArcPipelineAction::ReadRows(v) => {
+                CompiledReadRowsAction::compile(v, tenant_id).map(CompiledAction::ReadRows)
+            }
+            ArcPipelineAction::ReadFactors(v) => {
+                CompiledReadFactorsAction::compile(v, tenant_id).map(CompiledAction::ReadFactors)
+            }
+            ArcPipelineAction::MergeRow(v) => {
+                CompiledMergeRowAction::compile(v, tenant_id).map(CompiledAction::MergeRow)
+            }
+            ArcPipelineAction::InsertRow(v) => {
+                CompiledInsertRowAction::compile(v, tenant_id).map(CompiledAction::InsertRow)
+            }
+            ArcPipelineAction::InsertOrMergeRow(v) => {
+                CompiledInsertOrMergeRowAction::compile(v, tenant_id)
+                    .map(CompiledAction::InsertOrMergeRow)
+            }
+            ArcPipelineAction::WriteFactor(v) => {
+                CompiledWriteFactorAction::compile(v, tenant_id).map(CompiledAction::WriteFactor)
+            }
+            ArcPipelineAction::DeleteRow(v) => {
+                CompiledDeleteRowAction::compile(v, tenant_id).map(CompiledAction::DeleteRow)
+            }
+            ArcPipelineAction::DeleteRows(v) => {
+                CompiledDeleteRowsAction::compile(v, tenant_id).map(CompiledAction::DeleteRows)
+            }
+        }
+    }
+
+    pub fn r#type(&self) -> PipelineActionType {
+        match self {
+            Self::Alarm(_) => PipelineActionType::Alarm,
+            Self::CopyToMemory(_) => PipelineActionType::CopyToMemory,
+            Self::WriteToExternal(_) => PipelineActionType::WriteToExternal,
+            Self::ReadRow(_) => PipelineActionType::ReadRow,
+            Self::ReadFactor(_) => PipelineActionType::ReadFactor,
+            Self::Exists(_) => PipelineActionType::Exists,
+            Self::ReadRows(_) => PipelineActionType::ReadRows,
+            Self::ReadFactors(_) => PipelineActionType::ReadFactors,
+            Self::MergeRow(_) => PipelineActionType::MergeRow,
+            Self::InsertRow(_) => PipelineActionType::InsertRow,
+            Self::InsertOrMergeRow(_) => PipelineActionType::InsertOrMergeRow,
+            Self::WriteFactor(_) => PipelineActionType::WriteFactor,
+            Self::DeleteRow(_) => PipelineActionType::DeleteRow,
+            Self::DeleteRows(_) => PipelineActionType::DeleteRows,
+        }
+    }
+
+    /// execute action, returns the downstream executions.
+    /// only write and delete actions create downstream executions,
+    /// which are triggered by the changed data of target topic.
+    /// monitor log of action is appended into given logs, no matter it is successful or not.
+    pub async fn execute(
+        &self,
+        action_id: &Arc<PipelineActionId>,
+        executable: &mut PipelineExecutable,
+        action_logs: &mut Vec<MonitorLogAction>,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let stopwatch = MonitorLogStopwatch::start();
+        let log = MonitorLogAction::new()
+            .uid(IdGen::next_id()?.to_string())
+            .action_id(action_id.deref().clone())
+            .r#type(self.r#type())
+            .start_time(stopwatch.start_time());
+
+        let mut monitor = ActionMonitorLog::new();
+        let result = self.execute_action(executable, &mut monitor).await;
+
+        let mut log = monitor.fill(log);
+        log.spent_in_mills = Some(stopwatch.spent_in_mills());
+        match &result {
+            Ok(_) => log.status = Some(MonitorLogStatus::DONE),
+            Err(error) => {
+                log.status = Some(MonitorLogStatus::ERROR);
+                log.error = Some(error.to_string());
+            }
+        }
+        action_logs.push(log);
+
+        result
+    }
+
+    async fn execute_action(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        match self {
+            Self::Alarm(v) => v.execute(executable, monitor).await,
+            Self::CopyToMemory(v) => v.execute(executable, monitor).await,
+            Self::WriteToExternal(v) => v.execute(executable, monitor).await,
+            Self::ReadRow(v) => v.execute(executable, monitor).await,
+            Self::ReadFactor(v) => v.execute(executable, monitor).await,
+            Self::Exists(v) => v.execute(executable, monitor).await,
+            Self::ReadRows(v) => v.execute(executable, monitor).await,
+            Self::ReadFactors(v) => v.execute(executable, monitor).await,
+            Self::MergeRow(v) => v.execute(executable, monitor).await,
+            Self::InsertRow(v) => v.execute(executable, monitor).await,
+            Self::InsertOrMergeRow(v) => v.execute(executable, monitor).await,
+            Self::WriteFactor(v) => v.execute(executable, monitor).await,
+            Self::DeleteRow(v) => v.execute(executable, monitor).await,
+            Self::DeleteRows(v) => v.execute(executable, monitor).await,
+        }
+    }
+}
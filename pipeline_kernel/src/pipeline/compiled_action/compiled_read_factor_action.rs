@@ -0,0 +1,99 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, ArcTopicData, ArcTopicDataBuilder, ArcTopicDataValue,
+	CompiledCriteriaJoint, DataPath, DataVisitor, PipelineExecutable, PipelineExecution,
+	PipelineKernelErrorCode, TopicDataStore,
+};
+use bigdecimal::BigDecimal;
+use elf_base::{ErrorCode, StdR};
+use elf_model::{AggregateArithmetic, TenantId};
+use elf_runtime_model_kernel::{ArcFactor, ArcReadFactorAction, TopicDataCriteria, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledReadFactorAction {
+    topic_schema: Arc<TopicSchema>,
+    factor: Arc<ArcFactor>,
+    /// path to read factor value from the found row
+    factor_path: DataPath,
+    by: CompiledCriteriaJoint,
+    variable_name: Arc<String>,
+    arithmetic: Arc<AggregateArithmetic>,
+}
+
+impl ActionCompileHelper for CompiledReadFactorAction {}
+
+impl CompiledReadFactorAction {
+    pub fn compile(action: &ArcReadFactorAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+        let factor = Self::factor(&topic_schema, &action.factor_id)?;
+        let factor_path = DataPath::from_factor(&factor, &topic_schema)?;
+
+        Ok(CompiledReadFactorAction {
+            topic_schema,
+            factor,
+            factor_path,
+            by,
+            variable_name: action.variable_name.clone(),
+            arithmetic: action.arithmetic.clone(),
+        })
+    }
+
+    /// read factor value by criteria, and put it into variables.
+    /// - no arithmetic: read from the only matched row, none when no row found,
+    /// - count: count of matched rows,
+    /// - sum/avg: aggregate factor values of matched rows.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let data_service = executable.topic_data()?;
+        let value = match self.arithmetic.deref() {
+            AggregateArithmetic::None => {
+                match data_service.find_one(&self.topic_schema, &criteria)? {
+                    Some(row) => ArcTopicData::build(row).value_of(&self.factor_path)?,
+                    _ => Arc::new(ArcTopicDataValue::None),
+                }
+            }
+            AggregateArithmetic::Count => {
+                let count = data_service.count(&self.topic_schema, &criteria)?;
+                Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(count))))
+            }
+            AggregateArithmetic::Sum => self
+                .find_values(&data_service, &criteria)?
+                .sum(|| self.aggregate_not_supported("sum"))?,
+            AggregateArithmetic::Avg => self
+                .find_values(&data_service, &criteria)?
+                .avg(|| self.aggregate_not_supported("avg"))?,
+        };
+        monitor.touch(value.to_topic_data_value());
+        executable
+            .variables
+            .put_variable(self.variable_name.deref().clone(), value);
+
+        Ok(vec![])
+    }
+
+    fn find_values(
+        &self,
+        data_service: &Arc<dyn TopicDataStore>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<ArcTopicDataValue> {
+        let values = data_service.find_values(&self.topic_schema, &self.factor, criteria)?;
+        Ok(ArcTopicDataValue::Vec(Arc::new(ArcTopicData::build_vec(
+            values,
+        ))))
+    }
+
+    fn aggregate_not_supported<R>(&self, func: &str) -> StdR<R> {
+        PipelineKernelErrorCode::VariableFuncNotSupported.msg(format!(
+            "Cannot {} values of factor[{}] of topic[{}].",
+            func,
+            self.factor.name,
+            self.topic_schema.topic_id()
+        ))
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, ArcTopicData, ArcTopicDataBuilder, ArcTopicDataValue,
+	CompiledCriteriaJoint, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcFactor, ArcReadFactorsAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledReadFactorsAction {
+    topic_schema: Arc<TopicSchema>,
+    factor: Arc<ArcFactor>,
+    by: CompiledCriteriaJoint,
+    variable_name: Arc<String>,
+}
+
+impl ActionCompileHelper for CompiledReadFactorsAction {}
+
+impl CompiledReadFactorsAction {
+    pub fn compile(action: &ArcReadFactorsAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+        let factor = Self::factor(&topic_schema, &action.factor_id)?;
+
+        Ok(CompiledReadFactorsAction {
+            topic_schema,
+            factor,
+            by,
+            variable_name: action.variable_name.clone(),
+        })
+    }
+
+    /// find values of factor from rows which match criteria, and put them into variables as a vec.
+    /// put empty vec when no row found.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let values =
+            executable
+                .topic_data()?
+                .find_values(&self.topic_schema, &self.factor, &criteria)?;
+        let value = ArcTopicDataValue::Vec(Arc::new(ArcTopicData::build_vec(values)));
+        monitor.touch(value.to_topic_data_value());
+        executable
+            .variables
+            .put_variable(self.variable_name.deref().clone(), Arc::new(value));
+
+        Ok(vec![])
+    }
+}
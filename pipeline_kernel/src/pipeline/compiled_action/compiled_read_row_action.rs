@@ -0,0 +1,54 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, ArcTopicData, ArcTopicDataBuilder, ArcTopicDataValue,
+	CompiledCriteriaJoint, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcReadRowAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledReadRowAction {
+    topic_schema: Arc<TopicSchema>,
+    by: CompiledCriteriaJoint,
+    variable_name: Arc<String>,
+}
+
+impl ActionCompileHelper for CompiledReadRowAction {}
+
+impl CompiledReadRowAction {
+    pub fn compile(action: &ArcReadRowAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+
+        Ok(CompiledReadRowAction {
+            topic_schema,
+            by,
+            variable_name: action.variable_name.clone(),
+        })
+    }
+
+    /// find the only row by criteria, and put it into variables.
+    /// put none when no row found.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let row = executable
+            .topic_data()?
+            .find_one(&self.topic_schema, &criteria)?;
+        let value = match row {
+            Some(row) => ArcTopicDataValue::Map(ArcTopicData::build(row)),
+            _ => ArcTopicDataValue::None,
+        };
+        monitor.touch(value.to_topic_data_value());
+        executable
+            .variables
+            .put_variable(self.variable_name.deref().clone(), Arc::new(value));
+
+        Ok(vec![])
+    }
+}
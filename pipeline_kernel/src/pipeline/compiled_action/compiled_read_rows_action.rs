@@ -0,0 +1,55 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, ArcTopicData, ArcTopicDataBuilder, ArcTopicDataValue,
+	CompiledCriteriaJoint, PipelineExecutable, PipelineExecution,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcReadRowsAction, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledReadRowsAction {
+    topic_schema: Arc<TopicSchema>,
+    by: CompiledCriteriaJoint,
+    variable_name: Arc<String>,
+}
+
+impl ActionCompileHelper for CompiledReadRowsAction {}
+
+impl CompiledReadRowsAction {
+    pub fn compile(action: &ArcReadRowsAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+
+        Ok(CompiledReadRowsAction {
+            topic_schema,
+            by,
+            variable_name: action.variable_name.clone(),
+        })
+    }
+
+    /// find rows by criteria, and put them into variables as a vec.
+    /// put empty vec when no row found.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let criteria = self.by.to_criteria(&executable.variables)?;
+        monitor.find_by(&criteria);
+        let rows = executable
+            .topic_data()?
+            .find(&self.topic_schema, &criteria)?;
+        let value = ArcTopicDataValue::Vec(Arc::new(
+            rows.into_iter()
+                .map(|row| Arc::new(ArcTopicDataValue::Map(ArcTopicData::build(row))))
+                .collect(),
+        ));
+        monitor.touch(value.to_topic_data_value());
+        executable
+            .variables
+            .put_variable(self.variable_name.deref().clone(), Arc::new(value));
+
+        Ok(vec![])
+    }
+}
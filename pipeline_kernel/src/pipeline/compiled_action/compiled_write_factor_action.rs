@@ -0,0 +1,83 @@
+use crate::{
+	ActionCompileHelper, ActionMonitorLog, CompiledCriteriaJoint, CompiledMappingFactor,
+	CompiledParameter, PipelineExecutable, PipelineExecution, PipelineKernelErrorCode,
+	TopicTrigger, VersionedMerge,
+};
+use elf_base::{ErrorCode, StdR};
+use elf_model::{AccumulateMode, TenantId};
+use elf_runtime_model_kernel::{ArcWriteFactorAction, TopicSchema};
+use std::slice;
+use std::sync::Arc;
+
+pub struct CompiledWriteFactorAction {
+    topic_schema: Arc<TopicSchema>,
+    accumulate_mode: Arc<AccumulateMode>,
+    /// source, factor and arithmetic are same as a mapping factor
+    mapping: CompiledMappingFactor,
+    by: CompiledCriteriaJoint,
+}
+
+impl ActionCompileHelper for CompiledWriteFactorAction {}
+
+impl CompiledWriteFactorAction {
+    pub fn compile(action: &ArcWriteFactorAction, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let topic_schema = Self::topic_schema(&action.topic_id, tenant_id)?;
+        let by = CompiledCriteriaJoint::new(&action.by, &topic_schema, tenant_id)?;
+        let mapping = CompiledMappingFactor::new(
+            CompiledParameter::new(&action.source, tenant_id)?,
+            Self::factor(&topic_schema, &action.factor_id)?,
+            action.arithmetic.clone(),
+        );
+
+        Ok(CompiledWriteFactorAction {
+            topic_schema,
+            accumulate_mode: action.accumulate_mode.clone(),
+            mapping,
+            by,
+        })
+    }
+
+    /// write factor into the only row which matches criteria, and create downstream executions by merge trigger.
+    /// raise error when no row matched.
+    /// merged with version checked, re-read and recompute the accumulation on conflict.
+    /// value in previous data is taken back from the row which matches criteria of previous data,
+    /// see [VersionedMerge::accumulate].
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let data_service = executable.topic_data()?;
+        let merged = VersionedMerge::accumulate(
+            &data_service,
+            &self.topic_schema,
+            &self.by,
+            slice::from_ref(&self.mapping),
+            &self.accumulate_mode,
+            &executable.variables,
+            monitor,
+        )?;
+        let (previous, current) = match merged.current {
+            Some(merged) => merged,
+            _ => {
+                return PipelineKernelErrorCode::TopicDataNotFound.msg(format!(
+                    "Topic data not found by criteria[{:?}] of topic[{}], cannot write factor.",
+                    self.by.to_criteria(&executable.variables)?,
+                    self.topic_schema.topic_id()
+                ));
+            }
+        };
+        monitor.updated(&current);
+
+        let mut executions = executable
+            .create_next_executions(&self.topic_schema, TopicTrigger::merge(previous, current)?)?;
+        if let Some((previous, current)) = merged.previous {
+            monitor.updated(&current);
+            executions.extend(executable.create_next_executions(
+                &self.topic_schema,
+                TopicTrigger::merge(previous, current)?,
+            )?);
+        }
+        Ok(executions)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{ActionMonitorLog, ArcTopicDataConverter, PipelineExecutable, PipelineExecution};
+use elf_base::StdR;
+use elf_model::{ExternalWriterId, TenantId};
+use elf_runtime_model_kernel::{ArcWriteToExternalAction, ExternalWriteParams};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledWriteToExternalAction {
+    external_writer_id: Arc<ExternalWriterId>,
+    event_code: Arc<String>,
+}
+
+impl CompiledWriteToExternalAction {
+    pub fn compile(action: &ArcWriteToExternalAction, _tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        Ok(CompiledWriteToExternalAction {
+            external_writer_id: action.external_writer_id.clone(),
+            event_code: action.event_code.clone(),
+        })
+    }
+
+    /// write trigger data and variables to external writer,
+    /// the adapter is chosen by type of external writer, captured when simulating.
+    pub async fn execute(
+        &self,
+        executable: &mut PipelineExecutable,
+        _monitor: &mut ActionMonitorLog,
+    ) -> StdR<Vec<PipelineExecution>> {
+        let variables = &executable.variables;
+        let params = ExternalWriteParams {
+            event_code: self.event_code.deref().clone(),
+            trigger_type: executable.topic_trigger.r#type.to_string(),
+            previous: variables
+                .previous_data
+                .as_ref()
+                .map(|data| data.to_topic_data()),
+            current: variables
+                .current_data
+                .as_ref()
+                .map(|data| data.to_topic_data()),
+            variables: variables.variables.to_topic_data(),
+        };
+        executable
+            .environment
+            .write_external(&self.external_writer_id, params)
+            .await?;
+
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,39 @@
+mod action_compile_helper;
+mod compiled_alarm_action;
+mod compiled_alarm_message;
+mod compiled_copy_to_memory_action;
+mod compiled_delete_row_action;
+mod compiled_delete_rows_action;
+mod compiled_exists_action;
+mod compiled_insert_or_merge_row_action;
+mod compiled_insert_row_action;
+mod compiled_mapping_factor;
+mod compiled_merge_row_action;
+mod compiled_pipeline_action;
+mod compiled_read_factor_action;
+mod compiled_read_factors_action;
+mod compiled_read_row_action;
+mod compiled_read_rows_action;
+mod compiled_write_factor_action;
+mod compiled_write_to_external_action;
+mod versioned_merge;
+
+pub use action_compile_helper::*;
+pub use compiled_alarm_action::*;
+pub use compiled_alarm_message::*;
+pub use compiled_copy_to_memory_action::*;
+pub use compiled_delete_row_action::*;
+pub use compiled_delete_rows_action::*;
+pub use compiled_exists_action::*;
+pub use compiled_insert_or_merge_row_action::*;
+pub use compiled_insert_row_action::*;
+pub use compiled_mapping_factor::*;
+pub use compiled_merge_row_action::*;
+pub use compiled_pipeline_action::*;
+pub use compiled_read_factor_action::*;
+pub use compiled_read_factors_action::*;
+pub use compiled_read_row_action::*;
+pub use compiled_read_rows_action::*;
+pub use compiled_write_factor_action::*;
+pub use compiled_write_to_external_action::*;
+pub use versioned_merge::*;
@@ -0,0 +1,367 @@
+use crate::{
+	ActionMonitorLog, CompiledCriteriaJoint, CompiledMappingFactor, PipelineExecutionVariables,
+	PipelineKernelErrorCode, PipelineMergeRetries, TopicDataStore, TopicTrigger,
+};
+use elf_base::{ErrorCode, StdR};
+use elf_model::{AccumulateMode, TopicData};
+use elf_runtime_model_kernel::{TopicDataCriteria, TopicSchema};
+use std::sync::Arc;
+
+/// merged rows of accumulation, previous and current of each.
+pub struct AccumulatedMerge {
+    /// row matched by criteria of current data, none when no row matched
+    pub current: Option<(TopicData, TopicData)>,
+    /// row matched by criteria of previous data, only when it is not the row of current
+    pub previous: Option<(TopicData, TopicData)>,
+}
+
+/// merge with optimistic lock.
+/// on version conflict, the row is re-read and recomputed, until [PipelineMergeRetries] exhausted.
+pub struct VersionedMerge;
+
+impl VersionedMerge {
+    /// find the only row by criteria, compute the row to merge from it, and merge with version checked.
+    /// returns previous and current, or none when no row matched.
+    pub fn merge<F>(
+        store: &Arc<dyn TopicDataStore>,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+        monitor: &mut ActionMonitorLog,
+        compute: F,
+    ) -> StdR<Option<(TopicData, TopicData)>>
+    where
+        F: Fn(TopicData) -> StdR<TopicData>,
+    {
+        let max_retries = PipelineMergeRetries::global().max_retries;
+        let mut conflicts = 0;
+        loop {
+            let existing = match store.find_one(topic_schema, criteria)? {
+                Some(existing) => existing,
+                _ => return Ok(None),
+            };
+            let row = compute(existing)?;
+            if let Some(merged) = store.merge_versioned(topic_schema, row)? {
+                return Ok(Some(merged));
+            }
+
+            monitor.conflicted();
+            conflicts += 1;
+            if conflicts > max_retries {
+                return PipelineKernelErrorCode::TopicDataVersionConflicted.msg(format!(
+                    "Topic data version conflicted by criteria[{:?}] of topic[{}], retried {} time(s).",
+                    criteria,
+                    topic_schema.topic_id(),
+                    max_retries
+                ));
+            }
+        }
+    }
+
+    /// merge mappings into the only row which matches criteria, by given accumulate mode.
+    /// value in previous data is subtracted from the row which matches criteria built by previous data,
+    /// it is merged together with value in current data when both criteria match the same row,
+    /// otherwise merged separately, and nothing is subtracted when no row matched.
+    pub fn accumulate(
+        store: &Arc<dyn TopicDataStore>,
+        topic_schema: &Arc<TopicSchema>,
+        by: &CompiledCriteriaJoint,
+        mapping: &[CompiledMappingFactor],
+        accumulate_mode: &AccumulateMode,
+        variables: &PipelineExecutionVariables,
+        monitor: &mut ActionMonitorLog,
+    ) -> StdR<AccumulatedMerge> {
+        let criteria = by.to_criteria(variables)?;
+        monitor.find_by(&criteria);
+        let (current, previous) =
+            CompiledMappingFactor::accumulation_of(accumulate_mode, variables)?;
+        let previous = match previous {
+            Some(previous) => previous,
+            _ => {
+                return Ok(AccumulatedMerge {
+                    current: Self::merge(store, topic_schema, &criteria, monitor, |existing| {
+                        CompiledMappingFactor::merge_row(mapping, existing, current, None)
+                    })?,
+                    previous: None,
+                });
+            }
+        };
+
+        let previous_criteria = by.to_criteria(&previous)?;
+        let previous_row_id = match store.find_one(topic_schema, &previous_criteria)? {
+            Some(row) => Some(TopicTrigger::get_data_id(&row)?),
+            _ => None,
+        };
+        let merged = Self::merge(store, topic_schema, &criteria, monitor, |existing| {
+            let same_row = match &previous_row_id {
+                Some(previous_row_id) => &TopicTrigger::get_data_id(&existing)? == previous_row_id,
+                _ => false,
+            };
+            CompiledMappingFactor::merge_row(
+                mapping,
+                existing,
+                current,
+                same_row.then_some(&previous),
+            )
+        })?;
+        let subtracted = match (&merged, &previous_row_id) {
+            (Some((_, row)), Some(previous_row_id)) => {
+                &TopicTrigger::get_data_id(row)? == previous_row_id
+            }
+            _ => false,
+        };
+        let previous_merged = if previous_row_id.is_some() && !subtracted {
+            Self::merge(
+                store,
+                topic_schema,
+                &previous_criteria,
+                monitor,
+                |existing| {
+                    CompiledMappingFactor::merge_row(mapping, existing, None, Some(&previous))
+                },
+            )?
+        } else {
+            None
+        };
+
+        Ok(AccumulatedMerge {
+            current: merged,
+            previous: previous_merged,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ActionMonitorLog, ArcTopicData, ArcTopicDataBuilder, CompiledConstantParameter,
+        CompiledCriteriaJoint, CompiledMappingFactor, CompiledParameter, DataPath,
+        PipelineExecutionVariables, TopicDataStore, VersionedMerge,
+    };
+    use bigdecimal::BigDecimal;
+    use elf_base::StdR;
+    use elf_model::{
+        AccumulateMode, AggregateArithmetic, ConstantParameter, EqualsExpression, Factor,
+        FactorType, ParameterJoint, Topic, TopicData, TopicDataColumnNames, TopicDataId,
+        TopicDataValue, TopicFactorParameter, TopicKind, TopicType,
+    };
+    use elf_runtime_model_kernel::{
+        ArcFactor, ArcParameterJoint, TopicDataCriteria, TopicDataCriteriaOperand, TopicSchema,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, RwLock};
+
+    /// rows are found by value of factor "k", which is the right of the only expression of criteria
+    struct KeyedTopicDataStore {
+        rows: RwLock<Vec<TopicData>>,
+    }
+
+    impl KeyedTopicDataStore {
+        fn key_of(criteria: &TopicDataCriteria) -> Option<String> {
+            match criteria {
+                TopicDataCriteria::Joint(joint) => joint.conditions.first().and_then(Self::key_of),
+                TopicDataCriteria::Expression(expression) => match &expression.right {
+                    Some(TopicDataCriteriaOperand::Value(TopicDataValue::Str(key))) => {
+                        Some(key.clone())
+                    }
+                    _ => None,
+                },
+            }
+        }
+
+        fn total_of(&self, key: &str) -> BigDecimal {
+            let rows = self.rows.read().unwrap();
+            let row = rows
+                .iter()
+                .find(|row| matches!(row.get("k"), Some(TopicDataValue::Str(k)) if k == key))
+                .unwrap();
+            match row.get("total") {
+                Some(TopicDataValue::Num(total)) => total.clone(),
+                _ => panic!("total not found"),
+            }
+        }
+    }
+
+    impl TopicDataStore for KeyedTopicDataStore {
+        fn insert(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            Ok(data)
+        }
+        fn insert_or_merge(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<(Option<TopicData>, TopicData)> {
+            Ok((None, data))
+        }
+        fn merge(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<(TopicData, TopicData)> {
+            Ok((data.clone(), data))
+        }
+        fn merge_versioned(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<Option<(TopicData, TopicData)>> {
+            let id_column = TopicDataColumnNames::Id.column_name();
+            let mut rows = self.rows.write().unwrap();
+            let row = rows
+                .iter_mut()
+                .find(|row| {
+                    row.get(id_column).map(|id| id.to_string())
+                        == data.get(id_column).map(|id| id.to_string())
+                })
+                .unwrap();
+            let previous = std::mem::replace(row, data.clone());
+            Ok(Some((previous, data)))
+        }
+        fn delete(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            Ok(data)
+        }
+        fn find_by_id(&self, _: &Arc<TopicSchema>, _: &TopicDataId) -> StdR<Option<TopicData>> {
+            Ok(None)
+        }
+        fn find(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Vec<TopicData>> {
+            Ok(vec![])
+        }
+        fn find_one(
+            &self,
+            _: &Arc<TopicSchema>,
+            criteria: &TopicDataCriteria,
+        ) -> StdR<Option<TopicData>> {
+            let key = Self::key_of(criteria);
+            Ok(self
+                .rows
+                .read()
+                .unwrap()
+                .iter()
+                .find(|row| matches!((row.get("k"), &key), (Some(TopicDataValue::Str(k)), Some(key)) if k == key))
+                .cloned())
+        }
+        fn find_values(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: &Arc<ArcFactor>,
+            _: &TopicDataCriteria,
+        ) -> StdR<Vec<TopicDataValue>> {
+            Ok(vec![])
+        }
+        fn count(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<u64> {
+            Ok(0)
+        }
+        fn exists(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<bool> {
+            Ok(false)
+        }
+    }
+
+    fn create_row(id: &str, key: &str, total: i32) -> TopicData {
+        HashMap::from([
+            (
+                TopicDataColumnNames::Id.column_name().to_string(),
+                TopicDataValue::Str(id.to_string()),
+            ),
+            ("k".to_string(), TopicDataValue::Str(key.to_string())),
+            (
+                "total".to_string(),
+                TopicDataValue::Num(BigDecimal::from(total)),
+            ),
+        ])
+    }
+
+    fn create_data(key: &str, amount: i32) -> ArcTopicData {
+        ArcTopicData::build(HashMap::from([
+            ("k".to_string(), TopicDataValue::Str(key.to_string())),
+            (
+                "amount".to_string(),
+                TopicDataValue::Num(BigDecimal::from(amount)),
+            ),
+        ]))
+    }
+
+    /// sum amount of trigger data into total of the row which has the same key,
+    /// returns totals of rows of key a and b after merged, and whether rows are merged separately.
+    fn accumulate(previous_key: &str, current_key: &str) -> (BigDecimal, BigDecimal, bool) {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("k"))
+                    .r#type(FactorType::Text),
+                Factor::new()
+                    .factor_id(String::from("f2"))
+                    .name(String::from("total"))
+                    .r#type(FactorType::Number),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let topic_schema = Arc::new(TopicSchema::new(topic).unwrap());
+        let tenant_id = Arc::new(String::from("Tenant-1"));
+        let by = CompiledCriteriaJoint::new(
+            &ArcParameterJoint::new(ParameterJoint::and(vec![
+                EqualsExpression::init()
+                    .left(
+                        TopicFactorParameter::of(String::from("topic-1"), String::from("f1"))
+                            .to_parameter(),
+                    )
+                    .right(ConstantParameter::of(String::from("{k}")).to_parameter())
+                    .to_condition(),
+            ]))
+            .unwrap(),
+            &topic_schema,
+            &tenant_id,
+        )
+        .unwrap();
+        let mapping = CompiledMappingFactor::new(
+            CompiledParameter::Constant(CompiledConstantParameter::Path(
+                DataPath::from_str("amount").unwrap(),
+            )),
+            ArcFactor::new(
+                Factor::new()
+                    .factor_id(String::from("f2"))
+                    .name(String::from("total"))
+                    .r#type(FactorType::Number),
+            )
+            .unwrap(),
+            Arc::new(AggregateArithmetic::Sum),
+        );
+        let store = Arc::new(KeyedTopicDataStore {
+            rows: RwLock::new(vec![create_row("1", "a", 10), create_row("2", "b", 20)]),
+        });
+        let variables = PipelineExecutionVariables::new(
+            Some(create_data(previous_key, 2)),
+            Some(create_data(current_key, 5)),
+        );
+
+        let merged = VersionedMerge::accumulate(
+            &(store.clone() as Arc<dyn TopicDataStore>),
+            &topic_schema,
+            &by,
+            &[mapping],
+            &AccumulateMode::Standard,
+            &variables,
+            &mut ActionMonitorLog::new(),
+        )
+        .unwrap();
+        assert!(merged.current.is_some());
+        (
+            store.total_of("a"),
+            store.total_of("b"),
+            merged.previous.is_some(),
+        )
+    }
+
+    #[test]
+    fn test_accumulate_moved() {
+        // key changed from a to b, 2 is taken back from a, and 5 is added to b
+        assert_eq!(
+            accumulate("a", "b"),
+            (BigDecimal::from(8), BigDecimal::from(25), true)
+        );
+        // key not changed, merged at once
+        assert_eq!(
+            accumulate("a", "a"),
+            (BigDecimal::from(13), BigDecimal::from(20), false)
+        );
+    }
+}
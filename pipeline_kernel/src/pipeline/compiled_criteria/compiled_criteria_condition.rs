@@ -0,0 +1,37 @@
+use crate::{CompiledCriteriaExpression, CompiledCriteriaJoint, PipelineExecutionVariables};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcParameterCondition, TopicDataCriteria, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub enum CompiledCriteriaCondition {
+    Joint(CompiledCriteriaJoint),
+    Expression(CompiledCriteriaExpression),
+}
+
+impl CompiledCriteriaCondition {
+    pub fn new(
+        value: &Arc<ArcParameterCondition>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Self> {
+        match value.deref() {
+            ArcParameterCondition::Expression(v) => {
+                CompiledCriteriaExpression::new(v, topic_schema, tenant_id)
+                    .map(CompiledCriteriaCondition::Expression)
+            }
+            ArcParameterCondition::Joint(v) => {
+                CompiledCriteriaJoint::new(v, topic_schema, tenant_id)
+                    .map(CompiledCriteriaCondition::Joint)
+            }
+        }
+    }
+
+    pub fn to_criteria(&self, variables: &PipelineExecutionVariables) -> StdR<TopicDataCriteria> {
+        match self {
+            Self::Expression(v) => v.to_criteria(variables).map(TopicDataCriteria::Expression),
+            Self::Joint(v) => v.to_criteria(variables),
+        }
+    }
+}
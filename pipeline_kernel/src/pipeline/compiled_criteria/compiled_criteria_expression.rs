@@ -0,0 +1,61 @@
+use crate::{CompiledCriteriaOperand, PipelineExecutionVariables};
+use elf_base::StdR;
+use elf_model::{ParameterExpressionOperator, TenantId};
+use elf_runtime_model_kernel::{ArcParameterExpression, TopicDataCriteriaExpression, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub struct CompiledCriteriaExpression {
+    left: CompiledCriteriaOperand,
+    operator: Arc<ParameterExpressionOperator>,
+    /// none when operator is empty or not-empty
+    right: Option<CompiledCriteriaOperand>,
+}
+
+impl CompiledCriteriaExpression {
+    pub fn new(
+        value: &Arc<ArcParameterExpression>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Self> {
+        let (left, operator, right) = match value.deref() {
+            ArcParameterExpression::Empty(v) => (&v.left, &v.operator, None),
+            ArcParameterExpression::NotEmpty(v) => (&v.left, &v.operator, None),
+            ArcParameterExpression::Equals(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::NotEquals(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::LessThan(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::LessThanOrEquals(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::MoreThan(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::MoreThanOrEquals(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::In(v) => (&v.left, &v.operator, Some(&v.right)),
+            ArcParameterExpression::NotIn(v) => (&v.left, &v.operator, Some(&v.right)),
+        };
+
+        Ok(CompiledCriteriaExpression {
+            left: CompiledCriteriaOperand::new(left, topic_schema, tenant_id)?,
+            operator: operator.clone(),
+            right: match right {
+                Some(right) => Some(CompiledCriteriaOperand::new(
+                    right,
+                    topic_schema,
+                    tenant_id,
+                )?),
+                _ => None,
+            },
+        })
+    }
+
+    pub fn to_criteria(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<TopicDataCriteriaExpression> {
+        Ok(TopicDataCriteriaExpression {
+            left: self.left.to_criteria(variables)?,
+            operator: self.operator.clone(),
+            right: match &self.right {
+                Some(right) => Some(right.to_criteria(variables)?),
+                _ => None,
+            },
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{CompiledCriteriaCondition, PipelineExecutionVariables};
+use elf_base::StdR;
+use elf_model::{ParameterJointType, TenantId};
+use elf_runtime_model_kernel::{
+	ArcParameterJoint, TopicDataCriteria, TopicDataCriteriaJoint, TopicSchema,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// storage criteria, which is used to find data from the given topic.
+/// the topic factor parameters refer to the given topic are kept as factors,
+/// and others are computed in memory when building criteria.
+pub struct CompiledCriteriaJoint {
+    r#type: Arc<ParameterJointType>,
+    conditions: Vec<CompiledCriteriaCondition>,
+}
+
+impl CompiledCriteriaJoint {
+    pub fn new(
+        value: &Arc<ArcParameterJoint>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Self> {
+        let mut conditions = vec![];
+        for filter in value.filters.deref() {
+            conditions.push(CompiledCriteriaCondition::new(
+                filter,
+                topic_schema,
+                tenant_id,
+            )?)
+        }
+
+        Ok(CompiledCriteriaJoint {
+            r#type: value.joint_type.clone(),
+            conditions,
+        })
+    }
+
+    pub fn to_criteria(&self, variables: &PipelineExecutionVariables) -> StdR<TopicDataCriteria> {
+        let mut conditions = vec![];
+        for condition in &self.conditions {
+            conditions.push(condition.to_criteria(variables)?);
+        }
+
+        Ok(TopicDataCriteria::Joint(TopicDataCriteriaJoint {
+            joint_type: self.r#type.clone(),
+            conditions,
+        }))
+    }
+}
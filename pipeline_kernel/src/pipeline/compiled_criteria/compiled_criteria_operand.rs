@@ -0,0 +1,45 @@
+use crate::{
+	ActionCompileHelper, CompiledParameter, InMemoryParameter, PipelineExecutionVariables,
+};
+use elf_base::StdR;
+use elf_model::TenantId;
+use elf_runtime_model_kernel::{ArcFactor, ArcParameter, TopicDataCriteriaOperand, TopicSchema};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// operand of criteria,
+/// - factor: the topic factor parameter which refers to the queried topic,
+/// - value: any other parameter, value is computed in memory.
+pub enum CompiledCriteriaOperand {
+    Factor(Arc<ArcFactor>),
+    Value(CompiledParameter),
+}
+
+impl ActionCompileHelper for CompiledCriteriaOperand {}
+
+impl CompiledCriteriaOperand {
+    pub fn new(
+        parameter: &Arc<ArcParameter>,
+        topic_schema: &Arc<TopicSchema>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Self> {
+        match parameter.deref() {
+            ArcParameter::Topic(p) if p.topic_id == *topic_schema.topic_id() => {
+                Self::factor(topic_schema, &p.factor_id).map(CompiledCriteriaOperand::Factor)
+            }
+            _ => CompiledParameter::new(parameter, tenant_id).map(CompiledCriteriaOperand::Value),
+        }
+    }
+
+    pub fn to_criteria(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<TopicDataCriteriaOperand> {
+        match self {
+            Self::Factor(factor) => Ok(TopicDataCriteriaOperand::Factor(factor.clone())),
+            Self::Value(parameter) => Ok(TopicDataCriteriaOperand::Value(
+                parameter.value_from(variables)?.to_topic_data_value(),
+            )),
+        }
+    }
+}
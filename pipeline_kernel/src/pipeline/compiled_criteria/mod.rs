@@ -0,0 +1,9 @@
+mod compiled_criteria_condition;
+mod compiled_criteria_expression;
+mod compiled_criteria_joint;
+mod compiled_criteria_operand;
+
+pub use compiled_criteria_condition::*;
+pub use compiled_criteria_expression::*;
+pub use compiled_criteria_joint::*;
+pub use compiled_criteria_operand::*;
@@ -1,19 +1,391 @@
-use crate::{ArcTopicDataValue, InMemoryParameter, PipelineExecutionVariables};
-use elf_base::StdR;
+use crate::{
+	ArcTopicDataValue, CompiledParameter, CompiledParameterJoint, InMemoryParameter,
+	InMemoryParameterCondition, PipelineExecutionVariables, PipelineKernelErrorCode,
+};
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{Datelike, NaiveDate};
+use elf_base::{DateTimeUtils, ErrorCode, StdR, StringUtils};
 use elf_model::TenantId;
-use elf_runtime_model_kernel::ArcComputedParameter;
+use elf_runtime_model_kernel::{ArcCaseThenParameterRoute, ArcComputedParameter, ArcParameter};
+use std::ops::Deref;
 use std::sync::Arc;
 
-pub struct CompiledComputedParameter;
+pub struct CompiledCaseThenRoute {
+    on: Option<CompiledParameterJoint>,
+    parameter: CompiledParameter,
+}
+
+impl CompiledCaseThenRoute {
+    pub fn new(route: &Arc<ArcCaseThenParameterRoute>, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let on = match &route.on {
+            Some(on) => Some(CompiledParameterJoint::new(on, tenant_id)?),
+            None => None,
+        };
+        Ok(CompiledCaseThenRoute {
+            on,
+            parameter: CompiledParameter::new(&route.parameter, tenant_id)?,
+        })
+    }
+
+    /// route without condition is the default route, always matched
+    fn is_matched(&self, variables: &PipelineExecutionVariables) -> StdR<bool> {
+        match &self.on {
+            Some(on) => on.is_true(variables),
+            None => Ok(true),
+        }
+    }
+}
+
+pub enum CompiledComputedParameter {
+    None,
+    // math operations
+    Add(Vec<CompiledParameter>),
+    Subtract(Vec<CompiledParameter>),
+    Multiply(Vec<CompiledParameter>),
+    Divide(Vec<CompiledParameter>),
+    Modulus(Vec<CompiledParameter>),
+    // date related operations
+    YearOf(Box<CompiledParameter>),
+    HalfYearOf(Box<CompiledParameter>),
+    QuarterOf(Box<CompiledParameter>),
+    MonthOf(Box<CompiledParameter>),
+    WeekOfYear(Box<CompiledParameter>),
+    WeekOfMonth(Box<CompiledParameter>),
+    DayOfMonth(Box<CompiledParameter>),
+    DayOfWeek(Box<CompiledParameter>),
+    // conditional operation, default route is the last one
+    CaseThen(Vec<CompiledCaseThenRoute>),
+}
 
 impl CompiledComputedParameter {
-    pub fn new(_parameter: &Arc<ArcComputedParameter>, _tenant_id: &Arc<TenantId>) -> StdR<Self> {
-        Ok(CompiledComputedParameter {})
+    pub fn new(parameter: &Arc<ArcComputedParameter>, tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let compiled = match parameter.deref() {
+            ArcComputedParameter::None(_) => Self::None,
+            // math operations
+            ArcComputedParameter::Add(p) => Self::Add(Self::compile_all(&p.parameters, tenant_id)?),
+            ArcComputedParameter::Subtract(p) => {
+                Self::Subtract(Self::compile_all(&p.parameters, tenant_id)?)
+            }
+            ArcComputedParameter::Multiply(p) => {
+                Self::Multiply(Self::compile_all(&p.parameters, tenant_id)?)
+            }
+            ArcComputedParameter::Divide(p) => {
+                Self::Divide(Self::compile_all(&p.parameters, tenant_id)?)
+            }
+            ArcComputedParameter::Modulus(p) => {
+                Self::Modulus(Self::compile_all(&p.parameters, tenant_id)?)
+            }
+            // date related operations
+            ArcComputedParameter::YearOf(p) => {
+                Self::YearOf(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::HalfYearOf(p) => {
+                Self::HalfYearOf(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::QuarterOf(p) => {
+                Self::QuarterOf(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::MonthOf(p) => {
+                Self::MonthOf(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::WeekOfYear(p) => {
+                Self::WeekOfYear(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::WeekOfMonth(p) => {
+                Self::WeekOfMonth(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::DayOfMonth(p) => {
+                Self::DayOfMonth(Self::compile(&p.parameter, tenant_id)?)
+            }
+            ArcComputedParameter::DayOfWeek(p) => {
+                Self::DayOfWeek(Self::compile(&p.parameter, tenant_id)?)
+            }
+            // conditional operation
+            ArcComputedParameter::CaseThen(p) => {
+                let mut routes = vec![];
+                for route in p.parameters.iter() {
+                    routes.push(CompiledCaseThenRoute::new(route, tenant_id)?);
+                }
+                Self::CaseThen(routes)
+            }
+        };
+        Ok(compiled)
+    }
+
+    fn compile(
+        parameter: &Arc<ArcParameter>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Box<CompiledParameter>> {
+        Ok(Box::new(CompiledParameter::new(parameter, tenant_id)?))
+    }
+
+    fn compile_all(
+        parameters: &Arc<Vec<Arc<ArcParameter>>>,
+        tenant_id: &Arc<TenantId>,
+    ) -> StdR<Vec<CompiledParameter>> {
+        let mut compiled = vec![];
+        for parameter in parameters.iter() {
+            compiled.push(CompiledParameter::new(parameter, tenant_id)?);
+        }
+        Ok(compiled)
+    }
+
+    /// none and blank string are treated as zero
+    fn decimal_of(value: &ArcTopicDataValue) -> StdR<BigDecimal> {
+        if value.is_none_or_empty_str() {
+            Ok(BigDecimal::zero())
+        } else {
+            value.try_to_decimal().map(|v| v.deref().clone())
+        }
+    }
+
+    fn compute<F>(
+        parameters: &Vec<CompiledParameter>,
+        variables: &PipelineExecutionVariables,
+        compute: F,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        F: Fn(BigDecimal, BigDecimal) -> StdR<BigDecimal>,
+    {
+        let mut result: Option<BigDecimal> = None;
+        for parameter in parameters {
+            let value = Self::decimal_of(parameter.value_from(variables)?.deref())?;
+            result = match result {
+                Some(result) => Some(compute(result, value)?),
+                None => Some(value),
+            }
+        }
+        Ok(Arc::new(ArcTopicDataValue::Num(Arc::new(
+            result.unwrap_or(BigDecimal::zero()),
+        ))))
+    }
+
+    fn must_not_zero(operation: &str, value: &BigDecimal) -> StdR<()> {
+        if value.is_zero() {
+            PipelineKernelErrorCode::ValueDivideByZero.msg(format!(
+                "Computed parameter[{}] cannot divide by zero.",
+                operation
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// none and blank string are treated as none,
+    /// time part of datetime is ignored, string is parsed as date or datetime.
+    fn date_of(
+        parameter: &CompiledParameter,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<Option<NaiveDate>> {
+        let value = parameter.value_from(variables)?;
+        match value.deref() {
+            ArcTopicDataValue::Date(date) => Ok(Some(*date.deref())),
+            ArcTopicDataValue::DateTime(datetime) => Ok(Some(datetime.date())),
+            ArcTopicDataValue::None => Ok(None),
+            ArcTopicDataValue::Str(str) if str.is_blank() => Ok(None),
+            ArcTopicDataValue::Str(str) => str.to_date_loose().map(Some),
+            other => PipelineKernelErrorCode::ValueNotDateRelated.msg(format!(
+                "Cannot get date part from [{}].",
+                ArcTopicDataValue::display_in_error(other)
+            )),
+        }
+    }
+
+    fn date_part<F>(
+        parameter: &CompiledParameter,
+        variables: &PipelineExecutionVariables,
+        part: F,
+    ) -> StdR<Arc<ArcTopicDataValue>>
+    where
+        F: Fn(&NaiveDate) -> u32,
+    {
+        match Self::date_of(parameter, variables)? {
+            Some(date) => Ok(Arc::new(ArcTopicDataValue::Num(Arc::new(
+                BigDecimal::from(part(&date)),
+            )))),
+            None => Ok(Arc::new(ArcTopicDataValue::None)),
+        }
+    }
+
+    /// 0 (the partial week that precedes the first sunday of the year) - 53
+    fn week_of_year(date: &NaiveDate) -> u32 {
+        (date.ordinal0() + 7 - date.weekday().num_days_from_sunday()) / 7
+    }
+
+    /// 0 (the partial week that precedes the first sunday of the month) - 5
+    fn week_of_month(date: &NaiveDate) -> u32 {
+        (date.day0() + 7 - date.weekday().num_days_from_sunday()) / 7
     }
 }
 
 impl InMemoryParameter for CompiledComputedParameter {
-    fn value_from(&self, _variables: &PipelineExecutionVariables) -> StdR<Arc<ArcTopicDataValue>> {
-        todo!("implement value_from for CompiledComputedParameter")
+    fn value_from(&self, variables: &PipelineExecutionVariables) -> StdR<Arc<ArcTopicDataValue>> {
+        match self {
+            Self::None => Ok(Arc::new(ArcTopicDataValue::None)),
+            // math operations
+            Self::Add(parameters) => Self::compute(parameters, variables, |a, b| Ok(a + b)),
+            Self::Subtract(parameters) => Self::compute(parameters, variables, |a, b| Ok(a - b)),
+            Self::Multiply(parameters) => Self::compute(parameters, variables, |a, b| Ok(a * b)),
+            Self::Divide(parameters) => Self::compute(parameters, variables, |a, b| {
+                Self::must_not_zero("divide", &b)?;
+                Ok(a / b)
+            }),
+            Self::Modulus(parameters) => Self::compute(parameters, variables, |a, b| {
+                Self::must_not_zero("modulus", &b)?;
+                Ok(a % b)
+            }),
+            // date related operations
+            Self::YearOf(parameter) => {
+                Self::date_part(parameter, variables, |date| date.year() as u32)
+            }
+            Self::HalfYearOf(parameter) => {
+                Self::date_part(
+                    parameter,
+                    variables,
+                    |date| {
+                        if date.month() <= 6 { 1 } else { 2 }
+                    },
+                )
+            }
+            Self::QuarterOf(parameter) => {
+                Self::date_part(parameter, variables, |date| date.month0() / 3 + 1)
+            }
+            Self::MonthOf(parameter) => Self::date_part(parameter, variables, |date| date.month()),
+            Self::WeekOfYear(parameter) => {
+                Self::date_part(parameter, variables, Self::week_of_year)
+            }
+            Self::WeekOfMonth(parameter) => {
+                Self::date_part(parameter, variables, Self::week_of_month)
+            }
+            Self::DayOfMonth(parameter) => Self::date_part(parameter, variables, |date| date.day()),
+            Self::DayOfWeek(parameter) => Self::date_part(parameter, variables, |date| {
+                date.weekday().number_from_sunday()
+            }),
+            // conditional operation
+            Self::CaseThen(routes) => {
+                for route in routes {
+                    if route.is_matched(variables)? {
+                        return route.parameter.value_from(variables);
+                    }
+                }
+                Ok(Arc::new(ArcTopicDataValue::None))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcTopicDataValue, CompiledParameter, InMemoryParameter, PipelineExecutionVariables,
+    };
+    use bigdecimal::BigDecimal;
+    use elf_model::{
+        AddParameter, CaseThenParameter, CaseThenParameterRoute, ConstantParameter,
+        DayOfWeekParameter, DivideParameter, LessThanExpression, ModulusParameter, Parameter,
+        ParameterJoint, SubtractParameter, WeekOfMonthParameter, WeekOfYearParameter,
+    };
+    use elf_runtime_model_kernel::ArcParameter;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn constant(value: &str) -> Parameter {
+        ConstantParameter::of(value.to_string()).to_parameter()
+    }
+
+    fn constants(values: Vec<&str>) -> Vec<Parameter> {
+        values.into_iter().map(constant).collect()
+    }
+
+    fn less_than(value: &str) -> CaseThenParameterRoute {
+        CaseThenParameterRoute::case(ParameterJoint::and(vec![
+            LessThanExpression::init()
+                .left(constant("{amount}"))
+                .right(constant(value))
+                .to_condition(),
+        ]))
+    }
+
+    fn value_of(parameter: Parameter, amount: i32) -> Arc<ArcTopicDataValue> {
+        let parameter = ArcParameter::new(parameter).unwrap();
+        let variables = PipelineExecutionVariables::new(
+            None,
+            Some(Arc::new(HashMap::from([(
+                "amount".to_string(),
+                Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(amount)))),
+            )]))),
+        );
+        CompiledParameter::new(&parameter, &Arc::new(String::from("Tenant-1")))
+            .unwrap()
+            .value_from(&variables)
+            .unwrap()
+    }
+
+    fn assert_num(value: Arc<ArcTopicDataValue>, expected: &str) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from_str(expected).unwrap()),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    fn assert_str(value: Arc<ArcTopicDataValue>, expected: &str) {
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == expected),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    #[test]
+    fn test_value_from() {
+        let add = AddParameter::init().parameters(constants(vec!["10", "{amount}", "{missing}"]));
+        assert_num(value_of(add.to_parameter(), 20), "30");
+        let subtract = SubtractParameter::init().parameters(constants(vec!["{amount}", "30"]));
+        assert_num(value_of(subtract.to_parameter(), 100), "70");
+        let divide = DivideParameter::init().parameters(constants(vec!["10", "{amount}"]));
+        assert_num(value_of(divide.to_parameter(), 4), "2.5");
+        let modulus = ModulusParameter::init().parameters(constants(vec!["10", "{amount}"]));
+        assert_num(value_of(modulus.to_parameter(), 3), "1");
+
+        let parameter = ArcParameter::new(
+            DivideParameter::init()
+                .parameters(constants(vec!["10", "0"]))
+                .to_parameter(),
+        )
+        .unwrap();
+        let result = CompiledParameter::new(&parameter, &Arc::new(String::from("Tenant-1")))
+            .unwrap()
+            .value_from(&PipelineExecutionVariables::new(None, None));
+        assert!(result.is_err());
+
+        // 2025-03-09 is sunday, first sunday of 2025 is 01-05, first sunday of march is 03-02
+        let week_of_year = WeekOfYearParameter::init().parameter(Box::new(constant("2025-03-09")));
+        assert_num(value_of(week_of_year.to_parameter(), 0), "10");
+        let week_of_month =
+            WeekOfMonthParameter::init().parameter(Box::new(constant("2025-03-09")));
+        assert_num(value_of(week_of_month.to_parameter(), 0), "2");
+        let week_of_month =
+            WeekOfMonthParameter::init().parameter(Box::new(constant("2025-03-01")));
+        assert_num(value_of(week_of_month.to_parameter(), 0), "0");
+        let day_of_week = DayOfWeekParameter::init().parameter(Box::new(constant("2025-03-09")));
+        assert_num(value_of(day_of_week.to_parameter(), 0), "1");
+
+        let case_then = || {
+            CaseThenParameter::init()
+                .parameters(vec![
+                    CaseThenParameterRoute::default().parameter(constant("large")),
+                    less_than("10").parameter(constant("tiny")),
+                    less_than("50").parameter(constant("medium")),
+                ])
+                .to_parameter()
+        };
+        assert_str(value_of(case_then(), 5), "tiny");
+        assert_str(value_of(case_then(), 20), "medium");
+        assert_str(value_of(case_then(), 100), "large");
     }
 }
@@ -1,19 +1,249 @@
-use crate::{ArcTopicDataValue, InMemoryParameter, PipelineExecutionVariables};
-use elf_base::StdR;
+use crate::{
+	ArcTopicDataValue, DataPath, DataVisitor, InMemoryParameter, PipelineExecutionVariables,
+	PipelineKernelErrorCode,
+};
+use elf_base::{ErrorCode, StdR, StringConverter};
 use elf_model::TenantId;
 use elf_runtime_model_kernel::ArcConstantParameter;
 use std::sync::Arc;
 
-pub struct CompiledConstantParameter;
+pub enum CompiledConstantTemplateSegment {
+    Text(String),
+    Path(DataPath),
+}
+
+/// constant value is parsed once when compiling, the `{path}` in value is a path,
+/// - no path in value: a plain literal string,
+/// - exactly one path and nothing else, e.g. `{a.b}`: value of path, type is kept,
+/// - otherwise, e.g. `x{a.b}y{&old.c}`: values of paths are concatenated with texts as a string.
+///
+/// path is read from variables, or current trigger data when not declared in variables,
+/// use `&cur` or `&old` to read from current or previous trigger data explicitly.
+/// braces can be nested in path, use `\{` and `\}` to escape brace in text.
+/// unclosed `{` and blank `{}` are kept as text.
+pub enum CompiledConstantParameter {
+    Literal(Arc<ArcTopicDataValue>),
+    Path(DataPath),
+    Template(Vec<CompiledConstantTemplateSegment>),
+}
 
 impl CompiledConstantParameter {
-    pub fn new(_parameter: &Arc<ArcConstantParameter>, _tenant_id: &Arc<TenantId>) -> StdR<Self> {
-        Ok(CompiledConstantParameter {})
+    pub fn new(parameter: &Arc<ArcConstantParameter>, _tenant_id: &Arc<TenantId>) -> StdR<Self> {
+        let mut segments = Self::parse(parameter.value.as_str())?;
+        match segments.len() {
+            0 => Ok(Self::Literal(Arc::new(ArcTopicDataValue::Str(Arc::new(
+                String::new(),
+            ))))),
+            1 => match segments.remove(0) {
+                CompiledConstantTemplateSegment::Text(text) => Ok(Self::Literal(Arc::new(
+                    ArcTopicDataValue::Str(Arc::new(text)),
+                ))),
+                CompiledConstantTemplateSegment::Path(path) => Ok(Self::Path(path)),
+            },
+            _ => Ok(Self::Template(segments)),
+        }
+    }
+
+    /// find index of the `}` which closes the `{` at given index, escaped brace is skipped
+    fn find_closing(chars: &[char], start: usize) -> Option<usize> {
+        let mut depth = 0;
+        let mut index = start;
+        while index < chars.len() {
+            match chars[index] {
+                '\\' => index += 1,
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+        None
+    }
+
+    fn parse(value: &str) -> StdR<Vec<CompiledConstantTemplateSegment>> {
+        let chars: Vec<char> = value.chars().collect();
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut index = 0;
+        while index < chars.len() {
+            match chars[index] {
+                '\\' if matches!(chars.get(index + 1), Some('{') | Some('}')) => {
+                    text.push(chars[index + 1]);
+                    index += 2;
+                }
+                '{' => match Self::find_closing(&chars, index) {
+                    Some(end) => {
+                        let path: String = chars[index + 1..end].iter().collect();
+                        if path.trim().is_empty() {
+                            text.extend(&chars[index..=end]);
+                        } else {
+                            if !text.is_empty() {
+                                segments.push(CompiledConstantTemplateSegment::Text(text));
+                                text = String::new();
+                            }
+                            segments.push(CompiledConstantTemplateSegment::Path(
+                                DataPath::from_str(path.trim())?,
+                            ));
+                        }
+                        index = end + 1;
+                    }
+                    _ => {
+                        text.push('{');
+                        index += 1;
+                    }
+                },
+                char => {
+                    text.push(char);
+                    index += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            segments.push(CompiledConstantTemplateSegment::Text(text));
+        }
+        Ok(segments)
+    }
+
+    /// none is concatenated as empty string, elements of vec are joined by comma
+    fn to_text(value: &ArcTopicDataValue, path: &DataPath) -> StdR<String> {
+        match value {
+            ArcTopicDataValue::None => Ok(String::new()),
+            ArcTopicDataValue::Str(v) => Ok(v.to_string()),
+            ArcTopicDataValue::Num(v) => Ok(String::from_decimal(v)),
+            ArcTopicDataValue::Bool(v) => Ok(String::from_bool(v)),
+            ArcTopicDataValue::Date(v) => Ok(String::from_date(v)),
+            ArcTopicDataValue::Time(v) => Ok(String::from_time(v)),
+            ArcTopicDataValue::DateTime(v) => Ok(String::from_datetime(v)),
+            ArcTopicDataValue::Vec(vec) => Ok(vec
+                .iter()
+                .map(|v| Self::to_text(v, path))
+                .collect::<StdR<Vec<String>>>()?
+                .join(",")),
+            ArcTopicDataValue::Map(_) => PipelineKernelErrorCode::ValueNotConcatenatable.msg(
+                format!("Value of path[{}] is a map, cannot be concatenated.", path),
+            ),
+        }
     }
 }
 
 impl InMemoryParameter for CompiledConstantParameter {
-    fn value_from(&self, _variables: &PipelineExecutionVariables) -> StdR<Arc<ArcTopicDataValue>> {
-        todo!("implement value_from for CompiledConstantParameter")
+    fn value_from(&self, variables: &PipelineExecutionVariables) -> StdR<Arc<ArcTopicDataValue>> {
+        match self {
+            Self::Literal(value) => Ok(value.clone()),
+            Self::Path(path) => variables.value_of(path),
+            Self::Template(segments) => {
+                let mut value = String::new();
+                for segment in segments {
+                    match segment {
+                        CompiledConstantTemplateSegment::Text(text) => value.push_str(text),
+                        CompiledConstantTemplateSegment::Path(path) => value
+                            .push_str(&Self::to_text(variables.value_of(path)?.as_ref(), path)?),
+                    }
+                }
+                Ok(Arc::new(ArcTopicDataValue::Str(Arc::new(value))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcTopicDataValue, CompiledConstantParameter, InMemoryParameter, PipelineExecutionVariables,
+    };
+    use bigdecimal::BigDecimal;
+    use elf_model::{ConstantParameter, ParameterKind};
+    use elf_runtime_model_kernel::ArcConstantParameter;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    fn compile(value: &str) -> CompiledConstantParameter {
+        let parameter = ArcConstantParameter::new(
+            ConstantParameter::new()
+                .kind(ParameterKind::Constant)
+                .value(value.to_string()),
+        )
+        .unwrap();
+        CompiledConstantParameter::new(&parameter, &Arc::new(String::from("Tenant-1"))).unwrap()
+    }
+
+    fn str_value(value: &str) -> Arc<ArcTopicDataValue> {
+        Arc::new(ArcTopicDataValue::Str(Arc::new(value.to_string())))
+    }
+
+    fn num_value(value: i32) -> Arc<ArcTopicDataValue> {
+        Arc::new(ArcTopicDataValue::Num(Arc::new(BigDecimal::from(value))))
+    }
+
+    fn assert_str(
+        parameter: &CompiledConstantParameter,
+        variables: &PipelineExecutionVariables,
+        expected: &str,
+    ) {
+        let value = parameter.value_from(variables).unwrap();
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Str(s) if s.as_str() == expected),
+            "expected [{}], got [{:?}]",
+            expected,
+            value
+        );
+    }
+
+    #[test]
+    fn test_value_from() {
+        let previous_data = HashMap::from([("amount".to_string(), num_value(50))]);
+        let current_data = HashMap::from([
+            ("name".to_string(), str_value("x")),
+            ("amount".to_string(), num_value(100)),
+            (
+                "items".to_string(),
+                Arc::new(ArcTopicDataValue::Vec(Arc::new(vec![
+                    str_value("a"),
+                    str_value("b"),
+                ]))),
+            ),
+            (
+                "order".to_string(),
+                Arc::new(ArcTopicDataValue::Map(Arc::new(HashMap::from([(
+                    "code".to_string(),
+                    str_value("o1"),
+                )])))),
+            ),
+        ]);
+        let mut variables = PipelineExecutionVariables::new(
+            Some(Arc::new(previous_data)),
+            Some(Arc::new(current_data)),
+        );
+        variables.put_variable("name".to_string(), str_value("y"));
+
+        // literal
+        assert_str(&compile("abc"), &variables, "abc");
+        assert_str(&compile("a\\{b\\}"), &variables, "a{b}");
+        assert_str(
+            &compile("{ } and {unclosed"),
+            &variables,
+            "{ } and {unclosed",
+        );
+        // single path keeps type
+        let value = compile("{amount}").value_from(&variables).unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::Num(n) if **n == BigDecimal::from(100)));
+        let value = compile("{&old.amount}").value_from(&variables).unwrap();
+        assert!(matches!(value.deref(), ArcTopicDataValue::Num(n) if **n == BigDecimal::from(50)));
+        // variable first, then current trigger data
+        assert_str(&compile("{name}"), &variables, "y");
+        assert_str(&compile("{&cur.name}"), &variables, "x");
+        // template
+        assert_str(
+            &compile("{order.code}-{amount}/{&old.amount} of {items}{missing}"),
+            &variables,
+            "o1-100/50 of a,b",
+        );
+        assert!(compile("x{order}").value_from(&variables).is_err());
     }
 }
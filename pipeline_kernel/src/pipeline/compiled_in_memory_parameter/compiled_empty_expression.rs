@@ -2,7 +2,10 @@ use crate::{
 	CompiledParameter, InMemoryParameter, InMemoryParameterCondition, PipelineExecutionVariables,
 };
 use elf_base::StdR;
-use elf_model::TenantId;
+use elf_model::{
+	MonitorLogParameterExpression, MonitorLogParameterOperand, ParameterExpressionOperator,
+	TenantId,
+};
 use elf_runtime_model_kernel::ArcEmptyExpression;
 use std::sync::Arc;
 
@@ -16,6 +19,20 @@ impl CompiledEmptyExpression {
             left: CompiledParameter::new(&exp.left, tenant_id)?,
         })
     }
+
+    /// snapshot with resolved values, for monitor log
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<MonitorLogParameterExpression> {
+        Ok(MonitorLogParameterExpression {
+            left: MonitorLogParameterOperand::value(
+                self.left.value_from(variables)?.to_topic_data_value(),
+            ),
+            operator: ParameterExpressionOperator::Empty,
+            right: None,
+        })
+    }
 }
 
 impl InMemoryParameterCondition for CompiledEmptyExpression {
@@ -2,7 +2,10 @@ use crate::{
 	CompiledParameter, InMemoryParameter, InMemoryParameterCondition, PipelineExecutionVariables,
 };
 use elf_base::StdR;
-use elf_model::TenantId;
+use elf_model::{
+	MonitorLogParameterExpression, MonitorLogParameterOperand, ParameterExpressionOperator,
+	TenantId,
+};
 use elf_runtime_model_kernel::ArcInExpression;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -19,6 +22,22 @@ impl CompiledInExpression {
             right: CompiledParameter::new(&exp.right, tenant_id)?,
         })
     }
+
+    /// snapshot with resolved values, for monitor log
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<MonitorLogParameterExpression> {
+        Ok(MonitorLogParameterExpression {
+            left: MonitorLogParameterOperand::value(
+                self.left.value_from(variables)?.to_topic_data_value(),
+            ),
+            operator: ParameterExpressionOperator::In,
+            right: Some(MonitorLogParameterOperand::value(
+                self.right.value_from(variables)?.to_topic_data_value(),
+            )),
+        })
+    }
 }
 
 impl InMemoryParameterCondition for CompiledInExpression {
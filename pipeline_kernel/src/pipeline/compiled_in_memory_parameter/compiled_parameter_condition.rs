@@ -3,7 +3,7 @@ use crate::{
 	PipelineExecutionVariables,
 };
 use elf_base::StdR;
-use elf_model::TenantId;
+use elf_model::{MonitorLogParameterCondition, TenantId};
 use elf_runtime_model_kernel::ArcParameterCondition;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -22,6 +22,20 @@ impl CompiledParameterCondition {
                 .map(|p| CompiledParameterCondition::Joint(p)),
         }
     }
+
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<MonitorLogParameterCondition> {
+        match self {
+            Self::Expression(v) => v
+                .snapshot(variables)
+                .map(MonitorLogParameterCondition::Expression),
+            Self::Joint(v) => v
+                .snapshot(variables)
+                .map(MonitorLogParameterCondition::Joint),
+        }
+    }
 }
 
 impl InMemoryParameterCondition for CompiledParameterCondition {
@@ -5,7 +5,7 @@ use crate::{
 	CompiledNotInExpression, InMemoryParameterCondition, PipelineExecutionVariables,
 };
 use elf_base::StdR;
-use elf_model::TenantId;
+use elf_model::{MonitorLogParameterExpression, TenantId};
 use elf_runtime_model_kernel::ArcParameterExpression;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -53,6 +53,24 @@ impl CompiledParameterExpression {
                 .map(|p| CompiledParameterExpression::NotIn(p)),
         }
     }
+
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<MonitorLogParameterExpression> {
+        match self {
+            Self::Empty(v) => v.snapshot(variables),
+            Self::NotEmpty(v) => v.snapshot(variables),
+            Self::Equals(v) => v.snapshot(variables),
+            Self::NotEquals(v) => v.snapshot(variables),
+            Self::LessThan(v) => v.snapshot(variables),
+            Self::LessThanOrEquals(v) => v.snapshot(variables),
+            Self::MoreThan(v) => v.snapshot(variables),
+            Self::MoreThanOrEquals(v) => v.snapshot(variables),
+            Self::In(v) => v.snapshot(variables),
+            Self::NotIn(v) => v.snapshot(variables),
+        }
+    }
 }
 
 impl InMemoryParameterCondition for CompiledParameterExpression {
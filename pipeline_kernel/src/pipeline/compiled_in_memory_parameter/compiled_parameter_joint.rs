@@ -1,6 +1,6 @@
 use crate::{CompiledParameterCondition, InMemoryParameterCondition, PipelineExecutionVariables};
 use elf_base::StdR;
-use elf_model::{ParameterJointType, TenantId};
+use elf_model::{MonitorLogParameterJoint, ParameterJointType, TenantId};
 use elf_runtime_model_kernel::ArcParameterJoint;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -23,6 +23,22 @@ impl CompiledParameterJoint {
             conditions,
         })
     }
+
+    /// snapshot with resolved values, for monitor log
+    pub fn snapshot(
+        &self,
+        variables: &PipelineExecutionVariables,
+    ) -> StdR<MonitorLogParameterJoint> {
+        let mut filters = vec![];
+        for condition in &self.conditions {
+            filters.push(condition.snapshot(variables)?);
+        }
+
+        Ok(MonitorLogParameterJoint {
+            joint_type: self.r#type.deref().clone(),
+            filters,
+        })
+    }
 }
 
 impl InMemoryParameterCondition for CompiledParameterJoint {
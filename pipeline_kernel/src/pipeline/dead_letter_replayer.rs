@@ -0,0 +1,312 @@
+use crate::{
+	ArcTopicDataConverter, DeadLetterStores, PipelineCancellationToken,
+	PipelineExecutionEnvironment, PipelineExecutionLogMonitor, PipelineKernelErrorCode,
+	PipelineRunContext, PipelineRunSummary, PipelineRunSummaryCollector, PipelineRunner,
+	PipelineTrigger, TopicTrigger,
+};
+use elf_auth::Principal;
+use elf_base::{ErrorCode, StdR};
+use elf_model::{PipelineDeadLetter, PipelineTriggerTraceId, PipelineTriggerType};
+use elf_runtime_model_kernel::{
+	PipelineSchemaProvider, PipelineService, TopicSchema, TopicSchemaProvider, TopicService,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// dead letter with its run prepared, or the error of preparing
+type PipelineDeadLetterPrepared = (
+    PipelineDeadLetter,
+    StdR<(PipelineRunContext, Arc<PipelineRunSummaryCollector>)>,
+);
+
+/// outcome of replaying one dead letter, the dead letter is discarded when replay successful.
+pub struct PipelineDeadLetterReplay {
+    pub dead_letter: PipelineDeadLetter,
+    pub summary: PipelineRunSummary,
+}
+
+/// list, replay and discard dead letters of one trace, in tenant of given principal.
+/// dead letter is replayed by the current definition of its pipeline, and with its trace id.
+/// replayed execution is retried by failure policy, but not dead-lettered again,
+/// the dead letter is kept when replay failed.
+pub struct PipelineDeadLetterReplayer {
+    principal: Arc<Principal>,
+    unit_of_work: bool,
+}
+
+impl PipelineDeadLetterReplayer {
+    pub fn new(principal: Arc<Principal>, unit_of_work: bool) -> Self {
+        Self {
+            principal,
+            unit_of_work,
+        }
+    }
+
+    pub fn find(&self, trace_id: &PipelineTriggerTraceId) -> StdR<Vec<PipelineDeadLetter>> {
+        DeadLetterStores::store()?.find_by_trace_id(&self.principal.tenant_id, trace_id)
+    }
+
+    /// returns the discarded dead letters
+    pub fn discard(&self, trace_id: &PipelineTriggerTraceId) -> StdR<Vec<PipelineDeadLetter>> {
+        let store = DeadLetterStores::store()?;
+        let dead_letters = store.find_by_trace_id(&self.principal.tenant_id, trace_id)?;
+        for dead_letter in &dead_letters {
+            if let Some(dead_letter_id) = &dead_letter.dead_letter_id {
+                store.discard(&self.principal.tenant_id, dead_letter_id)?;
+            }
+        }
+        Ok(dead_letters)
+    }
+
+    /// rebuild topic trigger by snapshot of dead letter, snapshot is given back after rebuilt
+    fn topic_trigger_of(
+        topic_schema: &TopicSchema,
+        dead_letter: &mut PipelineDeadLetter,
+    ) -> StdR<Arc<TopicTrigger>> {
+        let previous = dead_letter.old_value.take();
+        let current = dead_letter.new_value.take();
+        let topic_trigger = match (&dead_letter.trigger_type, previous, current) {
+            (Some(PipelineTriggerType::Insert), _, Some(current)) => {
+                if topic_schema.topic().is_synonym_topic() {
+                    TopicTrigger::insert_to_synonym(current)
+                } else {
+                    TopicTrigger::insert(current)
+                }
+            }
+            (
+                Some(PipelineTriggerType::Merge | PipelineTriggerType::InsertOrMerge),
+                Some(previous),
+                Some(current),
+            ) => TopicTrigger::merge(previous, current),
+            (Some(PipelineTriggerType::InsertOrMerge), None, Some(current)) => {
+                TopicTrigger::insert(current)
+            }
+            (Some(PipelineTriggerType::Delete), Some(previous), _) => {
+                TopicTrigger::delete(previous)
+            }
+            (trigger_type, _, _) => {
+                PipelineKernelErrorCode::DeadLetterTriggerDataMissed.msg(format!(
+                    "Trigger data of dead letter[{}] not found, or not match trigger type[{}].",
+                    dead_letter.dead_letter_id.as_deref().unwrap_or_default(),
+                    trigger_type
+                        .as_ref()
+                        .map(|t| t.to_string())
+                        .unwrap_or_default()
+                ))
+            }
+        }?;
+
+        dead_letter.old_value = topic_trigger
+            .previous
+            .as_ref()
+            .map(|previous| previous.to_topic_data());
+        dead_letter.new_value = topic_trigger
+            .current
+            .as_ref()
+            .map(|current| current.to_topic_data());
+        Ok(topic_trigger)
+    }
+
+    fn prepare(
+        &self,
+        dead_letter: &mut PipelineDeadLetter,
+    ) -> StdR<(PipelineRunContext, Arc<PipelineRunSummaryCollector>)> {
+        let tenant_id = &self.principal.tenant_id;
+        let pipeline_id = dead_letter.pipeline_id.clone().unwrap_or_default();
+        let pipeline_schema = PipelineService::schema()?
+            .by_pipeline_id(&pipeline_id, tenant_id)?
+            .ok_or_else(|| {
+                PipelineKernelErrorCode::DeadLetterPipelineNotFound.err_with_msg(format!(
+                    "Pipeline[{}] of dead letter[{}] not found.",
+                    pipeline_id,
+                    dead_letter.dead_letter_id.as_deref().unwrap_or_default()
+                ))
+            })?;
+        let topic_schema =
+            TopicService::schema()?.by_id(&pipeline_schema.pipeline().topic_id, tenant_id)?;
+        let topic_trigger = Self::topic_trigger_of(&topic_schema, dead_letter)?;
+
+        let summary = Arc::new(PipelineRunSummaryCollector::new());
+        let trace_id = Arc::new(dead_letter.trace_id.clone().unwrap_or_default());
+        let pipeline_trigger = PipelineTrigger {
+            pipeline_id: Some(pipeline_id),
+            topic_schema,
+            r#type: pipeline_schema.r#type().deref().clone(),
+            principal: self.principal.clone(),
+            trace_id: trace_id.clone(),
+            execution_log_monitor: Arc::new(PipelineExecutionLogMonitor {
+                principal: self.principal.clone(),
+                trace_id,
+                summary: Some(summary.clone()),
+                writer: None,
+            }),
+            execution_environment: Arc::new(PipelineExecutionEnvironment {
+                principal: self.principal.clone(),
+                simulation: None,
+                unit_of_work: self.unit_of_work,
+                dead_letter: false,
+                cancellation: Arc::new(PipelineCancellationToken::new()),
+                time_budget: None,
+            }),
+        };
+        let context =
+            PipelineRunContext::new(&pipeline_trigger, topic_trigger, vec![pipeline_schema])?;
+        Ok((context, summary))
+    }
+
+    /// prepare each dead letter of trace, in saved order.
+    /// error of preparing is kept with its dead letter, will not abort the others.
+    fn prepare_all(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetterPrepared>> {
+        Ok(self
+            .find(trace_id)?
+            .into_iter()
+            .map(|mut dead_letter| {
+                let prepared = self.prepare(&mut dead_letter);
+                (dead_letter, prepared)
+            })
+            .collect())
+    }
+
+    /// discard the dead letter when no pipeline failed.
+    /// replay cannot be prepared or run is recorded as failed on pipeline of dead letter.
+    fn finish(
+        &self,
+        dead_letter: PipelineDeadLetter,
+        summary: StdR<PipelineRunSummary>,
+    ) -> StdR<PipelineDeadLetterReplay> {
+        let summary = match summary {
+            Ok(summary) => summary,
+            Err(error) => {
+                let pipeline_id = dead_letter.pipeline_id.clone().unwrap_or_default();
+                let mut summary = PipelineRunSummary::default();
+                summary.failed.push((Arc::new(pipeline_id), error));
+                summary
+            }
+        };
+        if summary.failed.is_empty()
+            && let Some(dead_letter_id) = &dead_letter.dead_letter_id
+        {
+            DeadLetterStores::store()?.discard(&self.principal.tenant_id, dead_letter_id)?;
+        }
+        Ok(PipelineDeadLetterReplay {
+            dead_letter,
+            summary,
+        })
+    }
+
+    /// replay dead letters one by one, in saved order.
+    /// failed replay is returned with its error, and next dead letter is replayed continually.
+    /// blocks current thread, do not call it in async context, use [replay_async] instead.
+    pub fn replay(&self, trace_id: &PipelineTriggerTraceId) -> StdR<Vec<PipelineDeadLetterReplay>> {
+        let mut replays = vec![];
+        for (dead_letter, prepared) in self.prepare_all(trace_id)? {
+            let summary = prepared.and_then(|(context, summary)| {
+                PipelineRunner::execute(context)?.wait()?;
+                Ok(summary.take())
+            });
+            replays.push(self.finish(dead_letter, summary)?);
+        }
+        Ok(replays)
+    }
+
+    pub async fn replay_async(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetterReplay>> {
+        let mut replays = vec![];
+        for (dead_letter, prepared) in self.prepare_all(trace_id)? {
+            let summary = match prepared {
+                Ok((context, summary)) => PipelineRunner::execute_async(context)
+                    .await
+                    .map(|_| summary.take()),
+                Err(error) => Err(error),
+            };
+            replays.push(self.finish(dead_letter, summary)?);
+        }
+        Ok(replays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PipelineDeadLetterReplayer;
+    use elf_auth::Principal;
+    use elf_base::{ErrorCode, StdErrCode};
+    use elf_model::{
+        Factor, FactorType, PipelineDeadLetter, PipelineTriggerType, Topic, TopicDataValue,
+        TopicKind, TopicType,
+    };
+    use elf_runtime_model_kernel::TopicSchema;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn create_topic_schema() -> TopicSchema {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("name"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        TopicSchema::new(topic).expect("failed to create topic schema")
+    }
+
+    fn create_row(id: &str, name: &str) -> HashMap<String, TopicDataValue> {
+        HashMap::from([
+            (String::from("id_"), TopicDataValue::Str(id.to_string())),
+            (String::from("name"), TopicDataValue::Str(name.to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_topic_trigger_of() {
+        let topic_schema = create_topic_schema();
+
+        let mut dead_letter = PipelineDeadLetter::new()
+            .dead_letter_id(String::from("dl-1"))
+            .trigger_type(PipelineTriggerType::Merge)
+            .old_value(create_row("1", "a"))
+            .new_value(create_row("1", "b"));
+        let topic_trigger =
+            PipelineDeadLetterReplayer::topic_trigger_of(&topic_schema, &mut dead_letter).unwrap();
+        assert_eq!(topic_trigger.r#type, PipelineTriggerType::Merge);
+        assert_eq!(topic_trigger.internal_data_id.as_str(), "1");
+        // snapshot is given back
+        assert!(matches!(
+            dead_letter.new_value.unwrap().get("name"),
+            Some(TopicDataValue::Str(v)) if v == "b"
+        ));
+
+        let mut dead_letter = PipelineDeadLetter::new()
+            .dead_letter_id(String::from("dl-2"))
+            .trigger_type(PipelineTriggerType::Delete)
+            .new_value(create_row("2", "b"));
+        let error =
+            PipelineDeadLetterReplayer::topic_trigger_of(&topic_schema, &mut dead_letter).err();
+        assert_eq!(error.unwrap().code, "PLKN-00802");
+    }
+
+    #[test]
+    fn test_finish_failed() {
+        let replayer =
+            PipelineDeadLetterReplayer::new(Arc::new(Principal::fake_super_admin()), false);
+        let dead_letter = PipelineDeadLetter::new()
+            .dead_letter_id(String::from("dl-1"))
+            .pipeline_id(String::from("p1"));
+        let replay = replayer
+            .finish(dead_letter, StdErrCode::Unknown.msg("Pipeline not found."))
+            .unwrap();
+        assert_eq!(replay.summary.failed.len(), 1);
+        assert_eq!(replay.summary.failed[0].0.as_str(), "p1");
+        assert_eq!(replay.dead_letter.dead_letter_id.as_deref(), Some("dl-1"));
+    }
+}
@@ -1,13 +1,19 @@
-use crate::{PipelineExecutionLogMonitor, PipelineKernelErrorCode, PipelineTrigger};
+use crate::{
+	PipelineCancellationToken, PipelineDeadLetterReplay, PipelineDeadLetterReplayer,
+	PipelineExecutionEnvironment, PipelineExecutionLogMonitor, PipelineKernelErrorCode,
+	PipelineSimulationResult, PipelineTrigger, PipelineTriggerBatchRows, PipelineTriggerOutcome,
+};
 use elf_auth::Principal;
 use elf_base::VoidResultHelper;
 use elf_base::{ErrorCode, StdR, StringUtils, VoidR};
 use elf_model::{
-	PipelineId, PipelineTriggerData, PipelineTriggerTraceId, PipelineTriggerType, TopicData,
-	TopicDataId, UserRole,
+	Pipeline, PipelineDeadLetter, PipelineId, PipelineTriggerBatchData, PipelineTriggerData,
+	PipelineTriggerResult, PipelineTriggerTraceId, PipelineTriggerType, TenantId, TopicCode,
+	TopicData, UserRole,
 };
 use elf_runtime_model_kernel::{IdGen, TopicSchema, TopicSchemaProvider, TopicService};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// This is the main entry point for executing pipelines.
 /// At this point, the specific pipelines to be executed are not yet known.
@@ -19,10 +25,17 @@ use std::sync::Arc;
 /// - The trace id is used as the basis for linking all processes in a single pipelines execution.
 ///   If this execution is caused by the execution of another pipeline,
 ///   then the trace id needs to be provided; otherwise, it is not required.
+/// - Writes of each pipeline can be buffered as a unit of work, which is optional.
+///
+/// Dead letters of a trace, which are the executions failed after all retries,
+/// can be listed, replayed and discarded in tenant of the principal.
 pub struct PipelineEntrypoint {
     principal: Principal,
     pipeline_id: Option<PipelineId>,
     trace_id: Option<PipelineTriggerTraceId>,
+    unit_of_work: bool,
+    cancellation: Arc<PipelineCancellationToken>,
+    time_budget: Option<Duration>,
 }
 
 impl PipelineEntrypoint {
@@ -31,6 +44,9 @@ impl PipelineEntrypoint {
             principal,
             pipeline_id: None,
             trace_id: None,
+            unit_of_work: false,
+            cancellation: Arc::new(PipelineCancellationToken::new()),
+            time_budget: None,
         }
     }
 
@@ -53,8 +69,32 @@ impl PipelineEntrypoint {
         }
     }
 
-    fn check_trigger_code(&self, trigger_data: &PipelineTriggerData) -> VoidR {
-        if let Some(code) = &trigger_data.code {
+    /// buffer writes of each pipeline, commit them when pipeline successful, or discard them on error.
+    pub fn unit_of_work(mut self) -> Self {
+        self.unit_of_work = true;
+        self
+    }
+
+    /// time budget of each trace, including the cascaded executions.
+    /// execution in progress is interrupted when budget exceeded, and the remaining ones are not run.
+    pub fn time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// share the given token with all executions triggered by this entrypoint
+    pub fn cancelled_by(mut self, cancellation: Arc<PipelineCancellationToken>) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// cancel all executions triggered by this entrypoint, including the in-progress ones
+    pub fn cancellation_token(&self) -> &Arc<PipelineCancellationToken> {
+        &self.cancellation
+    }
+
+    fn check_trigger_code(&self, code: &Option<TopicCode>) -> VoidR {
+        if let Some(code) = code {
             if code.is_blank() {
                 PipelineKernelErrorCode::TriggerCodeIsBlank
                     .msg("Pipeline trigger code cannot be blank.")
@@ -66,8 +106,8 @@ impl PipelineEntrypoint {
         }
     }
 
-    fn check_trigger_type(&self, trigger_data: &PipelineTriggerData) -> VoidR {
-        if trigger_data.trigger_type.is_none() {
+    fn check_trigger_type(&self, trigger_type: &Option<PipelineTriggerType>) -> VoidR {
+        if trigger_type.is_none() {
             PipelineKernelErrorCode::TriggerTypeMissed.msg("Pipeline trigger type cannot be empty.")
         } else {
             Ok(())
@@ -76,10 +116,10 @@ impl PipelineEntrypoint {
 
     fn check_trigger_type_with_topic(
         &self,
-        trigger_data: &PipelineTriggerData,
+        trigger_type: &Option<PipelineTriggerType>,
         topic_schema: &Arc<TopicSchema>,
     ) -> VoidR {
-        match &trigger_data.trigger_type {
+        match trigger_type {
             Some(trigger_type) => match trigger_type {
                 PipelineTriggerType::Insert => Ok(()),
                 other => {
@@ -114,9 +154,8 @@ impl PipelineEntrypoint {
         }
     }
 
-    fn check_trigger_access(&self, trigger_data: &PipelineTriggerData) -> VoidR {
+    fn check_trigger_access(&self, opt_tenant_id: &Option<TenantId>) -> VoidR {
         let principal = &self.principal;
-        let opt_tenant_id = &trigger_data.tenant_id;
 
         if principal.is_super_admin() {
             if let Some(tenant_id) = opt_tenant_id {
@@ -141,22 +180,45 @@ impl PipelineEntrypoint {
         Ok(())
     }
 
-    fn check_and_prepare(
-        &self,
-        trigger_data: PipelineTriggerData,
-    ) -> StdR<(PipelineTrigger, TopicData)> {
-        // check given data
-        Vec::new()
-            .collect(self.check_trigger_access(&trigger_data))
-            .collect(self.check_trigger_code(&trigger_data))
-            .collect(self.check_trigger_type(&trigger_data))
-            .collect(self.check_trigger_data(&trigger_data))
-            .accumulate()?;
+    fn check_batch_trigger_data(&self, batch_data: &PipelineTriggerBatchData) -> VoidR {
+        match &batch_data.data {
+            Some(data) if !data.is_empty() => Ok(()),
+            _ => PipelineKernelErrorCode::TriggerDataMissed
+                .msg("Pipeline batch trigger data cannot be empty."),
+        }
+    }
+
+    fn check_batch_trigger_type(&self, batch_data: &PipelineTriggerBatchData) -> VoidR {
+        match &batch_data.trigger_type {
+            Some(PipelineTriggerType::Insert) | Some(PipelineTriggerType::InsertOrMerge) => Ok(()),
+            Some(other) => PipelineKernelErrorCode::TriggerTypeNotSupportedOnBatch.msg(format!(
+                "Trigger type[{}] is not supported on batch, only insert and insert-or-merge are supported.",
+                other
+            )),
+            _ => Ok(()),
+        }
+    }
 
+    /// given trace id, or generate a new one
+    fn trace_id(&self) -> StdR<PipelineTriggerTraceId> {
+        if let Some(trace_id) = &self.trace_id {
+            Ok(trace_id.clone())
+        } else {
+            Ok(IdGen::next_id()?.to_string())
+        }
+    }
+
+    /// trigger data must be checked before calling this function
+    fn prepare_trigger(
+        &self,
+        code: &Option<TopicCode>,
+        trigger_type: Option<PipelineTriggerType>,
+        tenant_id: &Option<TenantId>,
+    ) -> StdR<PipelineTrigger> {
         // prepare execute principal
         let execute_principal: Principal = if self.principal.is_super_admin() {
             // switch to given tenant and fake as admin role
-            let trigger_tenant_id = trigger_data.tenant_id.clone().unwrap();
+            let trigger_tenant_id = tenant_id.clone().unwrap();
             self.principal
                 .switch_tenant(trigger_tenant_id, UserRole::Admin)
         } else {
@@ -164,52 +226,246 @@ impl PipelineEntrypoint {
             self.principal.clone()
         };
 
-        let topic_schema = TopicService::schema()?.by_code(
-            &trigger_data.code.as_ref().unwrap(),
-            &execute_principal.tenant_id,
-        )?;
-        self.check_trigger_type_with_topic(&trigger_data, &topic_schema)?;
+        let topic_schema = TopicService::schema()?
+            .by_code(code.as_ref().unwrap(), &execute_principal.tenant_id)?;
+        self.check_trigger_type_with_topic(&trigger_type, &topic_schema)?;
 
         // prepare trace id
-        let trace_id = if let Some(trace_id) = &self.trace_id {
-            trace_id.clone()
-        } else {
-            IdGen::next_id()?.to_string()
-        };
+        let trace_id = self.trace_id()?;
 
         let principal = Arc::new(execute_principal);
         let trace_id = Arc::new(trace_id);
-        let pipeline_trigger = PipelineTrigger {
+        Ok(PipelineTrigger {
             pipeline_id: self.pipeline_id.clone(),
             topic_schema,
-            r#type: trigger_data.trigger_type.unwrap(),
+            r#type: trigger_type.unwrap(),
             trace_id: trace_id.clone(),
             principal: principal.clone(),
             execution_log_monitor: Arc::new(PipelineExecutionLogMonitor {
                 trace_id,
+                principal: principal.clone(),
+                summary: None,
+                writer: None,
+            }),
+            execution_environment: Arc::new(PipelineExecutionEnvironment {
                 principal,
+                simulation: None,
+                unit_of_work: self.unit_of_work,
+                dead_letter: true,
+                cancellation: self.cancellation.clone(),
+                time_budget: self.time_budget,
             }),
-        };
+        })
+    }
+
+    fn check_and_prepare(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<(PipelineTrigger, TopicData)> {
+        // check given data
+        Vec::new()
+            .collect(self.check_trigger_access(&trigger_data.tenant_id))
+            .collect(self.check_trigger_code(&trigger_data.code))
+            .collect(self.check_trigger_type(&trigger_data.trigger_type))
+            .collect(self.check_trigger_data(&trigger_data))
+            .accumulate()?;
+
+        let pipeline_trigger = self.prepare_trigger(
+            &trigger_data.code,
+            trigger_data.trigger_type,
+            &trigger_data.tenant_id,
+        )?;
         let topic_data = trigger_data.data.unwrap();
         Ok((pipeline_trigger, topic_data))
     }
 
-    pub fn execute(&self, trigger_data: PipelineTriggerData) -> StdR<TopicDataId> {
+    /// trace id of first row is the trace id of returned trigger,
+    /// other rows use the given trace id, or a new generated one.
+    fn check_and_prepare_batch(
+        &self,
+        batch_data: PipelineTriggerBatchData,
+    ) -> StdR<(PipelineTrigger, PipelineTriggerBatchRows)> {
+        // check given data
+        Vec::new()
+            .collect(self.check_trigger_access(&batch_data.tenant_id))
+            .collect(self.check_trigger_code(&batch_data.code))
+            .collect(self.check_trigger_type(&batch_data.trigger_type))
+            .collect(self.check_batch_trigger_type(&batch_data))
+            .collect(self.check_batch_trigger_data(&batch_data))
+            .accumulate()?;
+
+        let pipeline_trigger = self.prepare_trigger(
+            &batch_data.code,
+            batch_data.trigger_type,
+            &batch_data.tenant_id,
+        )?;
+        let mut rows = vec![];
+        for (index, topic_data) in batch_data.data.unwrap().into_iter().enumerate() {
+            let trace_id = if index == 0 {
+                pipeline_trigger.trace_id.clone()
+            } else {
+                Arc::new(self.trace_id()?)
+            };
+            rows.push((trace_id, topic_data));
+        }
+        Ok((pipeline_trigger, rows))
+    }
+
+    pub fn execute(&self, trigger_data: PipelineTriggerData) -> StdR<PipelineTriggerResult> {
         let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
         pipeline_trigger.execute(topic_data)
     }
 
-    pub async fn execute_async(&self, trigger_data: PipelineTriggerData) -> StdR<TopicDataId> {
+    /// execute and wait until all pipelines completed, returns the result with run summary.
+    /// blocks current thread, do not call it in async context, use [execute_and_wait_async] instead.
+    pub fn execute_and_wait(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<PipelineTriggerOutcome> {
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger.execute_and_wait(topic_data)
+    }
+
+    pub async fn execute_and_wait_async(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<PipelineTriggerOutcome> {
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger.execute_and_wait_async(topic_data).await
+    }
+
+    /// existing row is always triggered as inserted,
+    /// insert-or-merge is accepted, so the pipeline of it can be matched.
+    pub(crate) fn check_existing_trigger_type(
+        &self,
+        trigger_type: &Option<PipelineTriggerType>,
+    ) -> VoidR {
+        match trigger_type {
+            Some(PipelineTriggerType::Insert | PipelineTriggerType::InsertOrMerge) => Ok(()),
+            Some(other) => PipelineKernelErrorCode::TriggerTypeNotSupported.msg(format!(
+                "Trigger type[{}] is not supported on existing data, only insert and insert-or-merge are supported.",
+                other
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// trigger by the row which exists in topic already, the row is not saved again.
+    /// only insert and insert-or-merge are supported, execute and wait until all pipelines completed.
+    /// blocks current thread, do not call it in async context,
+    /// use [execute_existing_and_wait_async] instead.
+    pub fn execute_existing_and_wait(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<PipelineTriggerOutcome> {
+        self.check_existing_trigger_type(&trigger_data.trigger_type)?;
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger.execute_existing_and_wait(topic_data)
+    }
+
+    pub async fn execute_existing_and_wait_async(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<PipelineTriggerOutcome> {
+        self.check_existing_trigger_type(&trigger_data.trigger_type)?;
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger
+            .execute_existing_and_wait_async(topic_data)
+            .await
+    }
+
+    /// simulate with given pipeline or the stored ones, nothing is persisted or sent.
+    /// returns the would-be changes per topic, and the captured alarms and external writes.
+    /// blocks current thread, do not call it in async context, use [simulate_async] instead.
+    pub fn simulate(
+        &self,
+        trigger_data: PipelineTriggerData,
+        pipeline: Option<Pipeline>,
+    ) -> StdR<PipelineSimulationResult> {
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger.simulate(topic_data, pipeline)
+    }
+
+    pub async fn simulate_async(
+        &self,
+        trigger_data: PipelineTriggerData,
+        pipeline: Option<Pipeline>,
+    ) -> StdR<PipelineSimulationResult> {
+        let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
+        pipeline_trigger.simulate_async(topic_data, pipeline).await
+    }
+
+    /// execute pipelines for each row of given batch,
+    /// the topic schema, principal and pipelines are resolved only once for all rows.
+    /// returns result of each row in the same order, a failed row does not abort the others.
+    pub fn execute_batch(
+        &self,
+        batch_data: PipelineTriggerBatchData,
+    ) -> StdR<Vec<StdR<PipelineTriggerResult>>> {
+        let (pipeline_trigger, rows) = self.check_and_prepare_batch(batch_data)?;
+        pipeline_trigger.execute_batch(rows)
+    }
+
+    pub async fn execute_async(
+        &self,
+        trigger_data: PipelineTriggerData,
+    ) -> StdR<PipelineTriggerResult> {
         let (pipeline_trigger, topic_data) = self.check_and_prepare(trigger_data)?;
         pipeline_trigger.execute_async(topic_data).await
     }
+
+    fn dead_letter_replayer(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<PipelineDeadLetterReplayer> {
+        if trace_id.is_blank() {
+            PipelineKernelErrorCode::TriggerTraceIdIsBlank.msg("Given trace id cannot be blank.")
+        } else {
+            Ok(PipelineDeadLetterReplayer::new(
+                Arc::new(self.principal.clone()),
+                self.unit_of_work,
+            ))
+        }
+    }
+
+    pub fn dead_letters(&self, trace_id: &PipelineTriggerTraceId) -> StdR<Vec<PipelineDeadLetter>> {
+        self.dead_letter_replayer(trace_id)?.find(trace_id)
+    }
+
+    /// replay dead letters of given trace, the successfully replayed ones are discarded.
+    /// blocks current thread, do not call it in async context, use [replay_dead_letters_async] instead.
+    pub fn replay_dead_letters(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetterReplay>> {
+        self.dead_letter_replayer(trace_id)?.replay(trace_id)
+    }
+
+    pub async fn replay_dead_letters_async(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetterReplay>> {
+        self.dead_letter_replayer(trace_id)?
+            .replay_async(trace_id)
+            .await
+    }
+
+    /// discard dead letters of given trace without replay, returns the discarded ones.
+    pub fn discard_dead_letters(
+        &self,
+        trace_id: &PipelineTriggerTraceId,
+    ) -> StdR<Vec<PipelineDeadLetter>> {
+        self.dead_letter_replayer(trace_id)?.discard(trace_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 	use crate::PipelineEntrypoint;
 	use elf_auth::Principal;
-	use elf_model::{PipelineTriggerData, PipelineTriggerType, TopicData};
+	use elf_model::{
+		PipelineTriggerBatchData, PipelineTriggerData, PipelineTriggerType, TopicData,
+	};
 
 	#[test]
     fn test() {
@@ -225,4 +481,18 @@ mod tests {
             .execute(trigger_data);
         assert!(result.is_ok());
     }
+
+	#[test]
+    fn test_batch_checks() {
+        let batch_data = PipelineTriggerBatchData::new()
+            .code(String::from("topic-1"))
+            .trigger_type(PipelineTriggerType::Delete)
+            .tenant_id(String::from("tenant-1"))
+            .data(vec![]);
+        let result =
+            PipelineEntrypoint::with(Principal::fake_super_admin()).execute_batch(batch_data);
+        let message = result.err().unwrap().to_string();
+        assert!(message.contains("PLKN-00015"));
+        assert!(message.contains("PLKN-00006"));
+    }
 }
@@ -1,15 +1,40 @@
-use crate::{PipelineExecutionLogMonitor, TopicTrigger};
+use crate::{PipelineExecutionEnvironment, PipelineExecutionLogMonitor, TopicTrigger};
 use elf_auth::Principal;
-use elf_model::PipelineTriggerTraceId;
-use elf_runtime_model_kernel::{PipelineSchema, TopicSchema};
+use elf_base::StdR;
+use elf_model::{PipelineMonitorLogId, PipelineTriggerTraceId};
+use elf_runtime_model_kernel::{IdGen, PipelineSchema, TopicSchema};
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct PipelineExecution {
     pub topic_schema: Arc<TopicSchema>,
     pub pipeline_schema: Arc<PipelineSchema>,
     pub topic_trigger: Arc<TopicTrigger>,
+    /// id of monitor log, assigned before run
+    pub monitor_log_id: Arc<PipelineMonitorLogId>,
+    /// id of monitor log of the first attempt when retried, none for the first attempt
+    pub retry_of: Option<Arc<PipelineMonitorLogId>>,
     // env
     pub principal: Arc<Principal>,
     pub trace_id: Arc<PipelineTriggerTraceId>,
     pub execution_log_monitor: Arc<PipelineExecutionLogMonitor>,
+    pub execution_environment: Arc<PipelineExecutionEnvironment>,
+}
+
+impl PipelineExecution {
+    pub fn next_monitor_log_id() -> StdR<Arc<PipelineMonitorLogId>> {
+        Ok(Arc::new(IdGen::next_id()?.to_string()))
+    }
+
+    /// next attempt of this execution, with a new monitor log id which linked to the first attempt.
+    pub fn retried(&self) -> StdR<Self> {
+        let mut execution = self.clone();
+        execution.retry_of = Some(
+            self.retry_of
+                .clone()
+                .unwrap_or_else(|| self.monitor_log_id.clone()),
+        );
+        execution.monitor_log_id = Self::next_monitor_log_id()?;
+        Ok(execution)
+    }
 }
@@ -0,0 +1,157 @@
+use crate::{
+	AlarmSinks, ArcTopicDataConverter, DeadLetterStores, PipelineAlarm, PipelineCancellationToken,
+	PipelineExecution, PipelineSimulation, SequenceStore, TopicDataStore,
+};
+use chrono::Local;
+use elf_auth::Principal;
+use elf_base::{StdErr, StdR, VoidR};
+use elf_model::{ExternalWriterId, PipelineDeadLetter};
+use elf_runtime_model_kernel::{
+	ExternalWriteParams, ExternalWriterAdapterProvider, ExternalWriterMetaProvider,
+	ExternalWriterService, IdGen, TopicDataProvider, TopicService,
+};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// environment of executions of one trigger, shared by the cascaded executions.
+/// routes topic data, alarms, external writes and dead letters.
+pub struct PipelineExecutionEnvironment {
+    pub principal: Arc<Principal>,
+    /// routes writes to in-memory overlay when simulating, otherwise none
+    pub simulation: Option<Arc<PipelineSimulation>>,
+    /// buffers writes of each pipeline, and commits them when pipeline successful
+    pub unit_of_work: bool,
+    /// persist the execution as dead letter when it is failed after all retries,
+    /// and failure policy of pipeline requires. false when replaying dead letters.
+    pub dead_letter: bool,
+    /// cancels all executions of the trigger, also reachable by run handle
+    pub cancellation: Arc<PipelineCancellationToken>,
+    /// time budget of one trace, including the cascaded executions, no limit when none
+    pub time_budget: Option<Duration>,
+}
+
+impl PipelineExecutionEnvironment {
+    /// create environment which shares everything with this one, except the simulation
+    pub fn simulated_by(&self, simulation: Arc<PipelineSimulation>) -> Self {
+        PipelineExecutionEnvironment {
+            principal: self.principal.clone(),
+            simulation: Some(simulation),
+            unit_of_work: self.unit_of_work,
+            dead_letter: self.dead_letter,
+            cancellation: self.cancellation.clone(),
+            time_budget: self.time_budget,
+        }
+    }
+
+    /// topic data store, the in-memory overlay when simulating
+    pub fn topic_data(&self) -> StdR<Arc<dyn TopicDataStore>> {
+        match &self.simulation {
+            Some(simulation) => Ok(simulation.data()),
+            _ => Ok(TopicService::data()?),
+        }
+    }
+
+    /// scratch sequence store when simulating,
+    /// otherwise none and the configured one of [crate::SequenceStores] is used
+    pub fn sequence_store(&self) -> Option<Arc<dyn SequenceStore>> {
+        self.simulation
+            .as_ref()
+            .map(|simulation| simulation.sequence_store())
+    }
+
+    /// emit alarm by [AlarmSinks], or capture it when simulating
+    pub fn alarm(&self, alarm: PipelineAlarm) -> VoidR {
+        match &self.simulation {
+            Some(simulation) => simulation.alarm(alarm),
+            _ => AlarmSinks::emit(alarm),
+        }
+    }
+
+    /// write to external writer, or capture it when simulating.
+    /// writer is not looked up when simulating.
+    pub async fn write_external(
+        &self,
+        external_writer_id: &Arc<ExternalWriterId>,
+        params: ExternalWriteParams,
+    ) -> VoidR {
+        if let Some(simulation) = &self.simulation {
+            return simulation.write_external(external_writer_id, params);
+        }
+
+        let writer = ExternalWriterService::meta()?
+            .find_by_id(external_writer_id, &self.principal.tenant_id)?;
+        ExternalWriterService::adapters()?
+            .write(&writer, &params)
+            .await
+    }
+
+    /// persist the given execution as dead letter by [DeadLetterStores].
+    pub fn save_dead_letter(
+        &self,
+        execution: &PipelineExecution,
+        error: &StdErr,
+        attempts: u32,
+    ) -> VoidR {
+        let topic_trigger = &execution.topic_trigger;
+        let dead_letter = IdGen::next_id().map(|id| {
+            PipelineDeadLetter::new()
+                .dead_letter_id(id.to_string())
+                .trace_id(execution.trace_id.deref().clone())
+                .pipeline_id(execution.pipeline_schema.pipeline_id().deref().clone())
+                .topic_id(execution.topic_schema.topic_id().deref().clone())
+                .data_id(topic_trigger.internal_data_id.deref().clone())
+                .trigger_type(topic_trigger.r#type.clone())
+                .error(error.to_string())
+                .attempts(attempts)
+                .created_at(Local::now().naive_local())
+        });
+        dead_letter.and_then(|mut dead_letter| {
+            dead_letter.old_value = topic_trigger
+                .previous
+                .as_ref()
+                .map(|previous| previous.to_topic_data());
+            dead_letter.new_value = topic_trigger
+                .current
+                .as_ref()
+                .map(|current| current.to_topic_data());
+            DeadLetterStores::store()?.save(&self.principal.tenant_id, dead_letter)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PipelineCancellationToken, PipelineExecutionEnvironment};
+    use elf_auth::Principal;
+    use elf_model::TopicData;
+    use elf_runtime_model_kernel::ExternalWriteParams;
+    use std::sync::Arc;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn test_external_writer_not_found() {
+        let environment = PipelineExecutionEnvironment {
+            principal: Arc::new(Principal::fake_super_admin()),
+            simulation: None,
+            unit_of_work: false,
+            dead_letter: false,
+            cancellation: Arc::new(PipelineCancellationToken::new()),
+            time_budget: None,
+        };
+        let params = ExternalWriteParams {
+            event_code: String::from("event-1"),
+            trigger_type: String::from("insert"),
+            previous: None,
+            current: None,
+            variables: TopicData::new(),
+        };
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let error = runtime
+            .block_on(environment.write_external(&Arc::new(String::from("writer-1")), params))
+            .err()
+            .unwrap();
+        assert_eq!(error.code, "RTMK-00904");
+    }
+}
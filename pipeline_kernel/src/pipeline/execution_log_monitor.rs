@@ -1,8 +1,103 @@
+use crate::{
+	AlarmSinks, MonitorLogWriter, MonitorLogWriters, PipelineAlarm, PipelineExecution,
+	PipelineRunSummaryCollector,
+};
 use elf_auth::Principal;
-use elf_model::PipelineTriggerTraceId;
+use elf_base::{StdErr, VoidR};
+use elf_model::{
+	AlarmActionSeverity, MonitorLogStatus, PipelineId, PipelineMonitorLog, PipelineTriggerTraceId,
+	PipelineTriggerType, TopicId,
+};
+use std::ops::Deref;
 use std::sync::Arc;
 
 pub struct PipelineExecutionLogMonitor {
     pub principal: Arc<Principal>,
     pub trace_id: Arc<PipelineTriggerTraceId>,
+    /// collects the run summary when trigger waits for completion, otherwise none
+    pub summary: Option<Arc<PipelineRunSummaryCollector>>,
+    /// writes logs instead of [MonitorLogWriters] when given, e.g. captured by simulation
+    pub writer: Option<Arc<dyn MonitorLogWriter>>,
+}
+
+impl PipelineExecutionLogMonitor {
+    /// log the given execution is not run, caused by given error.
+    pub fn log_error(&self, execution: &PipelineExecution, error: &StdErr) {
+        self.log_status(execution, MonitorLogStatus::ERROR, error);
+    }
+
+    /// log the given execution is interrupted by time budget exceeded.
+    /// writes done before interrupted are kept unless unit of work is used.
+    pub fn log_timeout(&self, execution: &PipelineExecution, error: &StdErr) {
+        self.log_status(execution, MonitorLogStatus::TIMEOUT, error);
+    }
+
+    fn log_status(&self, execution: &PipelineExecution, status: MonitorLogStatus, error: &StdErr) {
+        let mut log = PipelineMonitorLog::new()
+            .uid(execution.monitor_log_id.deref().clone())
+            .trace_id(self.trace_id.deref().clone())
+            .pipeline_id(execution.pipeline_schema.pipeline_id().deref().clone())
+            .topic_id(execution.topic_schema.topic_id().deref().clone())
+            .data_id(execution.topic_trigger.internal_data_id.deref().clone())
+            .status(status)
+            .error(error.to_string());
+        log.retry_of = execution
+            .retry_of
+            .as_ref()
+            .map(|retry_of| retry_of.deref().clone());
+        self.write(log);
+    }
+
+    /// write the given log by the given writer, or by [MonitorLogWriters] when not given.
+    /// failure of writing is emitted as alarm by [AlarmSinks], will not break the pipeline execution.
+    pub fn write(&self, log: PipelineMonitorLog) {
+        let uid = log.uid.clone().unwrap_or_default();
+        let written = match &self.writer {
+            Some(writer) => writer.write(&self.principal.tenant_id, log),
+            _ => MonitorLogWriters::write(&self.principal.tenant_id, log),
+        };
+        if let Err(error) = written {
+            // nowhere else to report when alarm cannot be emitted either
+            let _ = AlarmSinks::emit(PipelineAlarm {
+                tenant_id: self.principal.tenant_id.clone(),
+                trace_id: self.trace_id.clone(),
+                action_id: None,
+                severity: Arc::new(AlarmActionSeverity::High),
+                message: format!(
+                    "Failed to write pipeline monitor log[uid={}, trace_id={}], caused by {}.",
+                    uid, self.trace_id, error
+                ),
+            });
+        }
+    }
+
+    /// record the pipeline is run or skipped, into summary if required
+    pub fn executed(&self, pipeline_id: &Arc<PipelineId>, prerequisite: bool) {
+        if let Some(summary) = &self.summary {
+            if prerequisite {
+                summary.run(pipeline_id);
+            } else {
+                summary.skipped(pipeline_id);
+            }
+        }
+    }
+
+    /// record one row of given topic is touched, into summary if required
+    pub fn touched(&self, topic_id: &Arc<TopicId>, trigger_type: &PipelineTriggerType) {
+        if let Some(summary) = &self.summary {
+            summary.touched(topic_id, trigger_type);
+        }
+    }
+
+    /// record the pipeline is failed into summary, and returns ok.
+    /// or returns the given error when summary is not required.
+    pub fn failed(&self, pipeline_id: &Arc<PipelineId>, error: StdErr) -> VoidR {
+        match &self.summary {
+            Some(summary) => {
+                summary.failed(pipeline_id, error);
+                Ok(())
+            }
+            _ => Err(error),
+        }
+    }
 }
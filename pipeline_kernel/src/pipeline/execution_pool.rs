@@ -0,0 +1,290 @@
+use crate::{PipelineKernelErrorCode, PipelineRunHandle};
+use elf_base::{ErrorCode, StdR, VoidR};
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::Pin;
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::oneshot;
+
+/// what to do when the queue of pool is full
+pub enum PipelineBackpressurePolicy {
+    /// block the submitter until there is space in queue
+    Block,
+    /// reject the submission, raise error
+    Reject,
+}
+
+pub struct PipelineExecutionPoolConfig {
+    /// count of worker threads, each worker runs one pipeline run context at a time
+    pub workers: usize,
+    /// count of pipeline run contexts waiting for worker
+    pub queue_size: usize,
+    pub backpressure: PipelineBackpressurePolicy,
+}
+
+impl Default for PipelineExecutionPoolConfig {
+    /// workers is available parallelism, queue size is 1024, and block when queue is full.
+    fn default() -> Self {
+        PipelineExecutionPoolConfig {
+            workers: thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            queue_size: 1024,
+            backpressure: PipelineBackpressurePolicy::Block,
+        }
+    }
+}
+
+type PipelineRunFuture = Pin<Box<dyn Future<Output = VoidR>>>;
+type PipelineRunTask = Box<dyn FnOnce() -> PipelineRunFuture + Send>;
+
+struct PipelineRunJob {
+    task: PipelineRunTask,
+    sender: oneshot::Sender<VoidR>,
+}
+
+static GLOBAL_EXECUTION_POOL: OnceLock<PipelineExecutionPool> = OnceLock::new();
+
+/// bounded worker pool, each worker thread has its own tokio runtime.
+/// the default global pool is created by default config,
+/// [init] the global pool before first use to apply another config.
+pub struct PipelineExecutionPool {
+    sender: Mutex<Option<SyncSender<PipelineRunJob>>>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    backpressure: PipelineBackpressurePolicy,
+}
+
+impl PipelineExecutionPool {
+    pub fn new(config: PipelineExecutionPoolConfig) -> StdR<Self> {
+        if config.workers == 0 {
+            return PipelineKernelErrorCode::ExecutionPoolCreate
+                .msg("Worker count of pipeline execution pool must be greater than 0.");
+        }
+
+        let (sender, receiver) = sync_channel(config.queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut workers = vec![];
+        for index in 0..config.workers {
+            let receiver = receiver.clone();
+            let runtime = Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .or_else(|e| {
+                    PipelineKernelErrorCode::ExecutionPoolCreate.msg(format!(
+                        "Failed to create runtime for pipeline worker, caused by {}.",
+                        e
+                    ))
+                })?;
+            let worker = thread::Builder::new()
+                .name(format!("pipeline-worker-{}", index))
+                .spawn(move || Self::work(runtime, receiver))
+                .or_else(|e| {
+                    PipelineKernelErrorCode::ExecutionPoolCreate
+                        .msg(format!("Failed to spawn pipeline worker, caused by {}.", e))
+                })?;
+            workers.push(worker);
+        }
+
+        Ok(PipelineExecutionPool {
+            sender: Mutex::new(Some(sender)),
+            workers: Mutex::new(workers),
+            backpressure: config.backpressure,
+        })
+    }
+
+    /// initialize the global pool, raise error when it is initialized already.
+    pub fn init(config: PipelineExecutionPoolConfig) -> VoidR {
+        let pool = Self::new(config)?;
+        GLOBAL_EXECUTION_POOL.set(pool).or_else(|_| {
+            PipelineKernelErrorCode::ExecutionPoolAlreadyInitialized
+                .msg("Pipeline execution pool is initialized already.")
+        })
+    }
+
+    pub fn global() -> StdR<&'static PipelineExecutionPool> {
+        if let Some(pool) = GLOBAL_EXECUTION_POOL.get() {
+            return Ok(pool);
+        }
+        let pool = Self::new(PipelineExecutionPoolConfig::default())?;
+        Ok(GLOBAL_EXECUTION_POOL.get_or_init(|| pool))
+    }
+
+    /// worker takes job from queue until the queue is closed and drained
+    fn work(runtime: Runtime, receiver: Arc<Mutex<Receiver<PipelineRunJob>>>) {
+        loop {
+            let job = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                // another worker panicked when holding the lock
+                Err(_) => return,
+            };
+            let job = match job {
+                Ok(job) => job,
+                // queue is closed and drained
+                Err(_) => return,
+            };
+
+            let task = job.task;
+            let result = catch_unwind(AssertUnwindSafe(|| runtime.block_on(task())))
+                .unwrap_or_else(|_| {
+                    PipelineKernelErrorCode::PipelineRunAborted
+                        .msg("Pipeline run aborted, caused by panic.")
+                });
+            // handle is dropped, nobody cares the outcome,
+            // failures of executions are written to monitor logs and dead letters already
+            let _ = job.sender.send(result);
+        }
+    }
+
+    /// submit task to pool, returns handle of outcome.
+    /// when queue is full, block or reject by backpressure policy.
+    pub fn submit<F, Fut>(&self, task: F) -> StdR<PipelineRunHandle>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = VoidR> + 'static,
+    {
+        // clone the sender, avoid blocking shutdown when waiting for space in queue
+        let job_sender = match self.sender.lock() {
+            Ok(guard) => guard.clone(),
+            Err(e) => {
+                return PipelineKernelErrorCode::ExecutionPoolShutdown.msg(format!(
+                    "Pipeline execution pool is broken, caused by {}.",
+                    e
+                ));
+            }
+        };
+        let job_sender = match job_sender {
+            Some(job_sender) => job_sender,
+            _ => {
+                return PipelineKernelErrorCode::ExecutionPoolShutdown
+                    .msg("Pipeline execution pool is shutdown.");
+            }
+        };
+
+        let (sender, receiver) = oneshot::channel();
+        let job = PipelineRunJob {
+            task: Box::new(move || Box::pin(task()) as PipelineRunFuture),
+            sender,
+        };
+        match self.backpressure {
+            PipelineBackpressurePolicy::Block => job_sender.send(job).or_else(|_| {
+                PipelineKernelErrorCode::ExecutionPoolShutdown
+                    .msg("Pipeline execution pool is shutdown.")
+            })?,
+            PipelineBackpressurePolicy::Reject => match job_sender.try_send(job) {
+                Ok(_) => {}
+                Err(TrySendError::Full(_)) => {
+                    return PipelineKernelErrorCode::ExecutionPoolQueueFull
+                        .msg("Queue of pipeline execution pool is full.");
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    return PipelineKernelErrorCode::ExecutionPoolShutdown
+                        .msg("Pipeline execution pool is shutdown.");
+                }
+            },
+        }
+
+        Ok(PipelineRunHandle::new(receiver))
+    }
+
+    /// stop accepting new submissions, wait for queued and in-flight runs completed.
+    /// returns error when any worker panicked, after all workers stopped.
+    pub fn shutdown(&self) -> VoidR {
+        match self.sender.lock() {
+            Ok(mut guard) => {
+                guard.take();
+            }
+            Err(e) => {
+                return PipelineKernelErrorCode::ExecutionPoolShutdown.msg(format!(
+                    "Pipeline execution pool is broken, caused by {}.",
+                    e
+                ));
+            }
+        }
+
+        let workers = match self.workers.lock() {
+            Ok(mut guard) => guard.drain(..).collect::<Vec<JoinHandle<()>>>(),
+            Err(e) => {
+                return PipelineKernelErrorCode::ExecutionPoolShutdown.msg(format!(
+                    "Pipeline execution pool is broken, caused by {}.",
+                    e
+                ));
+            }
+        };
+        let panicked = workers
+            .into_iter()
+            .map(|worker| worker.join())
+            .filter(|joined| joined.is_err())
+            .count();
+        if panicked != 0 {
+            return PipelineKernelErrorCode::ExecutionPoolShutdown.msg(format!(
+                "{} pipeline worker(s) panicked before shutdown.",
+                panicked
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PipelineBackpressurePolicy, PipelineExecutionPool, PipelineExecutionPoolConfig};
+    use elf_base::ErrorCode;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_pool() {
+        let pool = PipelineExecutionPool::new(PipelineExecutionPoolConfig {
+            workers: 1,
+            queue_size: 1,
+            backpressure: PipelineBackpressurePolicy::Reject,
+        })
+        .unwrap();
+
+        // hold the only worker
+        let (release, wait_release) = channel::<()>();
+        let first = pool
+            .submit(move || async move {
+                wait_release.recv().unwrap();
+                Ok(())
+            })
+            .unwrap();
+        // wait until the first one taken by worker, then fill the queue
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut second = None;
+        while second.is_none() {
+            let counter = counter.clone();
+            second = pool
+                .submit(move || async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+                .ok();
+        }
+        let rejected = pool.submit(|| async { Ok(()) });
+        assert!(rejected.is_err());
+
+        release.send(()).unwrap();
+        // queued and in-flight are drained
+        pool.shutdown().unwrap();
+        assert!(first.wait().is_ok());
+        assert!(second.unwrap().wait().is_ok());
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(pool.submit(|| async { Ok(()) }).is_err());
+
+        // error and panic are returned by handle
+        let pool = PipelineExecutionPool::new(PipelineExecutionPoolConfig::default()).unwrap();
+        let failed = pool
+            .submit(|| async { crate::PipelineKernelErrorCode::PipelineRunAborted.msg("failed") })
+            .unwrap();
+        let panicked = pool.submit(|| async { panic!("panicked") }).unwrap();
+        assert!(failed.wait().is_err());
+        assert!(panicked.wait().is_err());
+        pool.shutdown().unwrap();
+    }
+}
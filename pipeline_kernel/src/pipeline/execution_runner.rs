@@ -1,21 +1,494 @@
-use crate::{PipelineCompilationProvider, PipelineExecutable, PipelineExecution};
-use elf_base::StdR;
-use elf_runtime_model_kernel::PipelineService;
+use crate::{
+	CompiledPipeline, PipelineCompilationProvider, PipelineExecutable, PipelineExecution,
+	PipelineExecutionDeadline, PipelineKernelErrorCode,
+};
+use elf_base::{ErrorCode, StdErr, StdR};
+use elf_runtime_model_kernel::{ArcPipelineFailurePolicy, PipelineService};
+use std::future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub struct PipelineExecutionRunner;
 
 impl PipelineExecutionRunner {
-    pub async fn run(execution: PipelineExecution) -> StdR<Option<Vec<PipelineExecution>>> {
-        let compiled_pipeline = PipelineService::compilation()?
-            .compile(execution.topic_schema, execution.pipeline_schema)?;
+    /// the earlier one of pipeline timeout and deadline of trace,
+    /// the error is raised when it is exceeded. none when neither is defined.
+    fn deadline_of(
+        execution: &PipelineExecution,
+        deadline: Option<Instant>,
+    ) -> Option<PipelineExecutionDeadline> {
+        let pipeline_id = execution.pipeline_schema.pipeline_id();
+        let pipeline_deadline =
+            execution
+                .pipeline_schema
+                .timeout()
+                .map(|timeout| PipelineExecutionDeadline {
+                    at: Instant::now() + timeout,
+                    code: PipelineKernelErrorCode::PipelineTimeout,
+                    message: format!(
+                        "Pipeline[pipeline_id={}, trace_id={}] timeout, exceeded {}ms.",
+                        pipeline_id,
+                        execution.trace_id,
+                        timeout.as_millis()
+                    ),
+                });
+        let trace_deadline = deadline.map(|deadline| PipelineExecutionDeadline {
+            at: deadline,
+            code: PipelineKernelErrorCode::TraceTimeout,
+            message: format!(
+                "Time budget of trace[{}] exceeded, at pipeline[{}].",
+                execution.trace_id, pipeline_id
+            ),
+        });
+        match (pipeline_deadline, trace_deadline) {
+            (Some(pipeline_deadline), Some(trace_deadline)) => {
+                if pipeline_deadline.at <= trace_deadline.at {
+                    Some(pipeline_deadline)
+                } else {
+                    Some(trace_deadline)
+                }
+            }
+            (pipeline_deadline, trace_deadline) => pipeline_deadline.or(trace_deadline),
+        }
+    }
+
+    /// compiled pipeline is not cached when simulating,
+    /// since the simulated pipeline might be changed without version increased.
+    async fn execute(
+        execution: PipelineExecution,
+        deadline: Option<Arc<PipelineExecutionDeadline>>,
+    ) -> StdR<Option<Vec<PipelineExecution>>> {
+        let compiled_pipeline = if execution.execution_environment.simulation.is_some() {
+            Arc::new(CompiledPipeline::compile(
+                execution.topic_schema,
+                execution.pipeline_schema,
+            )?)
+        } else {
+            PipelineService::compilation()?
+                .compile(execution.topic_schema, execution.pipeline_schema)?
+        };
 
         compiled_pipeline
-            .execute(PipelineExecutable::new(
-                execution.topic_trigger,
-                execution.principal,
-                execution.trace_id,
-                execution.execution_log_monitor,
-            ))
+            .execute(
+                PipelineExecutable::new(
+                    execution.topic_trigger,
+                    execution.principal,
+                    execution.trace_id,
+                    execution.execution_log_monitor,
+                    execution.execution_environment,
+                )
+                .with_deadline(deadline),
+                execution.monitor_log_id,
+                execution.retry_of,
+            )
             .await
     }
+
+    /// run within the time budget, and interrupted when it is exceeded or run is cancelled.
+    /// awaiting execution is interrupted here, and logged as timeout or error since its own log is not written.
+    /// synchronous store calls cannot be interrupted, the deadline is checked by executable
+    /// before each unit and action instead, and the execution is logged by itself.
+    /// writes done before interrupted are kept unless unit of work is used.
+    pub async fn run(
+        execution: PipelineExecution,
+        deadline: Option<Instant>,
+    ) -> StdR<Option<Vec<PipelineExecution>>> {
+        let log_monitor = execution.execution_log_monitor.clone();
+        let cancellation = execution.execution_environment.cancellation.clone();
+        let deadline = Self::deadline_of(&execution, deadline).map(Arc::new);
+        let timeout = async {
+            match &deadline {
+                Some(deadline) => {
+                    tokio::time::sleep_until(deadline.at.into()).await;
+                    deadline.error()
+                }
+                _ => future::pending::<StdErr>().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => {
+                let error = PipelineKernelErrorCode::PipelineRunCancelled.err_with_msg(format!(
+                    "Pipeline[pipeline_id={}, trace_id={}] cancelled.",
+                    execution.pipeline_schema.pipeline_id(),
+                    execution.trace_id
+                ));
+                log_monitor.log_error(&execution, &error);
+                Err(error)
+            }
+            error = timeout => {
+                log_monitor.log_timeout(&execution, &error);
+                Err(error)
+            }
+            result = Self::execute(execution.clone(), deadline.clone()) => result,
+        }
+    }
+
+    /// no more retry when run is cancelled or time budget of trace exceeded
+    fn is_interrupted(execution: &PipelineExecution, deadline: Option<Instant>) -> bool {
+        execution.execution_environment.cancellation.is_cancelled()
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// retries are ignored when unit of work is not used,
+    /// since writes of failed attempt are kept and applied again by retry,
+    /// e.g. values of accumulate mapping are counted twice.
+    fn retries_of(execution: &PipelineExecution, policy: &ArcPipelineFailurePolicy) -> u32 {
+        if execution.execution_environment.unit_of_work {
+            policy.retries
+        } else {
+            0
+        }
+    }
+
+    /// run by failure policy of pipeline, retried with backoff,
+    /// and saved as dead letter when all retries failed and policy requires.
+    /// failed attempts are not reported here, monitor log is written by each attempt,
+    /// with its own id, and linked to the first attempt by retry of.
+    /// failure of saving dead letter is appended to the error of execution.
+    /// retried only when unit of work is used, see [Self::retries_of].
+    /// policy is ignored when simulating, and cancelled execution is never dead-lettered.
+    pub async fn run_with_policy(
+        execution: PipelineExecution,
+        deadline: Option<Instant>,
+    ) -> StdR<Option<Vec<PipelineExecution>>> {
+        let environment = execution.execution_environment.clone();
+        let policy = match execution.pipeline_schema.failure_policy() {
+            Some(policy) if environment.simulation.is_none() => policy.clone(),
+            _ => return Self::run(execution, deadline).await,
+        };
+
+        let retries = Self::retries_of(&execution, &policy);
+        let mut attempt = execution.clone();
+        let mut retry = 0;
+        loop {
+            match Self::run(attempt.clone(), deadline).await {
+                Ok(executions) => return Ok(executions),
+                Err(_) if retry < retries && !Self::is_interrupted(&execution, deadline) => {
+                    retry += 1;
+                    attempt = attempt.retried()?;
+                    let backoff = policy.backoff_of(retry);
+                    if backoff != 0 {
+                        tokio::time::sleep(Duration::from_millis(backoff)).await;
+                    }
+                }
+                Err(error) => {
+                    if policy.dead_letter
+                        && environment.dead_letter
+                        && !environment.cancellation.is_cancelled()
+                        && let Err(e) = environment.save_dead_letter(&execution, &error, retry + 1)
+                    {
+                        return PipelineKernelErrorCode::DeadLetterNotSaved.msg(format!(
+                            "Pipeline[pipeline_id={}, trace_id={}] failed, caused by {}, and dead letter not saved, caused by {}.",
+                            execution.pipeline_schema.pipeline_id(),
+                            execution.trace_id,
+                            error,
+                            e
+                        ));
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcTopicDataValue, DataPath, DataVisitor, InMemoryMonitorLogWriter, MonitorLogWriters,
+        PipelineCancellationToken, PipelineExecutable, PipelineExecution,
+        PipelineExecutionEnvironment, PipelineExecutionLogMonitor, PipelineExecutionRunner,
+        PipelineSimulation, TopicDataStore, TopicTrigger,
+    };
+    use bigdecimal::BigDecimal;
+    use elf_auth::Principal;
+    use elf_base::StdR;
+    use elf_model::{
+        Factor, FactorType, MonitorLogStatus, Pipeline, PipelineFailurePolicy, PipelineStage,
+        PipelineTriggerType, PipelineUnit, Topic, TopicData, TopicDataColumnNames, TopicDataId,
+        TopicDataValue, TopicKind, TopicType, WriteToExternalAction,
+    };
+    use elf_runtime_model_kernel::{
+        ArcFactor, ArcPipelineFailurePolicy, IdGen, IdGenerator, PipelineSchema,
+        SnowflakeIdGenerator, TopicDataCriteria, TopicSchema,
+    };
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tokio::runtime::Builder;
+
+    /// every call blocks for the given delay, nothing is stored
+    struct SlowTopicDataStore {
+        delay: Duration,
+    }
+
+    impl SlowTopicDataStore {
+        fn block<R>(&self, r: R) -> StdR<R> {
+            thread::sleep(self.delay);
+            Ok(r)
+        }
+    }
+
+    impl TopicDataStore for SlowTopicDataStore {
+        fn insert(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            self.block(data)
+        }
+        fn insert_or_merge(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<(Option<TopicData>, TopicData)> {
+            self.block((None, data))
+        }
+        fn merge(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<(TopicData, TopicData)> {
+            self.block((data.clone(), data))
+        }
+        fn merge_versioned(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<Option<(TopicData, TopicData)>> {
+            self.block(Some((data.clone(), data)))
+        }
+        fn delete(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            self.block(data)
+        }
+        fn find_by_id(&self, _: &Arc<TopicSchema>, _: &TopicDataId) -> StdR<Option<TopicData>> {
+            self.block(None)
+        }
+        fn find(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Vec<TopicData>> {
+            self.block(vec![])
+        }
+        fn find_one(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Option<TopicData>> {
+            self.block(None)
+        }
+        fn find_values(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: &Arc<ArcFactor>,
+            _: &TopicDataCriteria,
+        ) -> StdR<Vec<TopicDataValue>> {
+            self.block(vec![])
+        }
+        fn count(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<u64> {
+            self.block(0)
+        }
+        fn exists(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<bool> {
+            self.block(false)
+        }
+    }
+
+    fn create_execution(
+        timeout_in_mills: Option<u32>,
+        simulation: Option<Arc<PipelineSimulation>>,
+    ) -> PipelineExecution {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("factor_1"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let mut pipeline = Pipeline::new()
+            .pipeline_id(String::from("p1"))
+            .topic_id(String::from("topic-1"))
+            .name(String::from("p1"))
+            .r#type(PipelineTriggerType::Insert)
+            .stages(vec![PipelineStage::new().units(vec![
+                PipelineUnit::new().r#do(vec![
+                    WriteToExternalAction::init()
+                        .external_writer_id(String::from("writer-1"))
+                        .event_code(String::from("event-1"))
+                        .to_action(),
+                ]),
+            ])])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        pipeline.timeout_in_mills = timeout_in_mills;
+        let data = HashMap::from([(
+            TopicDataColumnNames::Id.column_name().to_string(),
+            TopicDataValue::Str(String::from("1")),
+        )]);
+        let principal = Arc::new(Principal::fake_super_admin());
+        let trace_id = Arc::new(String::from("trace-1"));
+
+        PipelineExecution {
+            topic_schema: Arc::new(TopicSchema::new(topic).unwrap()),
+            pipeline_schema: Arc::new(PipelineSchema::new(pipeline).unwrap()),
+            topic_trigger: TopicTrigger::insert(data).unwrap(),
+            monitor_log_id: Arc::new(String::from("log-1")),
+            retry_of: None,
+            principal: principal.clone(),
+            trace_id: trace_id.clone(),
+            execution_log_monitor: Arc::new(PipelineExecutionLogMonitor {
+                principal: principal.clone(),
+                trace_id,
+                summary: None,
+                writer: None,
+            }),
+            execution_environment: Arc::new(PipelineExecutionEnvironment {
+                principal,
+                simulation,
+                unit_of_work: false,
+                dead_letter: false,
+                cancellation: Arc::new(PipelineCancellationToken::new()),
+                time_budget: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_deadline() {
+        assert!(
+            PipelineExecutionRunner::deadline_of(&create_execution(None, None), None).is_none()
+        );
+
+        let execution = create_execution(Some(100), None);
+        let deadline = PipelineExecutionRunner::deadline_of(&execution, None).unwrap();
+        assert!(deadline.at <= Instant::now() + Duration::from_millis(100));
+        assert_eq!(deadline.error().code, "PLKN-01000");
+
+        // trace deadline is earlier
+        let at = Instant::now() + Duration::from_millis(50);
+        let deadline = PipelineExecutionRunner::deadline_of(&execution, Some(at)).unwrap();
+        assert_eq!(deadline.at, at);
+        assert_eq!(deadline.error().code, "PLKN-01001");
+
+        // trace deadline exceeded already
+        let at = Instant::now() - Duration::from_millis(10);
+        let deadline = PipelineExecutionRunner::deadline_of(&execution, Some(at)).unwrap();
+        assert!(deadline.at <= Instant::now());
+        assert!(PipelineExecutionRunner::is_interrupted(
+            &execution,
+            Some(at)
+        ));
+    }
+
+    #[test]
+    fn test_slow_store_cut_off() {
+        let simulation = Arc::new(PipelineSimulation::with(Arc::new(SlowTopicDataStore {
+            delay: Duration::from_millis(50),
+        })));
+        let logs = Arc::new(InMemoryMonitorLogWriter::new());
+        let mut execution = create_execution(Some(10), Some(simulation));
+        execution.execution_log_monitor = Arc::new(PipelineExecutionLogMonitor {
+            principal: execution.principal.clone(),
+            trace_id: execution.trace_id.clone(),
+            summary: None,
+            writer: Some(logs.clone()),
+        });
+        let deadline = PipelineExecutionRunner::deadline_of(&execution, None).map(Arc::new);
+
+        // synchronous store call cannot be interrupted, it consumes the whole budget
+        let store = execution.execution_environment.topic_data().unwrap();
+        store
+            .find_by_id(&execution.topic_schema, &String::from("1"))
+            .unwrap();
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let error = runtime
+            .block_on(PipelineExecutionRunner::execute(execution, deadline))
+            .err()
+            .unwrap();
+        assert_eq!(error.code, "PLKN-01000");
+
+        // no unit is run after budget exceeded, and logged by pipeline itself
+        let logs = logs.take().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert!(matches!(logs[0].status, Some(MonitorLogStatus::ERROR)));
+        let stages = logs[0].stages.as_ref().unwrap();
+        assert!(stages[0].units.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancelled() {
+        MonitorLogWriters::set(Arc::new(InMemoryMonitorLogWriter::new())).unwrap();
+
+        let execution = create_execution(None, None);
+        execution.execution_environment.cancellation.cancel();
+        assert!(PipelineExecutionRunner::is_interrupted(&execution, None));
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let error = runtime
+            .block_on(PipelineExecutionRunner::run(execution, None))
+            .err()
+            .unwrap();
+        assert_eq!(error.code, "PLKN-01002");
+    }
+
+    #[test]
+    fn test_simulated_sequences() {
+        let simulation = Arc::new(PipelineSimulation::with(Arc::new(SlowTopicDataStore {
+            delay: Duration::ZERO,
+        })));
+        let execution = create_execution(None, Some(simulation.clone()));
+        let executable = PipelineExecutable::new(
+            execution.topic_trigger,
+            execution.principal,
+            execution.trace_id,
+            execution.execution_log_monitor,
+            execution.execution_environment,
+        );
+
+        // counted by scratch store of simulation, global store is not set in tests
+        let path = DataPath::from_str("&nextSeq(order)").unwrap();
+        let value = executable.variables.value_of(&path).unwrap();
+        assert!(
+            matches!(value.deref(), ArcTopicDataValue::Num(n) if n.deref() == &BigDecimal::from(1))
+        );
+        let tenant_id = &executable.principal.tenant_id;
+        assert_eq!(
+            simulation
+                .sequence_store()
+                .next_value(tenant_id, "order")
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_retried() {
+        IdGen::set(Box::new(SnowflakeIdGenerator::new(1).unwrap()) as Box<dyn IdGenerator>)
+            .unwrap();
+
+        let execution = create_execution(None, None);
+        let first = execution.retried().unwrap();
+        let second = first.retried().unwrap();
+        // each attempt has its own monitor log id, linked to the first attempt
+        assert_ne!(first.monitor_log_id, execution.monitor_log_id);
+        assert_ne!(second.monitor_log_id, first.monitor_log_id);
+        assert_eq!(first.retry_of, Some(execution.monitor_log_id.clone()));
+        assert_eq!(second.retry_of, Some(execution.monitor_log_id.clone()));
+    }
+
+    #[test]
+    fn test_retries_ignored_without_unit_of_work() {
+        let mut execution = create_execution(None, None);
+        let policy = ArcPipelineFailurePolicy::new(PipelineFailurePolicy {
+            retries: Some(2),
+            backoff_in_mills: None,
+            dead_letter: None,
+        });
+        assert_eq!(PipelineExecutionRunner::retries_of(&execution, &policy), 0);
+
+        execution.execution_environment = Arc::new(PipelineExecutionEnvironment {
+            principal: execution.principal.clone(),
+            simulation: None,
+            unit_of_work: true,
+            dead_letter: false,
+            cancellation: Arc::new(PipelineCancellationToken::new()),
+            time_budget: None,
+        });
+        assert_eq!(PipelineExecutionRunner::retries_of(&execution, &policy), 2);
+    }
 }
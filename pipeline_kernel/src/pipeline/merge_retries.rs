@@ -0,0 +1,33 @@
+use crate::PipelineKernelErrorCode;
+use elf_base::{ErrorCode, VoidR};
+use std::sync::OnceLock;
+
+/// retries of merge when version of row conflicted
+pub struct PipelineMergeRetries {
+    /// max retries after the first try, 0 means no retry
+    pub max_retries: u32,
+}
+
+impl Default for PipelineMergeRetries {
+    /// max retries is 3
+    fn default() -> Self {
+        PipelineMergeRetries { max_retries: 3 }
+    }
+}
+
+static GLOBAL_MERGE_RETRIES: OnceLock<PipelineMergeRetries> = OnceLock::new();
+
+/// the default global retries is created by default,
+/// [init] the global retries before first use to apply another one.
+impl PipelineMergeRetries {
+    pub fn init(retries: PipelineMergeRetries) -> VoidR {
+        GLOBAL_MERGE_RETRIES.set(retries).or_else(|_| {
+            PipelineKernelErrorCode::MergeRetriesAlreadyInitialized
+                .msg("Pipeline merge retries is initialized already.")
+        })
+    }
+
+    pub fn global() -> &'static PipelineMergeRetries {
+        GLOBAL_MERGE_RETRIES.get_or_init(PipelineMergeRetries::default)
+    }
+}
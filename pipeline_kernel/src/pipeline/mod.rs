@@ -1,21 +1,43 @@
+mod cancellation_token;
+mod cascade_limits;
 mod compile_service;
 mod compiled;
+mod compiled_action;
+mod compiled_criteria;
 mod compiled_in_memory_parameter;
+mod dead_letter_replayer;
 mod entrypoint;
 mod execution;
+mod execution_environment;
 mod execution_log_monitor;
+mod execution_pool;
 mod execution_runner;
+mod merge_retries;
 mod run_context;
+mod run_handle;
+mod run_summary;
 mod runner;
 mod trigger;
+mod unit_of_work;
 
+pub use cancellation_token::*;
+pub use cascade_limits::*;
 pub use compile_service::*;
 pub use compiled::*;
+pub use compiled_action::*;
+pub use compiled_criteria::*;
 pub use compiled_in_memory_parameter::*;
+pub use dead_letter_replayer::*;
 pub use entrypoint::*;
 pub use execution::*;
+pub use execution_environment::*;
 pub use execution_log_monitor::*;
+pub use execution_pool::*;
 pub use execution_runner::*;
+pub use merge_retries::*;
 pub use run_context::*;
+pub use run_handle::*;
+pub use run_summary::*;
 pub use runner::*;
 pub use trigger::*;
+pub use unit_of_work::*;
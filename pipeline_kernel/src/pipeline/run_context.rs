@@ -1,14 +1,42 @@
-use crate::{PipelineExecution, PipelineTrigger, TopicTrigger};
+use crate::{
+	PipelineCancellationToken, PipelineCascadeLimits, PipelineExecution, PipelineKernelErrorCode,
+	PipelineTrigger, TopicTrigger,
+};
+use elf_base::{ErrorCode, StdErr, StdR, VoidR};
+use elf_model::{PipelineId, PipelineMonitorLogId, TopicDataId, TopicId};
 use elf_runtime_model_kernel::PipelineSchema;
 use std::collections::VecDeque;
+use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Instant;
+
+/// (pipeline id, topic id, data id)
+type PipelineCascadeKey = (Arc<PipelineId>, Arc<TopicId>, Arc<TopicDataId>);
+
+struct PipelineRunContextItem {
+    execution: PipelineExecution,
+    depth: u32,
+    /// keys of ancestors and itself
+    path: Arc<Vec<PipelineCascadeKey>>,
+}
 
 /// It includes at least one pipeline execution,
 /// and based on the execution results of the already included pipelines,
 /// the number of pipelines included in the entire execution context may increase
 /// because some pipelines can trigger other pipelines.
+///
+/// cascading is guarded by [PipelineCascadeLimits], and the cycle is detected by the cascade path,
+/// which is the visited (pipeline, topic, data id) from the trigger data to current execution.
 pub struct PipelineRunContext {
-    queue: VecDeque<PipelineExecution>,
+    queue: VecDeque<PipelineRunContextItem>,
+    limits: &'static PipelineCascadeLimits,
+    /// count of executions ever queued
+    executions: u32,
+    /// depth and cascade path of the execution returned by last [next]
+    current: Option<(u32, Arc<Vec<PipelineCascadeKey>>)>,
+    cancellation: Arc<PipelineCancellationToken>,
+    /// time budget of trace is counted from the context created
+    deadline: Option<Instant>,
 }
 
 impl PipelineRunContext {
@@ -16,25 +44,93 @@ impl PipelineRunContext {
         pipeline_trigger: &PipelineTrigger,
         topic_trigger: Arc<TopicTrigger>,
         pipelines: Vec<Arc<PipelineSchema>>,
-    ) -> Self {
+    ) -> StdR<Self> {
+        let mut executions = vec![];
+        for pipeline_schema in pipelines {
+            executions.push(PipelineExecution {
+                topic_schema: pipeline_trigger.topic_schema.clone(),
+                topic_trigger: topic_trigger.clone(),
+                pipeline_schema,
+                monitor_log_id: PipelineExecution::next_monitor_log_id()?,
+                retry_of: None,
+                // env
+                principal: pipeline_trigger.principal.clone(),
+                trace_id: pipeline_trigger.trace_id.clone(),
+                execution_log_monitor: pipeline_trigger.execution_log_monitor.clone(),
+                execution_environment: pipeline_trigger.execution_environment.clone(),
+            });
+        }
+
+        Ok(Self::with(executions, PipelineCascadeLimits::global()))
+    }
+
+    /// id of monitor log of the first queued execution, which is triggered by trigger data directly.
+    /// returns none when no execution queued.
+    pub fn first_monitor_log_id(&self) -> Option<Arc<PipelineMonitorLogId>> {
+        self.queue
+            .front()
+            .map(|item| item.execution.monitor_log_id.clone())
+    }
+
+    fn with(executions: Vec<PipelineExecution>, limits: &'static PipelineCascadeLimits) -> Self {
+        let queue: VecDeque<PipelineRunContextItem> = executions
+            .into_iter()
+            .map(|execution| {
+                let key = Self::key_of(&execution);
+                PipelineRunContextItem {
+                    execution,
+                    depth: 0,
+                    path: Arc::new(vec![key]),
+                }
+            })
+            .collect();
+
+        // executions of one context share the same environment
+        let (cancellation, deadline) = match queue.front() {
+            Some(item) => {
+                let environment = &item.execution.execution_environment;
+                (
+                    environment.cancellation.clone(),
+                    environment
+                        .time_budget
+                        .map(|time_budget| Instant::now() + time_budget),
+                )
+            }
+            _ => (Arc::new(PipelineCancellationToken::new()), None),
+        };
+
         PipelineRunContext {
-            queue: pipelines
-                .into_iter()
-                .map(|pipeline_schema| PipelineExecution {
-                    topic_schema: pipeline_trigger.topic_schema.clone(),
-                    topic_trigger: topic_trigger.clone(),
-                    pipeline_schema,
-                    // env
-                    principal: pipeline_trigger.principal.clone(),
-                    trace_id: pipeline_trigger.trace_id.clone(),
-                    execution_log_monitor: pipeline_trigger.execution_log_monitor.clone(),
-                })
-                .collect(),
+            executions: queue.len() as u32,
+            queue,
+            limits,
+            current: None,
+            cancellation,
+            deadline,
         }
     }
 
+    fn key_of(execution: &PipelineExecution) -> PipelineCascadeKey {
+        (
+            execution.pipeline_schema.pipeline_id().clone(),
+            execution.topic_schema.topic_id().clone(),
+            execution.topic_trigger.internal_data_id.clone(),
+        )
+    }
+
+    pub fn cancellation_token(&self) -> Arc<PipelineCancellationToken> {
+        self.cancellation.clone()
+    }
+
+    /// none when no time budget of trace
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     pub fn next(&mut self) -> Option<PipelineExecution> {
-        self.queue.pop_front()
+        self.queue.pop_front().map(|item| {
+            self.current = Some((item.depth, item.path));
+            item.execution
+        })
     }
 
     pub fn has_more(&self) -> bool {
@@ -45,9 +141,233 @@ impl PipelineRunContext {
         self.queue.len()
     }
 
-    pub fn append(&mut self, executions: Option<Vec<PipelineExecution>>) {
-        if let Some(executions) = executions {
-            executions.into_iter().for_each(|e| self.queue.push_back(e))
+    /// check the given execution can be queued or not, as the downstream of current execution
+    fn check(
+        &self,
+        key: &PipelineCascadeKey,
+        depth: u32,
+        path: &[PipelineCascadeKey],
+    ) -> Option<StdErr> {
+        if path.contains(key) {
+            let path = path
+                .iter()
+                .map(|(pipeline_id, topic_id, data_id)| {
+                    format!(
+                        "[pipeline={}, topic={}, data={}]",
+                        pipeline_id, topic_id, data_id
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            Some(PipelineKernelErrorCode::CascadeCycleDetected.err_with_msg(format!(
+                "Pipeline cascade cycle detected, pipeline[{}] on topic[{}] of data[{}] is visited already, path is {}.",
+                key.0, key.1, key.2, path
+            )))
+        } else if depth > self.limits.max_depth {
+            Some(PipelineKernelErrorCode::CascadeDepthExceeded.err_with_msg(format!(
+                "Pipeline cascade depth exceeded the max depth[{}], at pipeline[{}] on topic[{}] of data[{}].",
+                self.limits.max_depth, key.0, key.1, key.2
+            )))
+        } else if self.executions >= self.limits.max_executions {
+            Some(PipelineKernelErrorCode::CascadeExecutionsExceeded.err_with_msg(format!(
+                "Pipeline cascade executions exceeded the max count[{}], at pipeline[{}] on topic[{}] of data[{}].",
+                self.limits.max_executions, key.0, key.1, key.2
+            )))
+        } else {
+            None
         }
     }
+
+    /// append the downstream executions of current execution.
+    /// raise error when any of them breaks the limits or leads a cycle,
+    /// and the error is logged into monitor log of the breaking one.
+    pub fn append(&mut self, executions: Option<Vec<PipelineExecution>>) -> VoidR {
+        let executions = match executions {
+            Some(executions) => executions,
+            _ => return Ok(()),
+        };
+        let (depth, path) = match &self.current {
+            Some((depth, path)) => (depth + 1, path.clone()),
+            _ => (0, Arc::new(vec![])),
+        };
+
+        for execution in executions {
+            let key = Self::key_of(&execution);
+            if let Some(error) = self.check(&key, depth, path.deref()) {
+                execution
+                    .execution_log_monitor
+                    .log_error(&execution, &error);
+                return Err(error);
+            }
+
+            let mut execution_path = path.deref().clone();
+            execution_path.push(key);
+            self.executions += 1;
+            self.queue.push_back(PipelineRunContextItem {
+                execution,
+                depth,
+                path: Arc::new(execution_path),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        InMemoryMonitorLogWriter, MonitorLogWriters, PipelineCancellationToken,
+        PipelineCascadeLimits, PipelineExecution, PipelineExecutionEnvironment,
+        PipelineExecutionLogMonitor, PipelineRunContext, TopicTrigger,
+    };
+    use elf_auth::Principal;
+    use elf_model::{
+        Factor, FactorType, Pipeline, PipelineStage, PipelineTriggerType, PipelineUnit, Topic,
+        TopicDataColumnNames, TopicDataValue, TopicKind, TopicType, WriteToExternalAction,
+    };
+    use elf_runtime_model_kernel::{PipelineSchema, TopicSchema};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    static LIMITS: PipelineCascadeLimits = PipelineCascadeLimits {
+        max_depth: 2,
+        max_executions: 5,
+    };
+
+    fn create_execution(pipeline_id: &str, data_id: &str) -> PipelineExecution {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("factor_1"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let pipeline = Pipeline::new()
+            .pipeline_id(String::from(pipeline_id))
+            .topic_id(String::from("topic-1"))
+            .name(String::from(pipeline_id))
+            .r#type(PipelineTriggerType::Insert)
+            .stages(vec![PipelineStage::new().units(vec![
+                PipelineUnit::new().r#do(vec![
+                    WriteToExternalAction::init()
+                        .external_writer_id(String::from("writer-1"))
+                        .event_code(String::from("event-1"))
+                        .to_action(),
+                ]),
+            ])])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        let data = HashMap::from([(
+            TopicDataColumnNames::Id.column_name().to_string(),
+            TopicDataValue::Str(data_id.to_string()),
+        )]);
+        let principal = Arc::new(Principal::fake_super_admin());
+        let trace_id = Arc::new(String::from("trace-1"));
+
+        PipelineExecution {
+            topic_schema: Arc::new(TopicSchema::new(topic).unwrap()),
+            pipeline_schema: Arc::new(PipelineSchema::new(pipeline).unwrap()),
+            topic_trigger: TopicTrigger::insert(data).unwrap(),
+            monitor_log_id: Arc::new(format!("{}-{}", pipeline_id, data_id)),
+            retry_of: None,
+            principal: principal.clone(),
+            trace_id: trace_id.clone(),
+            execution_log_monitor: Arc::new(PipelineExecutionLogMonitor {
+                principal: principal.clone(),
+                trace_id,
+                summary: None,
+                writer: None,
+            }),
+            execution_environment: Arc::new(PipelineExecutionEnvironment {
+                principal,
+                simulation: None,
+                unit_of_work: false,
+                dead_letter: false,
+                cancellation: Arc::new(PipelineCancellationToken::new()),
+                time_budget: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        MonitorLogWriters::set(Arc::new(InMemoryMonitorLogWriter::new())).unwrap();
+
+        let mut context = PipelineRunContext::with(vec![create_execution("p1", "1")], &LIMITS);
+        context.next().unwrap();
+        assert!(
+            context
+                .append(Some(vec![create_execution("p2", "2")]))
+                .is_ok()
+        );
+        context.next().unwrap();
+        // p1 on data 1 is ancestor
+        assert!(
+            context
+                .append(Some(vec![create_execution("p1", "1")]))
+                .is_err()
+        );
+
+        // same pipeline on same data, but on different branches is not a cycle
+        let mut context = PipelineRunContext::with(
+            vec![create_execution("p1", "1"), create_execution("p2", "1")],
+            &LIMITS,
+        );
+        context.next().unwrap();
+        assert!(
+            context
+                .append(Some(vec![create_execution("p3", "3")]))
+                .is_ok()
+        );
+        context.next().unwrap();
+        assert!(
+            context
+                .append(Some(vec![create_execution("p3", "3")]))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        MonitorLogWriters::set(Arc::new(InMemoryMonitorLogWriter::new())).unwrap();
+
+        let mut context = PipelineRunContext::with(vec![create_execution("p1", "1")], &LIMITS);
+        for data_id in ["2", "3"] {
+            context.next().unwrap();
+            assert!(
+                context
+                    .append(Some(vec![create_execution("p1", data_id)]))
+                    .is_ok()
+            );
+        }
+        // depth 3 exceeded
+        context.next().unwrap();
+        assert!(
+            context
+                .append(Some(vec![create_execution("p1", "4")]))
+                .is_err()
+        );
+
+        let mut context = PipelineRunContext::with(vec![create_execution("p1", "1")], &LIMITS);
+        context.next().unwrap();
+        let executions = ["2", "3", "4", "5"]
+            .iter()
+            .map(|data_id| create_execution("p2", data_id))
+            .collect();
+        assert!(context.append(Some(executions)).is_ok());
+        // 6th exceeded
+        context.next().unwrap();
+        assert!(
+            context
+                .append(Some(vec![create_execution("p3", "6")]))
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,53 @@
+use crate::{PipelineCancellationToken, PipelineKernelErrorCode};
+use elf_base::{ErrorCode, VoidR};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// handle of pipeline run context submitted to execution pool,
+/// to get the final outcome of the whole run context, or cancel it.
+/// dropping the handle does not cancel the run.
+pub struct PipelineRunHandle {
+    receiver: oneshot::Receiver<VoidR>,
+    cancellation: Arc<PipelineCancellationToken>,
+}
+
+impl PipelineRunHandle {
+    pub fn new(receiver: oneshot::Receiver<VoidR>) -> Self {
+        PipelineRunHandle {
+            receiver,
+            cancellation: Arc::new(PipelineCancellationToken::new()),
+        }
+    }
+
+    /// cancelled by the given token
+    pub fn cancelled_by(mut self, cancellation: Arc<PipelineCancellationToken>) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// token is shared by all executions of the run, and maybe other runs of same trigger
+    pub fn cancellation_token(&self) -> &Arc<PipelineCancellationToken> {
+        &self.cancellation
+    }
+
+    /// interrupt the execution in progress, and the remaining ones are not run
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// block current thread until the run completed,
+    /// do not call it in async context, use [outcome] instead.
+    pub fn wait(self) -> VoidR {
+        self.receiver.blocking_recv().unwrap_or_else(|_| {
+            PipelineKernelErrorCode::PipelineRunAborted
+                .msg("Pipeline run aborted, outcome not received.")
+        })
+    }
+
+    pub async fn outcome(self) -> VoidR {
+        self.receiver.await.unwrap_or_else(|_| {
+            PipelineKernelErrorCode::PipelineRunAborted
+                .msg("Pipeline run aborted, outcome not received.")
+        })
+    }
+}
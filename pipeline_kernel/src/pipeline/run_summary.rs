@@ -0,0 +1,117 @@
+use elf_base::StdErr;
+use elf_model::{PipelineId, PipelineTriggerResult, PipelineTriggerType, TopicId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// counts of rows written by pipelines, on one topic
+#[derive(Default, Debug)]
+pub struct PipelineTopicTouched {
+    pub inserted: u32,
+    pub updated: u32,
+    pub deleted: u32,
+}
+
+/// outcome of all pipelines of one trace, including the cascaded ones.
+#[derive(Default, Debug)]
+pub struct PipelineRunSummary {
+    /// pipelines run, in run order
+    pub run: Vec<Arc<PipelineId>>,
+    /// pipelines skipped since prerequisite not met
+    pub skipped: Vec<Arc<PipelineId>>,
+    /// pipelines failed, the run is stopped at the first failure
+    pub failed: Vec<(Arc<PipelineId>, StdErr)>,
+    /// rows touched by pipelines, key is topic id
+    pub touched: HashMap<Arc<TopicId>, PipelineTopicTouched>,
+}
+
+/// result of trigger, with the summary of all pipelines run by trigger
+pub struct PipelineTriggerOutcome {
+    pub result: PipelineTriggerResult,
+    pub summary: PipelineRunSummary,
+}
+
+/// collect summary during the run, shared by all executions of one trace.
+/// collecting never breaks the run, poisoned lock is recovered.
+#[derive(Default)]
+pub struct PipelineRunSummaryCollector {
+    summary: RwLock<PipelineRunSummary>,
+}
+
+impl PipelineRunSummaryCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn collect<F>(&self, f: F)
+    where
+        F: FnOnce(&mut PipelineRunSummary),
+    {
+        let mut guard = self.summary.write().unwrap_or_else(|e| e.into_inner());
+        f(&mut guard);
+    }
+
+    pub fn run(&self, pipeline_id: &Arc<PipelineId>) {
+        self.collect(|summary| summary.run.push(pipeline_id.clone()));
+    }
+
+    pub fn skipped(&self, pipeline_id: &Arc<PipelineId>) {
+        self.collect(|summary| summary.skipped.push(pipeline_id.clone()));
+    }
+
+    pub fn failed(&self, pipeline_id: &Arc<PipelineId>, error: StdErr) {
+        self.collect(|summary| summary.failed.push((pipeline_id.clone(), error)));
+    }
+
+    /// count the row by trigger type, insert is inserted, merge is updated and delete is deleted.
+    pub fn touched(&self, topic_id: &Arc<TopicId>, trigger_type: &PipelineTriggerType) {
+        self.collect(|summary| {
+            let touched = summary.touched.entry(topic_id.clone()).or_default();
+            match trigger_type {
+                PipelineTriggerType::Insert => touched.inserted += 1,
+                PipelineTriggerType::Merge | PipelineTriggerType::InsertOrMerge => {
+                    touched.updated += 1
+                }
+                PipelineTriggerType::Delete => touched.deleted += 1,
+            }
+        });
+    }
+
+    /// take the collected summary, collector is reset
+    pub fn take(&self) -> PipelineRunSummary {
+        let mut guard = self.summary.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PipelineKernelErrorCode, PipelineRunSummaryCollector};
+    use elf_base::ErrorCode;
+    use elf_model::PipelineTriggerType;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_collect() {
+        let collector = PipelineRunSummaryCollector::new();
+        let topic_id = Arc::new(String::from("topic-1"));
+        collector.run(&Arc::new(String::from("p1")));
+        collector.skipped(&Arc::new(String::from("p2")));
+        collector.failed(
+            &Arc::new(String::from("p3")),
+            PipelineKernelErrorCode::CascadeCycleDetected.err_with_msg("cycle"),
+        );
+        collector.touched(&topic_id, &PipelineTriggerType::Insert);
+        collector.touched(&topic_id, &PipelineTriggerType::Insert);
+        collector.touched(&topic_id, &PipelineTriggerType::Merge);
+
+        let summary = collector.take();
+        assert_eq!(summary.run.len(), 1);
+        assert_eq!(summary.skipped[0].as_str(), "p2");
+        assert_eq!(summary.failed[0].1.code, "PLKN-00501");
+        let touched = summary.touched.get(&topic_id).unwrap();
+        assert_eq!(touched.inserted, 2);
+        assert_eq!(touched.updated, 1);
+        assert_eq!(touched.deleted, 0);
+        assert!(collector.take().run.is_empty());
+    }
+}
@@ -1,21 +1,33 @@
-use crate::{PipelineExecutionRunner, PipelineRunContext};
-use elf_base::VoidR;
-use tokio;
+use crate::{
+	PipelineExecutionPool, PipelineExecutionRunner, PipelineRunContext, PipelineRunHandle,
+};
+use elf_base::{StdR, VoidR};
 
 pub struct PipelineRunner;
 
 impl PipelineRunner {
-    pub fn execute(context: PipelineRunContext) -> VoidR {
-        // TODO currently still on this thread, any needs to create a new thread?
-        tokio::spawn(Self::execute_async(context));
-
-        Ok(())
+    /// submit the run context to global execution pool, returns handle of the final outcome.
+    pub fn execute(context: PipelineRunContext) -> StdR<PipelineRunHandle> {
+        let cancellation = context.cancellation_token();
+        Ok(PipelineExecutionPool::global()?
+            .submit(move || Self::execute_async(context))?
+            .cancelled_by(cancellation))
     }
 
+    /// run stops at the first failure, timeout or cancellation.
+    /// the failure is recorded into summary when it is required, and returns ok.
     pub async fn execute_async(mut context: PipelineRunContext) -> VoidR {
+        let deadline = context.deadline();
         while context.has_more() {
             if let Some(execution) = context.next() {
-                context.append(PipelineExecutionRunner::run(execution).await?);
+                let pipeline_id = execution.pipeline_schema.pipeline_id().clone();
+                let log_monitor = execution.execution_log_monitor.clone();
+                let result = PipelineExecutionRunner::run_with_policy(execution, deadline)
+                    .await
+                    .and_then(|executions| context.append(executions));
+                if let Err(error) = result {
+                    return log_monitor.failed(&pipeline_id, error);
+                }
             }
         }
 
@@ -1,17 +1,93 @@
 use crate::{
-    PipelineExecutionLogMonitor, PipelineKernelErrorCode, PipelineRunContext, PipelineRunner,
-    TopicTrigger,
+	PipelineExecutionEnvironment, PipelineExecutionLogMonitor, PipelineKernelErrorCode,
+	PipelineRunContext, PipelineRunSummaryCollector, PipelineRunner, PipelineSimulation,
+	PipelineSimulationResult, PipelineTriggerOutcome, TopicTrigger,
 };
 use elf_auth::Principal;
 use elf_base::{ErrorCode, StdR, VoidR};
-use elf_model::{PipelineId, PipelineTriggerTraceId, PipelineTriggerType, TopicData, TopicDataId};
+use elf_model::{
+	Pipeline, PipelineId, PipelineMonitorLogId, PipelineTriggerResult, PipelineTriggerTraceId,
+	PipelineTriggerType, TopicData, TopicDataId,
+};
 use elf_runtime_model_kernel::{
-    PipelineSchema, PipelineSchemaProvider, PipelineService, TopicDataProvider, TopicSchema,
-    TopicService,
+	PipelineSchema, PipelineSchemaProvider, PipelineService, TopicSchema,
 };
 use std::ops::Deref;
 use std::sync::Arc;
 
+/// count of rows which are saved in one round of batch trigger
+const BATCH_CHUNK_SIZE: usize = 100;
+
+/// rows of batch trigger, each row with its trace id
+pub type PipelineTriggerBatchRows = Vec<(Arc<PipelineTriggerTraceId>, TopicData)>;
+
+/// how the waited run of trigger is monitored, summary is always collected
+struct PipelineTriggerMonitorConfig {
+    /// writes go to simulation when given
+    simulation: Option<Arc<PipelineSimulation>>,
+    /// pipeline triggered instead of the stored ones, only when simulating
+    pipeline: Option<Pipeline>,
+    /// row exists in topic already, and is not saved again
+    existing: bool,
+}
+
+impl PipelineTriggerMonitorConfig {
+    fn saved() -> Self {
+        PipelineTriggerMonitorConfig {
+            simulation: None,
+            pipeline: None,
+            existing: false,
+        }
+    }
+
+    fn existing() -> Self {
+        PipelineTriggerMonitorConfig {
+            simulation: None,
+            pipeline: None,
+            existing: true,
+        }
+    }
+
+    fn simulated(simulation: Arc<PipelineSimulation>, pipeline: Option<Pipeline>) -> Self {
+        PipelineTriggerMonitorConfig {
+            simulation: Some(simulation),
+            pipeline,
+            existing: false,
+        }
+    }
+}
+
+/// prepared run of trigger, which is waited until all pipelines of the trace completed
+struct PipelineTriggerWaitedRun {
+    result: PipelineTriggerResult,
+    summary: Arc<PipelineRunSummaryCollector>,
+    /// none when no pipeline triggered
+    context: Option<PipelineRunContext>,
+}
+
+impl PipelineTriggerWaitedRun {
+    /// blocks current thread
+    fn wait(self) -> StdR<PipelineTriggerOutcome> {
+        if let Some(context) = self.context {
+            PipelineRunner::execute(context)?.wait()?;
+        }
+        Ok(PipelineTriggerOutcome {
+            result: self.result,
+            summary: self.summary.take(),
+        })
+    }
+
+    async fn wait_async(self) -> StdR<PipelineTriggerOutcome> {
+        if let Some(context) = self.context {
+            PipelineRunner::execute_async(context).await?;
+        }
+        Ok(PipelineTriggerOutcome {
+            result: self.result,
+            summary: self.summary.take(),
+        })
+    }
+}
+
 pub struct PipelineTrigger {
     pub pipeline_id: Option<PipelineId>,
     pub topic_schema: Arc<TopicSchema>,
@@ -19,6 +95,7 @@ pub struct PipelineTrigger {
     pub principal: Arc<Principal>,
     pub trace_id: Arc<PipelineTriggerTraceId>,
     pub execution_log_monitor: Arc<PipelineExecutionLogMonitor>,
+    pub execution_environment: Arc<PipelineExecutionEnvironment>,
 }
 
 impl PipelineTrigger {
@@ -34,7 +111,7 @@ impl PipelineTrigger {
         if topic.is_synonym_topic() && self.r#type.is_insert() {
             TopicTrigger::insert_to_synonym(data)
         } else {
-            let topic_data_service = TopicService::data()?;
+            let topic_data_service = self.execution_environment.topic_data()?;
 
             match self.r#type {
                 PipelineTriggerType::Insert => {
@@ -62,6 +139,87 @@ impl PipelineTrigger {
         }
     }
 
+    /// save prepared rows in one round, returns topic trigger of each row in the same order
+    fn save_trigger_data_batch(&self, data: Vec<TopicData>) -> StdR<Vec<StdR<Arc<TopicTrigger>>>> {
+        if data.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let topic = self.topic_schema.topic();
+        if topic.is_synonym_topic() && self.r#type.is_insert() {
+            return Ok(data
+                .into_iter()
+                .map(TopicTrigger::insert_to_synonym)
+                .collect());
+        }
+
+        let topic_data_service = self.execution_environment.topic_data()?;
+        match self.r#type {
+            PipelineTriggerType::Insert => Ok(topic_data_service
+                .insert_batch(&self.topic_schema, data)?
+                .into_iter()
+                .map(TopicTrigger::insert)
+                .collect()),
+            PipelineTriggerType::InsertOrMerge => Ok(topic_data_service
+                .insert_or_merge_batch(&self.topic_schema, data)?
+                .into_iter()
+                .map(|(previous_data, current_data)| match previous_data {
+                    Some(previous_data) => TopicTrigger::merge(previous_data, current_data),
+                    _ => TopicTrigger::insert(current_data),
+                })
+                .collect()),
+            _ => PipelineKernelErrorCode::TriggerTypeNotSupportedOnBatch.msg(format!(
+                "Trigger type[{}] is not supported on batch.",
+                self.r#type
+            )),
+        }
+    }
+
+    /// prepare each row of chunk, and save the prepared rows in one round.
+    /// returns topic trigger of each row in the same order,
+    /// error when row cannot be prepared, or the chunk cannot be saved.
+    fn save_trigger_data_chunk(&self, chunk: Vec<TopicData>) -> Vec<StdR<Arc<TopicTrigger>>> {
+        let mut saved: Vec<Option<StdR<Arc<TopicTrigger>>>> = Vec::with_capacity(chunk.len());
+        let mut prepared_indexes = vec![];
+        let mut prepared = vec![];
+        for mut data in chunk {
+            match self.prepare_trigger_data(&mut data) {
+                Ok(_) => {
+                    prepared_indexes.push(saved.len());
+                    prepared.push(data);
+                    saved.push(None);
+                }
+                Err(error) => saved.push(Some(Err(error))),
+            }
+        }
+
+        match self.save_trigger_data_batch(prepared) {
+            Ok(topic_triggers) => {
+                for (index, topic_trigger) in prepared_indexes.into_iter().zip(topic_triggers) {
+                    saved[index] = Some(topic_trigger);
+                }
+            }
+            Err(error) => {
+                for index in prepared_indexes {
+                    saved[index] = Some(
+                        PipelineKernelErrorCode::TriggerBatchChunkNotSaved
+                            .msg(format!("Topic data not saved, caused by {}.", error)),
+                    );
+                }
+            }
+        }
+
+        saved
+            .into_iter()
+            .map(|topic_trigger| {
+                topic_trigger.unwrap_or_else(|| {
+                    PipelineKernelErrorCode::TriggerBatchChunkNotSaved
+                        .msg("Topic data not saved, no saved row returned.")
+                })
+            })
+            .collect()
+    }
+
     fn load_pipelines(&self) -> StdR<Option<Vec<Arc<PipelineSchema>>>> {
         let pipelines = match &self.pipeline_id {
             Some(pipeline_id) => {
@@ -108,40 +266,287 @@ impl PipelineTrigger {
         Ok(pipelines)
     }
 
+    /// given pipeline is used when it is defined on the topic and trigger type,
+    /// otherwise load pipelines of topic.
+    fn load_given_pipelines(
+        &self,
+        pipeline: Option<Pipeline>,
+    ) -> StdR<Option<Vec<Arc<PipelineSchema>>>> {
+        let pipeline = match pipeline {
+            Some(pipeline) => Arc::new(PipelineSchema::new(pipeline)?),
+            _ => return self.load_pipelines(),
+        };
+
+        let topic_id = &pipeline.pipeline().topic_id;
+        if topic_id != self.topic_schema.topic_id() {
+            return PipelineKernelErrorCode::TriggerTopicMismatchPipeline.msg(format!(
+                "Given pipeline[{}]'s topic[{}] does not match trigger topic[{}].",
+                pipeline.pipeline_id(),
+                topic_id,
+                self.topic_schema.topic_id()
+            ));
+        }
+        let r#type = pipeline.r#type();
+        if *r#type.deref() != self.r#type {
+            return PipelineKernelErrorCode::TriggerTypeMismatchPipeline.msg(format!(
+                "Given pipeline[{}]'s trigger type[{}] does not match given trigger type[{}].",
+                pipeline.pipeline_id(),
+                r#type,
+                self.r#type
+            ));
+        }
+        Ok(Some(vec![pipeline]))
+    }
+
     fn prepare_execution(
         &self,
         data: TopicData,
+    ) -> StdR<(TopicDataId, Option<PipelineRunContext>)> {
+        let pipelines = self.load_pipelines()?;
+        self.prepare_execution_of(data, pipelines)
+    }
+
+    fn prepare_execution_of(
+        &self,
+        data: TopicData,
+        pipelines: Option<Vec<Arc<PipelineSchema>>>,
     ) -> StdR<(TopicDataId, Option<PipelineRunContext>)> {
         let topic_trigger = self.save_trigger_data(data)?;
+        self.prepare_execution_by(topic_trigger, pipelines)
+    }
+
+    fn prepare_execution_by(
+        &self,
+        topic_trigger: Arc<TopicTrigger>,
+        pipelines: Option<Vec<Arc<PipelineSchema>>>,
+    ) -> StdR<(TopicDataId, Option<PipelineRunContext>)> {
         let topic_data_id = topic_trigger.internal_data_id.deref().clone();
 
-        let pipelines = self.load_pipelines()?;
-        if let Some(pipelines) = pipelines {
-            let context = PipelineRunContext::new(self, topic_trigger, pipelines);
-            Ok((topic_data_id, Some(context)))
-        } else {
-            println!(
-                "No pipeline needs to be triggered by topic[topic_id={}, topic_name={}].",
-                self.topic_schema.topic_id(),
-                self.topic_schema.name()
-            );
-            Ok((topic_data_id, None))
+        match pipelines {
+            Some(pipelines) => {
+                let context = PipelineRunContext::new(self, topic_trigger, pipelines)?;
+                Ok((topic_data_id, Some(context)))
+            }
+            // no pipeline needs to be triggered, log id of result is none
+            _ => Ok((topic_data_id, None)),
+        }
+    }
+
+    /// log id of result is the monitor log id of first pipeline triggered by given data,
+    /// none when no pipeline triggered.
+    fn create_result(
+        &self,
+        topic_data_id: TopicDataId,
+        log_id: Option<Arc<PipelineMonitorLogId>>,
+    ) -> PipelineTriggerResult {
+        let mut result = PipelineTriggerResult::new()
+            .received(true)
+            .trace_id(self.trace_id.deref().clone())
+            .internal_data_id(topic_data_id);
+        result.log_id = log_id.map(|log_id| log_id.deref().clone());
+        result
+    }
+
+    /// create trigger which shares everything with this one, except the log monitor and environment.
+    /// monitor logs are captured when environment is simulating.
+    fn monitored_by(
+        &self,
+        trace_id: Arc<PipelineTriggerTraceId>,
+        summary: Option<Arc<PipelineRunSummaryCollector>>,
+        execution_environment: Arc<PipelineExecutionEnvironment>,
+    ) -> PipelineTrigger {
+        PipelineTrigger {
+            pipeline_id: self.pipeline_id.clone(),
+            topic_schema: self.topic_schema.clone(),
+            r#type: self.r#type.clone(),
+            principal: self.principal.clone(),
+            trace_id: trace_id.clone(),
+            execution_log_monitor: Arc::new(PipelineExecutionLogMonitor {
+                trace_id,
+                principal: self.principal.clone(),
+                summary,
+                writer: execution_environment
+                    .simulation
+                    .as_ref()
+                    .map(|simulation| simulation.monitor_log_writer()),
+            }),
+            execution_environment,
         }
     }
 
-    pub fn execute(&self, data: TopicData) -> StdR<TopicDataId> {
+    /// create trigger which shares everything with this one, except the trace id
+    fn traced_with(&self, trace_id: Arc<PipelineTriggerTraceId>) -> PipelineTrigger {
+        self.monitored_by(
+            trace_id,
+            self.execution_log_monitor.summary.clone(),
+            self.execution_environment.clone(),
+        )
+    }
+
+    fn execute_saved(
+        &self,
+        topic_trigger: Arc<TopicTrigger>,
+        pipelines: &Option<Vec<Arc<PipelineSchema>>>,
+    ) -> StdR<PipelineTriggerResult> {
+        let topic_data_id = topic_trigger.internal_data_id.deref().clone();
+        let log_id = match pipelines {
+            Some(pipelines) => {
+                let context = PipelineRunContext::new(self, topic_trigger, pipelines.clone())?;
+                let log_id = context.first_monitor_log_id();
+                PipelineRunner::execute(context)?;
+                log_id
+            }
+            _ => None,
+        };
+        Ok(self.create_result(topic_data_id, log_id))
+    }
+
+    pub fn execute(&self, data: TopicData) -> StdR<PipelineTriggerResult> {
         let (topic_data_id, context) = self.prepare_execution(data)?;
+        let log_id = context
+            .as_ref()
+            .and_then(|context| context.first_monitor_log_id());
         if let Some(context) = context {
             PipelineRunner::execute(context)?;
         }
-        Ok(topic_data_id)
+        Ok(self.create_result(topic_data_id, log_id))
     }
 
-    pub async fn execute_async(&self, data: TopicData) -> StdR<TopicDataId> {
+    pub async fn execute_async(&self, data: TopicData) -> StdR<PipelineTriggerResult> {
         let (topic_data_id, context) = self.prepare_execution(data)?;
+        let log_id = context
+            .as_ref()
+            .and_then(|context| context.first_monitor_log_id());
         if let Some(context) = context {
             PipelineRunner::execute_async(context).await?;
         }
-        Ok(topic_data_id)
+        Ok(self.create_result(topic_data_id, log_id))
+    }
+
+    /// save or use the existing row, and prepare the run which is waited until completed.
+    /// summary is always collected, and writes go to simulation when it is given.
+    fn prepare_waited(
+        &self,
+        data: TopicData,
+        config: PipelineTriggerMonitorConfig,
+    ) -> StdR<PipelineTriggerWaitedRun> {
+        let summary = Arc::new(PipelineRunSummaryCollector::new());
+        let execution_environment = match config.simulation {
+            Some(simulation) => Arc::new(self.execution_environment.simulated_by(simulation)),
+            _ => self.execution_environment.clone(),
+        };
+        let trigger = self.monitored_by(
+            self.trace_id.clone(),
+            Some(summary.clone()),
+            execution_environment,
+        );
+        let (topic_data_id, context) = if config.existing {
+            let pipelines = trigger.load_pipelines()?;
+            trigger.prepare_execution_by(TopicTrigger::insert(data)?, pipelines)?
+        } else {
+            let pipelines = trigger.load_given_pipelines(config.pipeline)?;
+            trigger.prepare_execution_of(data, pipelines)?
+        };
+        let log_id = context
+            .as_ref()
+            .and_then(|context| context.first_monitor_log_id());
+        Ok(PipelineTriggerWaitedRun {
+            result: trigger.create_result(topic_data_id, log_id),
+            summary,
+            context,
+        })
+    }
+
+    /// execute and wait until all pipelines of the trace completed, including the cascaded ones.
+    /// blocks current thread, do not call it in async context, use [execute_and_wait_async] instead.
+    pub fn execute_and_wait(&self, data: TopicData) -> StdR<PipelineTriggerOutcome> {
+        self.prepare_waited(data, PipelineTriggerMonitorConfig::saved())?
+            .wait()
+    }
+
+    pub async fn execute_and_wait_async(&self, data: TopicData) -> StdR<PipelineTriggerOutcome> {
+        self.prepare_waited(data, PipelineTriggerMonitorConfig::saved())?
+            .wait_async()
+            .await
+    }
+
+    /// trigger by the row which exists in topic already, the row is not saved again.
+    /// wait until all pipelines of the trace completed, including the cascaded ones.
+    /// blocks current thread, do not call it in async context,
+    /// use [execute_existing_and_wait_async] instead.
+    pub fn execute_existing_and_wait(&self, data: TopicData) -> StdR<PipelineTriggerOutcome> {
+        self.prepare_waited(data, PipelineTriggerMonitorConfig::existing())?
+            .wait()
+    }
+
+    pub async fn execute_existing_and_wait_async(
+        &self,
+        data: TopicData,
+    ) -> StdR<PipelineTriggerOutcome> {
+        self.prepare_waited(data, PipelineTriggerMonitorConfig::existing())?
+            .wait_async()
+            .await
+    }
+
+    /// simulate and wait until all pipelines of the trace completed, nothing is persisted or sent.
+    /// topic data writes go to an in-memory overlay, and reads see the overlay first.
+    /// alarms, external writes and monitor logs are captured.
+    /// given pipeline is triggered instead of the stored ones of topic,
+    /// the cascaded pipelines are always the stored ones.
+    /// blocks current thread, do not call it in async context, use [simulate_async] instead.
+    pub fn simulate(
+        &self,
+        data: TopicData,
+        pipeline: Option<Pipeline>,
+    ) -> StdR<PipelineSimulationResult> {
+        let simulation = Arc::new(PipelineSimulation::new()?);
+        let outcome = self
+            .prepare_waited(
+                data,
+                PipelineTriggerMonitorConfig::simulated(simulation.clone(), pipeline),
+            )?
+            .wait()?;
+        simulation.finish(outcome)
+    }
+
+    pub async fn simulate_async(
+        &self,
+        data: TopicData,
+        pipeline: Option<Pipeline>,
+    ) -> StdR<PipelineSimulationResult> {
+        let simulation = Arc::new(PipelineSimulation::new()?);
+        let outcome = self
+            .prepare_waited(
+                data,
+                PipelineTriggerMonitorConfig::simulated(simulation.clone(), pipeline),
+            )?
+            .wait_async()
+            .await?;
+        simulation.finish(outcome)
+    }
+
+    /// rows are saved in chunks, and pipelines are loaded only once.
+    /// each row is executed by its own trace id,
+    /// returns result of each row in the same order, a failed row does not abort the others.
+    /// rows are still saved when no pipeline needs to be triggered, log id of results are none.
+    pub fn execute_batch(
+        &self,
+        rows: PipelineTriggerBatchRows,
+    ) -> StdR<Vec<StdR<PipelineTriggerResult>>> {
+        let pipelines = self.load_pipelines()?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        let mut rows = rows.into_iter().peekable();
+        while rows.peek().is_some() {
+            let (trace_ids, chunk): (Vec<_>, Vec<_>) = rows.by_ref().take(BATCH_CHUNK_SIZE).unzip();
+            let topic_triggers = self.save_trigger_data_chunk(chunk);
+            for (trace_id, topic_trigger) in trace_ids.into_iter().zip(topic_triggers) {
+                results.push(topic_trigger.and_then(|topic_trigger| {
+                    self.traced_with(trace_id)
+                        .execute_saved(topic_trigger, &pipelines)
+                }));
+            }
+        }
+        Ok(results)
     }
 }
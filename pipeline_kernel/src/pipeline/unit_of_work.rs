@@ -0,0 +1,456 @@
+use crate::{OverlayTopicDataStore, PipelineKernelErrorCode, TopicDataStore, TopicDataWrite};
+use bigdecimal::BigDecimal;
+use elf_base::{ErrorCode, VoidR};
+use elf_model::{TopicData, TopicDataColumnNames, TopicDataValue};
+use elf_runtime_model_kernel::TopicSchema;
+use std::sync::Arc;
+
+/// buffer writes of one pipeline execution, reads see the buffered writes first.
+/// buffered writes are committed to store when pipeline successful, or discarded on error.
+pub struct PipelineUnitOfWork {
+    buffer: Arc<OverlayTopicDataStore>,
+}
+
+impl PipelineUnitOfWork {
+    pub fn new(store: Arc<dyn TopicDataStore>) -> Self {
+        PipelineUnitOfWork {
+            buffer: Arc::new(OverlayTopicDataStore::new(store)),
+        }
+    }
+
+    pub fn data(&self) -> Arc<dyn TopicDataStore> {
+        self.buffer.clone()
+    }
+
+    /// write the buffered to store in written order.
+    /// in transaction when store supports, otherwise the written ones are compensated on failure.
+    /// failure of rollback is appended to the error of writing.
+    pub fn commit(&self) -> VoidR {
+        let writes = self.buffer.take_writes()?;
+        if writes.is_empty() {
+            return Ok(());
+        }
+
+        let store = self.buffer.base();
+        match store.begin()? {
+            Some(transaction) => match Self::apply(transaction.as_ref(), &writes) {
+                Ok(_) => transaction.commit(),
+                Err(error) => match transaction.rollback() {
+                    Ok(_) => Err(error),
+                    Err(rollback_error) => {
+                        PipelineKernelErrorCode::UnitOfWorkNotRolledBack.msg(format!(
+                            "Unit of work failed, caused by {}, and not rolled back, caused by {}.",
+                            error, rollback_error
+                        ))
+                    }
+                },
+            },
+            _ => Self::apply_or_compensate(store.as_ref(), &writes),
+        }
+    }
+
+    /// discard the buffered
+    pub fn discard(&self) -> VoidR {
+        self.buffer.take_writes().map(|_| ())
+    }
+
+    fn apply_one<S>(store: &S, topic_schema: &Arc<TopicSchema>, write: &TopicDataWrite) -> VoidR
+    where
+        S: TopicDataStore + ?Sized,
+    {
+        match write {
+            TopicDataWrite::Insert(data) => store.insert(topic_schema, data.clone()).map(|_| ()),
+            TopicDataWrite::Merge(_, current) => {
+                store.merge(topic_schema, current.clone()).map(|_| ())
+            }
+            TopicDataWrite::MergeVersioned(_, current, version) => {
+                Self::merge_versioned(store, topic_schema, current, *version)
+            }
+            TopicDataWrite::Delete(previous) => {
+                store.delete(topic_schema, previous.clone()).map(|_| ())
+            }
+        }
+    }
+
+    /// merge with the version expected when buffered, conflict fails the commit
+    fn merge_versioned<S>(
+        store: &S,
+        topic_schema: &Arc<TopicSchema>,
+        current: &TopicData,
+        version: u64,
+    ) -> VoidR
+    where
+        S: TopicDataStore + ?Sized,
+    {
+        let mut data = current.clone();
+        data.insert(
+            TopicDataColumnNames::Version.column_name().to_string(),
+            TopicDataValue::Num(BigDecimal::from(version)),
+        );
+        match store.merge_versioned(topic_schema, data)? {
+            Some(_) => Ok(()),
+            _ => PipelineKernelErrorCode::UnitOfWorkVersionConflicted.msg(format!(
+                "Topic data version conflicted on commit, of data[id={:?}] of topic[{}], expected version is {}.",
+                current.get(TopicDataColumnNames::Id.column_name()),
+                topic_schema.topic_id(),
+                version
+            )),
+        }
+    }
+
+    fn apply<S>(store: &S, writes: &[(Arc<TopicSchema>, TopicDataWrite)]) -> VoidR
+    where
+        S: TopicDataStore + ?Sized,
+    {
+        for (topic_schema, write) in writes {
+            Self::apply_one(store, topic_schema, write)?;
+        }
+        Ok(())
+    }
+
+    /// revert the write, insert is deleted, merge is merged back, and delete is inserted back.
+    fn compensate_one<S>(
+        store: &S,
+        topic_schema: &Arc<TopicSchema>,
+        write: &TopicDataWrite,
+    ) -> VoidR
+    where
+        S: TopicDataStore + ?Sized,
+    {
+        match write {
+            TopicDataWrite::Insert(data) => store.delete(topic_schema, data.clone()).map(|_| ()),
+            TopicDataWrite::Merge(previous, _) | TopicDataWrite::MergeVersioned(previous, _, _) => {
+                store.merge(topic_schema, previous.clone()).map(|_| ())
+            }
+            TopicDataWrite::Delete(previous) => {
+                store.insert(topic_schema, previous.clone()).map(|_| ())
+            }
+        }
+    }
+
+    /// fallback for store which does not support transaction.
+    /// writes are applied one by one, and the applied ones are compensated in reverse order on failure.
+    fn apply_or_compensate<S>(store: &S, writes: &[(Arc<TopicSchema>, TopicDataWrite)]) -> VoidR
+    where
+        S: TopicDataStore + ?Sized,
+    {
+        for (index, (topic_schema, write)) in writes.iter().enumerate() {
+            if let Err(error) = Self::apply_one(store, topic_schema, write) {
+                let mut not_compensated = vec![];
+                for (topic_schema, write) in writes[..index].iter().rev() {
+                    if let Err(e) = Self::compensate_one(store, topic_schema, write) {
+                        not_compensated.push(e.to_string());
+                    }
+                }
+                return if not_compensated.is_empty() {
+                    Err(error)
+                } else {
+                    PipelineKernelErrorCode::UnitOfWorkNotCompensated.msg(format!(
+                        "Unit of work failed, caused by {}, and {} write(s) not compensated, caused by [{}].",
+                        error,
+                        not_compensated.len(),
+                        not_compensated.join(", ")
+                    ))
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        PipelineCancellationToken, PipelineExecutable, PipelineExecutionEnvironment,
+        PipelineExecutionLogMonitor, PipelineSimulation, PipelineUnitOfWork, TopicDataStore,
+        TopicDataTransaction, TopicTrigger,
+    };
+    use elf_auth::Principal;
+    use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+    use elf_model::{
+        Factor, FactorType, Topic, TopicData, TopicDataId, TopicDataValue, TopicKind, TopicType,
+    };
+    use elf_runtime_model_kernel::{ArcFactor, TopicDataCriteria, TopicSchema};
+    use std::collections::HashMap;
+    use std::sync::{Arc, RwLock};
+
+    /// rows in memory, fails on writing the given id,
+    /// and version conflicted on merging the given id with version.
+    /// transaction begun when transactional, which cannot be rolled back.
+    struct FallibleTopicDataStore {
+        rows: RwLock<HashMap<TopicDataId, TopicData>>,
+        fail_on: TopicDataId,
+        transactional: bool,
+    }
+
+    impl FallibleTopicDataStore {
+        fn id_of(data: &TopicData) -> TopicDataId {
+            match data.get("id_") {
+                Some(TopicDataValue::Str(id)) => id.clone(),
+                _ => panic!("id not found"),
+            }
+        }
+
+        fn put(&self, data: TopicData) -> StdR<TopicData> {
+            let id = Self::id_of(&data);
+            if id == self.fail_on {
+                return StdErrCode::Unknown.msg(format!("Failed on writing [{}].", id));
+            }
+            self.rows.write().unwrap().insert(id, data.clone());
+            Ok(data)
+        }
+
+        fn len(&self) -> usize {
+            self.rows.read().unwrap().len()
+        }
+    }
+
+    impl TopicDataStore for FallibleTopicDataStore {
+        fn insert(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            self.put(data)
+        }
+        fn insert_or_merge(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<(Option<TopicData>, TopicData)> {
+            self.put(data).map(|data| (None, data))
+        }
+        fn merge(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<(TopicData, TopicData)> {
+            self.put(data).map(|data| (data.clone(), data))
+        }
+        fn merge_versioned(
+            &self,
+            _: &Arc<TopicSchema>,
+            data: TopicData,
+        ) -> StdR<Option<(TopicData, TopicData)>> {
+            if Self::id_of(&data) == self.fail_on {
+                return Ok(None);
+            }
+            self.put(data).map(|data| Some((data.clone(), data)))
+        }
+        fn delete(&self, _: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+            self.rows.write().unwrap().remove(&Self::id_of(&data));
+            Ok(data)
+        }
+        fn find_by_id(&self, _: &Arc<TopicSchema>, id: &TopicDataId) -> StdR<Option<TopicData>> {
+            Ok(self.rows.read().unwrap().get(id).cloned())
+        }
+        fn find(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Vec<TopicData>> {
+            Ok(self.rows.read().unwrap().values().cloned().collect())
+        }
+        fn find_one(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Option<TopicData>> {
+            Ok(None)
+        }
+        fn find_values(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: &Arc<ArcFactor>,
+            _: &TopicDataCriteria,
+        ) -> StdR<Vec<TopicDataValue>> {
+            Ok(vec![])
+        }
+        fn count(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<u64> {
+            Ok(self.len() as u64)
+        }
+        fn exists(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<bool> {
+            Ok(self.len() != 0)
+        }
+        fn begin(&self) -> StdR<Option<Box<dyn TopicDataTransaction>>> {
+            if !self.transactional {
+                return Ok(None);
+            }
+            Ok(Some(Box::new(FallibleTopicDataStore {
+                rows: RwLock::new(HashMap::new()),
+                fail_on: self.fail_on.clone(),
+                transactional: false,
+            })))
+        }
+    }
+
+    impl TopicDataTransaction for FallibleTopicDataStore {
+        fn commit(self: Box<Self>) -> VoidR {
+            Ok(())
+        }
+        fn rollback(self: Box<Self>) -> VoidR {
+            StdErrCode::Unknown.msg("Failed on rollback.")
+        }
+    }
+
+    fn create_topic_schema() -> Arc<TopicSchema> {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("name"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        Arc::new(TopicSchema::new(topic).expect("failed to create topic schema"))
+    }
+
+    fn create_row(id: &str) -> TopicData {
+        HashMap::from([(String::from("id_"), TopicDataValue::Str(id.to_string()))])
+    }
+
+    #[test]
+    fn test_commit_and_discard() {
+        let topic_schema = create_topic_schema();
+        let store = Arc::new(FallibleTopicDataStore {
+            rows: RwLock::new(HashMap::new()),
+            fail_on: String::from("5"),
+            transactional: false,
+        });
+
+        let unit_of_work = PipelineUnitOfWork::new(store.clone());
+        unit_of_work
+            .data()
+            .insert(&topic_schema, create_row("1"))
+            .unwrap();
+        unit_of_work
+            .data()
+            .insert(&topic_schema, create_row("2"))
+            .unwrap();
+        assert_eq!(store.len(), 0);
+        unit_of_work.commit().unwrap();
+        assert_eq!(store.len(), 2);
+
+        let unit_of_work = PipelineUnitOfWork::new(store.clone());
+        unit_of_work
+            .data()
+            .delete(&topic_schema, create_row("1"))
+            .unwrap();
+        unit_of_work
+            .data()
+            .insert(&topic_schema, create_row("3"))
+            .unwrap();
+        unit_of_work.discard().unwrap();
+        unit_of_work.commit().unwrap();
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_compensate() {
+        let topic_schema = create_topic_schema();
+        let store = Arc::new(FallibleTopicDataStore {
+            rows: RwLock::new(HashMap::from([(String::from("1"), create_row("1"))])),
+            fail_on: String::from("5"),
+            transactional: false,
+        });
+
+        let unit_of_work = PipelineUnitOfWork::new(store.clone());
+        let data = unit_of_work.data();
+        data.delete(&topic_schema, create_row("1")).unwrap();
+        data.insert(&topic_schema, create_row("4")).unwrap();
+        data.insert(&topic_schema, create_row("5")).unwrap();
+        assert!(unit_of_work.commit().is_err());
+        // deleted row is inserted back, and inserted row is deleted
+        assert!(store.rows.read().unwrap().contains_key("1"));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_rollback_failed() {
+        let topic_schema = create_topic_schema();
+        let store = Arc::new(FallibleTopicDataStore {
+            rows: RwLock::new(HashMap::new()),
+            fail_on: String::from("5"),
+            transactional: true,
+        });
+
+        let unit_of_work = PipelineUnitOfWork::new(store.clone());
+        let data = unit_of_work.data();
+        data.insert(&topic_schema, create_row("4")).unwrap();
+        data.insert(&topic_schema, create_row("5")).unwrap();
+        // both failures are returned
+        let error = unit_of_work.commit().err().unwrap();
+        assert_eq!(error.code, "PLKN-00702");
+        let message = error.to_string();
+        assert!(message.contains("Failed on writing [5]."));
+        assert!(message.contains("Failed on rollback."));
+    }
+
+    #[test]
+    fn test_discard_on_failed_action() {
+        let topic_schema = create_topic_schema();
+        let store = Arc::new(FallibleTopicDataStore {
+            rows: RwLock::new(HashMap::new()),
+            fail_on: String::from("5"),
+            transactional: false,
+        });
+        let simulation = Arc::new(PipelineSimulation::with(store.clone()));
+        let principal = Arc::new(Principal::fake_super_admin());
+        let trace_id = Arc::new(String::from("trace-1"));
+        let log_monitor = Arc::new(PipelineExecutionLogMonitor {
+            principal: principal.clone(),
+            trace_id: trace_id.clone(),
+            summary: None,
+            writer: Some(simulation.monitor_log_writer()),
+        });
+        let environment = Arc::new(PipelineExecutionEnvironment {
+            principal: principal.clone(),
+            simulation: Some(simulation.clone()),
+            unit_of_work: true,
+            dead_letter: false,
+            cancellation: Arc::new(PipelineCancellationToken::new()),
+            time_budget: None,
+        });
+        let mut executable = PipelineExecutable::new(
+            TopicTrigger::insert(create_row("1")).unwrap(),
+            principal,
+            trace_id,
+            log_monitor,
+            environment,
+        );
+
+        executable.begin_unit_of_work().unwrap();
+        // insert action writes to the unit of work, and the next action fails
+        executable
+            .topic_data()
+            .unwrap()
+            .insert(&topic_schema, create_row("2"))
+            .unwrap();
+        let result: StdR<()> = StdErrCode::Unknown.msg("Action failed.");
+        assert!(executable.end_unit_of_work(result).is_err());
+        assert!(
+            simulation
+                .data()
+                .find_by_id(&topic_schema, &String::from("2"))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(store.len(), 0);
+    }
+
+    #[test]
+    fn test_compensate_on_version_conflicted() {
+        let topic_schema = create_topic_schema();
+        let store = Arc::new(FallibleTopicDataStore {
+            rows: RwLock::new(HashMap::from([
+                (String::from("1"), create_row("1")),
+                (String::from("5"), create_row("5")),
+            ])),
+            fail_on: String::from("5"),
+            transactional: false,
+        });
+
+        let unit_of_work = PipelineUnitOfWork::new(store.clone());
+        let data = unit_of_work.data();
+        data.insert(&topic_schema, create_row("4")).unwrap();
+        assert!(
+            data.merge_versioned(&topic_schema, create_row("5"))
+                .unwrap()
+                .is_some()
+        );
+        let error = unit_of_work.commit().unwrap_err();
+        assert_eq!(error.code, "PLKN-00701");
+        // inserted row is deleted
+        assert!(!store.rows.read().unwrap().contains_key("4"));
+        assert_eq!(store.len(), 2);
+    }
+}
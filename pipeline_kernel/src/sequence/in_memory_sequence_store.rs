@@ -0,0 +1,49 @@
+use crate::SequenceStore;
+use elf_base::{ErrorCode, StdErrCode, StdR};
+use elf_model::TenantId;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// keep sequences in memory, values are lost when process restarts.
+/// used by tests and as the scratch store of simulation.
+#[derive(Default)]
+pub struct InMemorySequenceStore {
+    sequences: RwLock<HashMap<(TenantId, String), u64>>,
+}
+
+impl InMemorySequenceStore {
+    pub fn new() -> Self {
+        InMemorySequenceStore {
+            sequences: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl SequenceStore for InMemorySequenceStore {
+    fn next_value(&self, tenant_id: &TenantId, name: &str) -> StdR<u64> {
+        let mut guard = self.sequences.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        let value = guard
+            .entry((tenant_id.clone(), name.to_string()))
+            .or_insert(0);
+        *value += 1;
+        Ok(*value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InMemorySequenceStore, SequenceStore};
+
+    #[test]
+    fn test_next_value() {
+        let store = InMemorySequenceStore::new();
+        let tenant_1 = "Tenant-1".to_string();
+        let tenant_2 = "Tenant-2".to_string();
+        assert_eq!(store.next_value(&tenant_1, "order").unwrap(), 1);
+        assert_eq!(store.next_value(&tenant_1, "order").unwrap(), 2);
+        assert_eq!(store.next_value(&tenant_1, "invoice").unwrap(), 1);
+        assert_eq!(store.next_value(&tenant_2, "order").unwrap(), 1);
+    }
+}
@@ -0,0 +1,5 @@
+mod in_memory_sequence_store;
+mod sequence_store;
+
+pub use in_memory_sequence_store::*;
+pub use sequence_store::*;
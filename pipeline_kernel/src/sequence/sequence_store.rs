@@ -0,0 +1,70 @@
+use crate::PipelineKernelErrorCode;
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::TenantId;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// named sequences, counted per tenant
+pub trait SequenceStore: Send + Sync {
+    /// next value of given sequence, starts from 1
+    fn next_value(&self, tenant_id: &TenantId, name: &str) -> StdR<u64>;
+}
+
+static GLOBAL_SEQUENCE_STORE: OnceLock<RwLock<Option<Arc<dyn SequenceStore>>>> = OnceLock::new();
+
+/// there is no default sequence store, since values must survive restarts,
+/// [set] a store before named sequences are used.
+/// [crate::InMemorySequenceStore] is for tests only.
+pub struct SequenceStores;
+
+impl SequenceStores {
+    fn init() -> RwLock<Option<Arc<dyn SequenceStore>>> {
+        RwLock::new(None)
+    }
+
+    /// get current store, and release lock before use.
+    /// returns error when no store is set.
+    pub fn store() -> StdR<Arc<dyn SequenceStore>> {
+        let store = GLOBAL_SEQUENCE_STORE
+            .get_or_init(SequenceStores::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?
+            .clone();
+        match store {
+            Some(store) => Ok(store),
+            _ => PipelineKernelErrorCode::SequenceStoreNotConfigured
+                .msg("Sequence store is not configured, set one by SequenceStores::set."),
+        }
+    }
+
+    pub fn set(new_store: Arc<dyn SequenceStore>) -> VoidR {
+        let mut guard = GLOBAL_SEQUENCE_STORE
+            .get_or_init(SequenceStores::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        *guard = Some(new_store);
+
+        Ok(())
+    }
+}
+
+/// named sequences of one tenant,
+/// by the given store, or by [SequenceStores] when not given.
+pub struct TenantSequences {
+    pub tenant_id: Arc<TenantId>,
+    pub store: Option<Arc<dyn SequenceStore>>,
+}
+
+impl TenantSequences {
+    pub fn next_value(&self, name: &str) -> StdR<u64> {
+        match &self.store {
+            Some(store) => store.next_value(&self.tenant_id, name),
+            _ => SequenceStores::store()?.next_value(&self.tenant_id, name),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+mod pipeline_simulation;
+
+pub use pipeline_simulation::*;
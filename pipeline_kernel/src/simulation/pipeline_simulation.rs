@@ -0,0 +1,105 @@
+use crate::{
+	AlarmSink, InMemoryAlarmSink, InMemoryMonitorLogWriter, InMemorySequenceStore,
+	MonitorLogWriter, OverlayTopicDataStore, PipelineAlarm, PipelineTriggerOutcome, SequenceStore,
+	TopicDataChanges, TopicDataStore,
+};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{ExternalWriterId, PipelineMonitorLog, TopicId};
+use elf_runtime_model_kernel::{ExternalWriteParams, TopicDataProvider, TopicService};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// external write captured instead of sent
+pub struct PipelineSimulatedExternalWrite {
+    pub external_writer_id: Arc<ExternalWriterId>,
+    pub params: ExternalWriteParams,
+}
+
+/// result of simulation, nothing is persisted or sent.
+pub struct PipelineSimulationResult {
+    pub outcome: PipelineTriggerOutcome,
+    /// would-be changes, key is topic id
+    pub changes: HashMap<Arc<TopicId>, TopicDataChanges>,
+    pub alarms: Vec<PipelineAlarm>,
+    pub external_writes: Vec<PipelineSimulatedExternalWrite>,
+    pub monitor_logs: Vec<PipelineMonitorLog>,
+}
+
+/// shared by all executions of one simulated trace.
+/// topic data writes go to the in-memory overlay,
+/// alarms, external writes and monitor logs are captured,
+/// named sequences are counted in a scratch store, the configured one is not touched.
+pub struct PipelineSimulation {
+    data: Arc<OverlayTopicDataStore>,
+    sequences: Arc<InMemorySequenceStore>,
+    alarms: InMemoryAlarmSink,
+    external_writes: RwLock<Vec<PipelineSimulatedExternalWrite>>,
+    monitor_logs: Arc<InMemoryMonitorLogWriter>,
+}
+
+impl PipelineSimulation {
+    /// overlay on the topic data service
+    pub fn new() -> StdR<Self> {
+        Ok(Self::with(TopicService::data()?))
+    }
+
+    pub fn with(base: Arc<dyn TopicDataStore>) -> Self {
+        PipelineSimulation {
+            data: Arc::new(OverlayTopicDataStore::new(base)),
+            sequences: Arc::new(InMemorySequenceStore::new()),
+            alarms: InMemoryAlarmSink::new(),
+            external_writes: RwLock::new(vec![]),
+            monitor_logs: Arc::new(InMemoryMonitorLogWriter::new()),
+        }
+    }
+
+    pub fn data(&self) -> Arc<dyn TopicDataStore> {
+        self.data.clone()
+    }
+
+    pub fn sequence_store(&self) -> Arc<dyn SequenceStore> {
+        self.sequences.clone()
+    }
+
+    pub fn alarm(&self, alarm: PipelineAlarm) -> VoidR {
+        self.alarms.emit(alarm)
+    }
+
+    pub fn write_external(
+        &self,
+        external_writer_id: &Arc<ExternalWriterId>,
+        params: ExternalWriteParams,
+    ) -> VoidR {
+        let mut guard = self.external_writes.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })?;
+        guard.push(PipelineSimulatedExternalWrite {
+            external_writer_id: external_writer_id.clone(),
+            params,
+        });
+        Ok(())
+    }
+
+    /// writer which captures monitor logs
+    pub fn monitor_log_writer(&self) -> Arc<dyn MonitorLogWriter> {
+        self.monitor_logs.clone()
+    }
+
+    /// take all captured, and build the result with given outcome
+    pub fn finish(&self, outcome: PipelineTriggerOutcome) -> StdR<PipelineSimulationResult> {
+        let external_writes = {
+            let mut guard = self.external_writes.write().map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+            std::mem::take(&mut *guard)
+        };
+        Ok(PipelineSimulationResult {
+            outcome,
+            changes: self.data.take_changes()?,
+            alarms: self.alarms.alarms()?,
+            external_writes,
+            monitor_logs: self.monitor_logs.take()?,
+        })
+    }
+}
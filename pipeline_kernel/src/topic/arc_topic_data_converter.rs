@@ -0,0 +1,67 @@
+use crate::{ArcTopicDataMap, ArcTopicDataValue};
+use elf_model::{TopicData, TopicDataValue};
+use std::ops::Deref;
+
+/// convert arc topic data back to topic data, values are cloned.
+pub trait ArcTopicDataConverter {
+    fn to_topic_data(&self) -> TopicData;
+}
+
+impl ArcTopicDataConverter for ArcTopicDataMap {
+    fn to_topic_data(&self) -> TopicData {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.to_topic_data_value()))
+            .collect()
+    }
+}
+
+impl ArcTopicDataValue {
+    pub fn to_topic_data_value(&self) -> TopicDataValue {
+        match self {
+            Self::Str(str) => TopicDataValue::Str(str.deref().clone()),
+            Self::Num(num) => TopicDataValue::Num(num.deref().clone()),
+            Self::Bool(bool) => TopicDataValue::Bool(*bool),
+            Self::DateTime(datetime) => TopicDataValue::DateTime(*datetime.deref()),
+            Self::Date(date) => TopicDataValue::Date(*date.deref()),
+            Self::Time(time) => TopicDataValue::Time(*time.deref()),
+            Self::Vec(vec) => {
+                TopicDataValue::Vec(vec.iter().map(|v| v.to_topic_data_value()).collect())
+            }
+            Self::Map(map) => TopicDataValue::Map(map.to_topic_data()),
+            Self::None => TopicDataValue::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcTopicData, ArcTopicDataBuilder, ArcTopicDataConverter};
+    use bigdecimal::BigDecimal;
+    use elf_model::TopicDataValue;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_round_trip() {
+        let mut data = HashMap::new();
+        data.insert("a".to_string(), TopicDataValue::Str("x".to_string()));
+        data.insert(
+            "b".to_string(),
+            TopicDataValue::Vec(vec![
+                TopicDataValue::Num(BigDecimal::from(1)),
+                TopicDataValue::None,
+            ]),
+        );
+        let mut sub = HashMap::new();
+        sub.insert("c".to_string(), TopicDataValue::Bool(true));
+        data.insert("d".to_string(), TopicDataValue::Map(sub));
+
+        let converted = ArcTopicData::build(data).to_topic_data();
+        assert!(matches!(converted.get("a"), Some(TopicDataValue::Str(s)) if s == "x"));
+        assert!(
+            matches!(converted.get("b"), Some(TopicDataValue::Vec(v)) if v.len() == 2 && matches!(v[1], TopicDataValue::None))
+        );
+        assert!(
+            matches!(converted.get("d"), Some(TopicDataValue::Map(m)) if matches!(m.get("c"), Some(TopicDataValue::Bool(true))))
+        );
+    }
+}
@@ -1,7 +1,13 @@
 mod arc_topic_data;
 mod arc_topic_data_builder;
+mod arc_topic_data_converter;
+mod overlay_topic_data_store;
+mod topic_data_store;
 mod trigger;
 
 pub use arc_topic_data::*;
 pub use arc_topic_data_builder::*;
+pub use arc_topic_data_converter::*;
+pub use overlay_topic_data_store::*;
+pub use topic_data_store::*;
 pub use trigger::*;
@@ -0,0 +1,621 @@
+use crate::{ArcTopicData, ArcTopicDataBuilder, PipelineKernelErrorCode, TopicDataStore};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{
+	ParameterExpressionOperator, ParameterJointType, TopicData, TopicDataColumnNames, TopicDataId,
+	TopicDataValue, TopicId,
+};
+use elf_runtime_model_kernel::{
+	ArcFactor, IdGen, TopicDataCriteria, TopicDataCriteriaExpression, TopicDataCriteriaOperand,
+	TopicSchema,
+};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// one write kept by overlay
+pub enum TopicDataWrite {
+    Insert(TopicData),
+    /// previous and current
+    Merge(TopicData, TopicData),
+    /// previous, current, and version of previous which is expected in store
+    MergeVersioned(TopicData, TopicData, u64),
+    /// previous
+    Delete(TopicData),
+}
+
+/// changes of one topic, grouped from writes
+#[derive(Default, Debug)]
+pub struct TopicDataChanges {
+    pub inserted: Vec<TopicData>,
+    /// previous and current
+    pub merged: Vec<(TopicData, TopicData)>,
+    pub deleted: Vec<TopicData>,
+}
+
+#[derive(Default)]
+struct TopicDataOverlay {
+    /// changed rows, key is topic id and data id, value is none when row deleted
+    rows: HashMap<Arc<TopicId>, HashMap<TopicDataId, Option<TopicData>>>,
+    /// writes in written order
+    writes: Vec<(Arc<TopicSchema>, TopicDataWrite)>,
+}
+
+/// keep all writes in memory as an overlay of the base store, base store is never written.
+/// reads see the overlay first, rows of base store are replaced by the changed ones.
+pub struct OverlayTopicDataStore {
+    base: Arc<dyn TopicDataStore>,
+    overlay: RwLock<TopicDataOverlay>,
+}
+
+impl OverlayTopicDataStore {
+    pub fn new(base: Arc<dyn TopicDataStore>) -> Self {
+        OverlayTopicDataStore {
+            base,
+            overlay: RwLock::new(TopicDataOverlay::default()),
+        }
+    }
+
+    pub fn base(&self) -> &Arc<dyn TopicDataStore> {
+        &self.base
+    }
+
+    fn read(&self) -> StdR<RwLockReadGuard<'_, TopicDataOverlay>> {
+        self.overlay.read().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+        })
+    }
+
+    fn write(&self) -> StdR<RwLockWriteGuard<'_, TopicDataOverlay>> {
+        self.overlay.write().map_err(|e| {
+            StdErrCode::RwLock.err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+        })
+    }
+
+    /// take the writes in written order, and clear the overlay
+    pub fn take_writes(&self) -> StdR<Vec<(Arc<TopicSchema>, TopicDataWrite)>> {
+        let mut overlay = self.write()?;
+        overlay.rows.clear();
+        Ok(std::mem::take(&mut overlay.writes))
+    }
+
+    /// take the writes grouped by topic, and clear the overlay
+    pub fn take_changes(&self) -> StdR<HashMap<Arc<TopicId>, TopicDataChanges>> {
+        let mut changes: HashMap<Arc<TopicId>, TopicDataChanges> = HashMap::new();
+        for (topic_schema, write) in self.take_writes()? {
+            let topic_changes = changes.entry(topic_schema.topic_id().clone()).or_default();
+            match write {
+                TopicDataWrite::Insert(data) => topic_changes.inserted.push(data),
+                TopicDataWrite::Merge(previous, current)
+                | TopicDataWrite::MergeVersioned(previous, current, _) => {
+                    topic_changes.merged.push((previous, current))
+                }
+                TopicDataWrite::Delete(previous) => topic_changes.deleted.push(previous),
+            }
+        }
+        Ok(changes)
+    }
+
+    fn id_of(data: &TopicData) -> StdR<Option<TopicDataId>> {
+        match data.get(TopicDataColumnNames::Id.column_name()) {
+            Some(TopicDataValue::Str(id)) => Ok(Some(id.clone())),
+            Some(TopicDataValue::Num(id)) => Ok(Some(id.to_string())),
+            Some(TopicDataValue::None) | None => Ok(None),
+            _ => PipelineKernelErrorCode::TopicDataIdTypeNotSupported.msg(format!(
+                "Topic data id type not supported, of data[{:?}].",
+                data
+            )),
+        }
+    }
+
+    fn id_required(data: &TopicData) -> StdR<TopicDataId> {
+        match Self::id_of(data)? {
+            Some(id) => Ok(id),
+            _ => PipelineKernelErrorCode::TopicDataIdNotFound
+                .msg(format!("Topic data id not found, of data[{:?}].", data)),
+        }
+    }
+
+    /// version of row, 0 when row has no version
+    fn version_of(data: &TopicData) -> StdR<u64> {
+        let version = match data.get(TopicDataColumnNames::Version.column_name()) {
+            Some(TopicDataValue::Num(version)) => version.to_u64(),
+            Some(TopicDataValue::Str(version)) => version.parse::<u64>().ok(),
+            Some(TopicDataValue::None) | None => Some(0),
+            _ => None,
+        };
+        match version {
+            Some(version) => Ok(version),
+            _ => PipelineKernelErrorCode::TopicDataVersionTypeNotSupported.msg(format!(
+                "Topic data version type not supported, of data[{:?}].",
+                data
+            )),
+        }
+    }
+
+    /// value of factor, factor name is a dot joined path
+    fn value_of<'a>(data: &'a TopicData, factor: &ArcFactor) -> Option<&'a TopicDataValue> {
+        let mut segments = factor.name.split('.');
+        let mut value = data.get(segments.next()?)?;
+        for segment in segments {
+            value = match value {
+                TopicDataValue::Map(map) => map.get(segment)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    fn operand_value(data: &TopicData, operand: &TopicDataCriteriaOperand) -> TopicDataValue {
+        match operand {
+            TopicDataCriteriaOperand::Factor(factor) => Self::value_of(data, factor)
+                .cloned()
+                .unwrap_or(TopicDataValue::None),
+            TopicDataCriteriaOperand::Value(value) => value.clone(),
+        }
+    }
+
+    fn matches_expression(
+        data: &TopicData,
+        expression: &TopicDataCriteriaExpression,
+    ) -> StdR<bool> {
+        let left = ArcTopicData::build_value(Self::operand_value(data, &expression.left));
+        let right = ArcTopicData::build_value(match &expression.right {
+            Some(right) => Self::operand_value(data, right),
+            _ => TopicDataValue::None,
+        });
+        match expression.operator.deref() {
+            ParameterExpressionOperator::Empty => Ok(left.is_empty()),
+            ParameterExpressionOperator::NotEmpty => Ok(left.is_not_empty()),
+            ParameterExpressionOperator::Equals => Ok(left.is_same_as(&right)),
+            ParameterExpressionOperator::NotEquals => Ok(left.is_not_same_as(&right)),
+            ParameterExpressionOperator::Less => left.is_less_than(&right),
+            ParameterExpressionOperator::LessEquals => left.is_less_than_or_equals(&right),
+            ParameterExpressionOperator::More => left.is_more_than(&right),
+            ParameterExpressionOperator::MoreEquals => left.is_more_than_or_equals(&right),
+            ParameterExpressionOperator::In => left.is_in(&right),
+            ParameterExpressionOperator::NotIn => left.is_not_in(&right),
+        }
+    }
+
+    fn matches(data: &TopicData, criteria: &TopicDataCriteria) -> StdR<bool> {
+        match criteria {
+            TopicDataCriteria::Expression(expression) => Self::matches_expression(data, expression),
+            TopicDataCriteria::Joint(joint) => match joint.joint_type.deref() {
+                ParameterJointType::And => {
+                    for condition in &joint.conditions {
+                        if !Self::matches(data, condition)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                ParameterJointType::Or => {
+                    for condition in &joint.conditions {
+                        if Self::matches(data, condition)? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+            },
+        }
+    }
+
+    /// find from overlay first, then the base store.
+    /// returns none when not found, or the row is deleted.
+    fn find_previous(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: &TopicDataId,
+    ) -> StdR<Option<TopicData>> {
+        if let Some(row) = self
+            .read()?
+            .rows
+            .get(topic_schema.topic_id())
+            .and_then(|rows| rows.get(id))
+        {
+            return Ok(row.clone());
+        }
+        self.base.find_by_id(topic_schema, id)
+    }
+
+    fn find_previous_to_merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: &TopicDataId,
+    ) -> StdR<TopicData> {
+        match self.find_previous(topic_schema, id)? {
+            Some(previous) => Ok(previous),
+            _ => PipelineKernelErrorCode::TopicDataNotFound.msg(format!(
+                "Topic data[id={}] not found of topic[{}], cannot merge.",
+                id,
+                topic_schema.topic_id()
+            )),
+        }
+    }
+
+    /// keep the row in overlay, none when row deleted
+    fn record(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: TopicDataId,
+        row: Option<TopicData>,
+        write: TopicDataWrite,
+    ) -> VoidR {
+        let mut overlay = self.write()?;
+        overlay
+            .rows
+            .entry(topic_schema.topic_id().clone())
+            .or_default()
+            .insert(id, row);
+        overlay.writes.push((topic_schema.clone(), write));
+        Ok(())
+    }
+}
+
+impl TopicDataStore for OverlayTopicDataStore {
+    /// id is generated when not given
+    fn insert(&self, topic_schema: &Arc<TopicSchema>, mut data: TopicData) -> StdR<TopicData> {
+        let id = match Self::id_of(&data)? {
+            Some(id) => id,
+            _ => {
+                let id = IdGen::next_id()?.to_string();
+                data.insert(
+                    TopicDataColumnNames::Id.column_name().to_string(),
+                    TopicDataValue::Str(id.clone()),
+                );
+                id
+            }
+        };
+        self.record(
+            topic_schema,
+            id,
+            Some(data.clone()),
+            TopicDataWrite::Insert(data.clone()),
+        )?;
+        Ok(data)
+    }
+
+    fn insert_or_merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(Option<TopicData>, TopicData)> {
+        let previous = match Self::id_of(&data)? {
+            Some(id) => self.find_previous(topic_schema, &id)?,
+            _ => None,
+        };
+        match previous {
+            Some(_) => self
+                .merge(topic_schema, data)
+                .map(|(previous, current)| (Some(previous), current)),
+            _ => self
+                .insert(topic_schema, data)
+                .map(|current| (None, current)),
+        }
+    }
+
+    fn merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(TopicData, TopicData)> {
+        let id = Self::id_required(&data)?;
+        let previous = self.find_previous_to_merge(topic_schema, &id)?;
+        self.record(
+            topic_schema,
+            id,
+            Some(data.clone()),
+            TopicDataWrite::Merge(previous.clone(), data.clone()),
+        )?;
+        Ok((previous, data))
+    }
+
+    /// version is increased when merged
+    fn merge_versioned(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        mut data: TopicData,
+    ) -> StdR<Option<(TopicData, TopicData)>> {
+        let id = Self::id_required(&data)?;
+        let previous = self.find_previous_to_merge(topic_schema, &id)?;
+        let version = Self::version_of(&previous)?;
+        if version != Self::version_of(&data)? {
+            return Ok(None);
+        }
+
+        data.insert(
+            TopicDataColumnNames::Version.column_name().to_string(),
+            TopicDataValue::Num(BigDecimal::from(version + 1)),
+        );
+        self.record(
+            topic_schema,
+            id,
+            Some(data.clone()),
+            TopicDataWrite::MergeVersioned(previous.clone(), data.clone(), version),
+        )?;
+        Ok(Some((previous, data)))
+    }
+
+    fn delete(&self, topic_schema: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+        let id = Self::id_required(&data)?;
+        let previous = self.find_previous(topic_schema, &id)?.unwrap_or(data);
+        self.record(
+            topic_schema,
+            id,
+            None,
+            TopicDataWrite::Delete(previous.clone()),
+        )?;
+        Ok(previous)
+    }
+
+    fn find_by_id(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: &TopicDataId,
+    ) -> StdR<Option<TopicData>> {
+        self.find_previous(topic_schema, id)
+    }
+
+    fn find(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicData>> {
+        let stored = self.base.find(topic_schema, criteria)?;
+
+        let overlay = self.read()?;
+        let changed = match overlay.rows.get(topic_schema.topic_id()) {
+            Some(changed) => changed,
+            _ => return Ok(stored),
+        };
+        let mut rows = vec![];
+        for row in stored {
+            match Self::id_of(&row)? {
+                // replaced by overlay
+                Some(id) if changed.contains_key(&id) => {}
+                _ => rows.push(row),
+            }
+        }
+        for row in changed.values().flatten() {
+            if Self::matches(row, criteria)? {
+                rows.push(row.clone());
+            }
+        }
+        Ok(rows)
+    }
+
+    fn find_one(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Option<TopicData>> {
+        let mut rows = self.find(topic_schema, criteria)?;
+        if rows.len() > 1 {
+            PipelineKernelErrorCode::TopicDataMatchedMoreThanOne.msg(format!(
+                "Topic data matched more than one[count={}] by criteria[{:?}] of topic[{}].",
+                rows.len(),
+                criteria,
+                topic_schema.topic_id()
+            ))
+        } else {
+            Ok(rows.pop())
+        }
+    }
+
+    fn find_values(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        factor: &Arc<ArcFactor>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicDataValue>> {
+        Ok(self
+            .find(topic_schema, criteria)?
+            .iter()
+            .map(|row| {
+                Self::value_of(row, factor)
+                    .cloned()
+                    .unwrap_or(TopicDataValue::None)
+            })
+            .collect())
+    }
+
+    fn count(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<u64> {
+        Ok(self.find(topic_schema, criteria)?.len() as u64)
+    }
+
+    fn exists(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<bool> {
+        Ok(!self.find(topic_schema, criteria)?.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OverlayTopicDataStore, TopicDataStore};
+    use bigdecimal::BigDecimal;
+    use elf_base::StdR;
+    use elf_model::{
+        Factor, FactorType, ParameterExpressionOperator, Topic, TopicData, TopicDataId,
+        TopicDataValue, TopicKind, TopicType,
+    };
+    use elf_runtime_model_kernel::{
+        ArcFactor, TopicDataCriteria, TopicDataCriteriaExpression, TopicDataCriteriaOperand,
+        TopicSchema,
+    };
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// base store has one row only, id is "1"
+    struct OneRowTopicDataStore;
+
+    impl OneRowTopicDataStore {
+        fn row() -> TopicData {
+            HashMap::from([
+                (String::from("id_"), TopicDataValue::Str(String::from("1"))),
+                (String::from("name"), TopicDataValue::Str(String::from("a"))),
+            ])
+        }
+    }
+
+    impl TopicDataStore for OneRowTopicDataStore {
+        fn insert(&self, _: &Arc<TopicSchema>, _: TopicData) -> StdR<TopicData> {
+            panic!("base store cannot be written")
+        }
+        fn insert_or_merge(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: TopicData,
+        ) -> StdR<(Option<TopicData>, TopicData)> {
+            panic!("base store cannot be written")
+        }
+        fn merge(&self, _: &Arc<TopicSchema>, _: TopicData) -> StdR<(TopicData, TopicData)> {
+            panic!("base store cannot be written")
+        }
+        fn merge_versioned(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: TopicData,
+        ) -> StdR<Option<(TopicData, TopicData)>> {
+            panic!("base store cannot be written")
+        }
+        fn delete(&self, _: &Arc<TopicSchema>, _: TopicData) -> StdR<TopicData> {
+            panic!("base store cannot be written")
+        }
+        fn find_by_id(&self, _: &Arc<TopicSchema>, id: &TopicDataId) -> StdR<Option<TopicData>> {
+            Ok(Some(Self::row()).filter(|_| id == "1"))
+        }
+        fn find(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Vec<TopicData>> {
+            Ok(vec![Self::row()])
+        }
+        fn find_one(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<Option<TopicData>> {
+            Ok(Some(Self::row()))
+        }
+        fn find_values(
+            &self,
+            _: &Arc<TopicSchema>,
+            _: &Arc<ArcFactor>,
+            _: &TopicDataCriteria,
+        ) -> StdR<Vec<TopicDataValue>> {
+            Ok(vec![TopicDataValue::Str(String::from("a"))])
+        }
+        fn count(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<u64> {
+            Ok(1)
+        }
+        fn exists(&self, _: &Arc<TopicSchema>, _: &TopicDataCriteria) -> StdR<bool> {
+            Ok(true)
+        }
+    }
+
+    fn create_topic_schema() -> Arc<TopicSchema> {
+        let topic = Topic::new()
+            .topic_id(String::from("topic-1"))
+            .name(String::from("topic_1"))
+            .r#type(TopicType::Distinct)
+            .kind(TopicKind::Business)
+            .factors(vec![
+                Factor::new()
+                    .factor_id(String::from("f1"))
+                    .name(String::from("name"))
+                    .r#type(FactorType::Text),
+            ])
+            .tenant_id(String::from("Tenant-1"))
+            .version(1);
+        Arc::new(TopicSchema::new(topic).expect("failed to create topic schema"))
+    }
+
+    fn create_row(id: &str, name: &str) -> TopicData {
+        HashMap::from([
+            (String::from("id_"), TopicDataValue::Str(id.to_string())),
+            (String::from("name"), TopicDataValue::Str(name.to_string())),
+        ])
+    }
+
+    fn name_equals(name: &str) -> TopicDataCriteria {
+        let factor = ArcFactor::new(
+            Factor::new()
+                .factor_id(String::from("f1"))
+                .name(String::from("name"))
+                .r#type(FactorType::Text),
+        )
+        .expect("failed to create factor");
+        TopicDataCriteria::Expression(TopicDataCriteriaExpression {
+            left: TopicDataCriteriaOperand::Factor(factor),
+            operator: Arc::new(ParameterExpressionOperator::Equals),
+            right: Some(TopicDataCriteriaOperand::Value(TopicDataValue::Str(
+                name.to_string(),
+            ))),
+        })
+    }
+
+    #[test]
+    fn test_overlay() {
+        let topic_schema = create_topic_schema();
+        let store = OverlayTopicDataStore::new(Arc::new(OneRowTopicDataStore));
+
+        store.insert(&topic_schema, create_row("2", "b")).unwrap();
+        let (previous, _) = store
+            .insert_or_merge(&topic_schema, create_row("1", "c"))
+            .unwrap();
+        assert!(matches!(
+            previous.unwrap().get("name"),
+            Some(TopicDataValue::Str(name)) if name == "a"
+        ));
+        // row of base store is replaced by the merged one
+        assert_eq!(store.count(&topic_schema, &name_equals("a")).unwrap(), 0);
+        assert!(store.exists(&topic_schema, &name_equals("c")).unwrap());
+        assert!(
+            store
+                .find_one(&topic_schema, &name_equals("b"))
+                .unwrap()
+                .is_some()
+        );
+
+        store.delete(&topic_schema, create_row("2", "b")).unwrap();
+        assert!(!store.exists(&topic_schema, &name_equals("b")).unwrap());
+        assert!(
+            store
+                .find_by_id(&topic_schema, &String::from("2"))
+                .unwrap()
+                .is_none()
+        );
+
+        let changes = store.take_changes().unwrap();
+        let changes = changes.get(topic_schema.topic_id()).unwrap();
+        assert_eq!(changes.inserted.len(), 1);
+        assert_eq!(changes.merged.len(), 1);
+        assert_eq!(changes.deleted.len(), 1);
+        assert!(store.take_writes().unwrap().is_empty());
+        assert!(store.exists(&topic_schema, &name_equals("a")).unwrap());
+    }
+
+    #[test]
+    fn test_merge_versioned() {
+        let topic_schema = create_topic_schema();
+        let store = OverlayTopicDataStore::new(Arc::new(OneRowTopicDataStore));
+
+        // row of base store has no version, which is 0
+        let mut row = create_row("1", "b");
+        row.insert(
+            String::from("version_"),
+            TopicDataValue::Num(BigDecimal::from(1)),
+        );
+        assert!(store.merge_versioned(&topic_schema, row).unwrap().is_none());
+
+        let (_, current) = store
+            .merge_versioned(&topic_schema, create_row("1", "b"))
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            current.get("version_"),
+            Some(TopicDataValue::Num(version)) if *version == BigDecimal::from(1)
+        ));
+        // version 0 is stale now
+        assert!(
+            store
+                .merge_versioned(&topic_schema, create_row("1", "c"))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use elf_base::{StdR, VoidR};
+use elf_model::{TopicData, TopicDataId, TopicDataValue};
+use elf_runtime_model_kernel::{ArcFactor, TopicDataCriteria, TopicDataService, TopicSchema};
+use std::sync::Arc;
+
+/// topic data accessed by pipeline execution.
+/// [TopicDataService] is the one persists data into storage,
+/// and [OverlayTopicDataStore] keeps changes in memory.
+pub trait TopicDataStore: Send + Sync {
+    fn insert(&self, topic_schema: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData>;
+    fn insert_or_merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(Option<TopicData>, TopicData)>;
+    /// insert rows in one round, returns inserted rows in the same order.
+    /// rows are inserted one by one by default.
+    fn insert_batch(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: Vec<TopicData>,
+    ) -> StdR<Vec<TopicData>> {
+        data.into_iter()
+            .map(|data| self.insert(topic_schema, data))
+            .collect()
+    }
+    /// insert or merge rows in one round, returns previous and current of each row in the same order.
+    /// rows are inserted or merged one by one by default.
+    fn insert_or_merge_batch(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: Vec<TopicData>,
+    ) -> StdR<Vec<(Option<TopicData>, TopicData)>> {
+        data.into_iter()
+            .map(|data| self.insert_or_merge(topic_schema, data))
+            .collect()
+    }
+    fn merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(TopicData, TopicData)>;
+    /// merge with optimistic lock, returns none when version conflicted
+    fn merge_versioned(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<Option<(TopicData, TopicData)>>;
+    fn delete(&self, topic_schema: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData>;
+    fn find_by_id(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: &TopicDataId,
+    ) -> StdR<Option<TopicData>>;
+    fn find(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicData>>;
+    fn find_one(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Option<TopicData>>;
+    fn find_values(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        factor: &Arc<ArcFactor>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicDataValue>>;
+    fn count(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<u64>;
+    fn exists(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<bool>;
+    /// begin a transaction, writes through the transaction are applied only when committed.
+    /// returns none when store does not support transaction, which is the default.
+    fn begin(&self) -> StdR<Option<Box<dyn TopicDataTransaction>>> {
+        Ok(None)
+    }
+}
+
+/// transaction of store, reads through it see the uncommitted writes.
+pub trait TopicDataTransaction: TopicDataStore {
+    fn commit(self: Box<Self>) -> VoidR;
+    fn rollback(self: Box<Self>) -> VoidR;
+}
+
+impl TopicDataStore for TopicDataService {
+    fn insert(&self, topic_schema: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+        TopicDataService::insert(self, topic_schema, data)
+    }
+
+    fn insert_or_merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(Option<TopicData>, TopicData)> {
+        TopicDataService::insert_or_merge(self, topic_schema, data)
+    }
+
+    fn insert_batch(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: Vec<TopicData>,
+    ) -> StdR<Vec<TopicData>> {
+        TopicDataService::insert_batch(self, topic_schema, data)
+    }
+
+    fn insert_or_merge_batch(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: Vec<TopicData>,
+    ) -> StdR<Vec<(Option<TopicData>, TopicData)>> {
+        TopicDataService::insert_or_merge_batch(self, topic_schema, data)
+    }
+
+    fn merge(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<(TopicData, TopicData)> {
+        TopicDataService::merge(self, topic_schema, data)
+    }
+
+    fn merge_versioned(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        data: TopicData,
+    ) -> StdR<Option<(TopicData, TopicData)>> {
+        TopicDataService::merge_versioned(self, topic_schema, data)
+    }
+
+    fn delete(&self, topic_schema: &Arc<TopicSchema>, data: TopicData) -> StdR<TopicData> {
+        TopicDataService::delete(self, topic_schema, data)
+    }
+
+    fn find_by_id(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        id: &TopicDataId,
+    ) -> StdR<Option<TopicData>> {
+        TopicDataService::find_by_id(self, topic_schema, id)
+    }
+
+    fn find(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicData>> {
+        TopicDataService::find(self, topic_schema, criteria)
+    }
+
+    fn find_one(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Option<TopicData>> {
+        TopicDataService::find_one(self, topic_schema, criteria)
+    }
+
+    fn find_values(
+        &self,
+        topic_schema: &Arc<TopicSchema>,
+        factor: &Arc<ArcFactor>,
+        criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicDataValue>> {
+        TopicDataService::find_values(self, topic_schema, factor, criteria)
+    }
+
+    fn count(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<u64> {
+        TopicDataService::count(self, topic_schema, criteria)
+    }
+
+    fn exists(&self, topic_schema: &Arc<TopicSchema>, criteria: &TopicDataCriteria) -> StdR<bool> {
+        TopicDataService::exists(self, topic_schema, criteria)
+    }
+}
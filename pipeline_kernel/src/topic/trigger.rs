@@ -14,7 +14,7 @@ pub struct TopicTrigger {
 }
 
 impl TopicTrigger {
-    fn get_data_id(data: &TopicData) -> StdR<Arc<TopicDataId>> {
+    pub fn get_data_id(data: &TopicData) -> StdR<Arc<TopicDataId>> {
         let data_id = data.get(TopicDataColumnNames::Id.column_name());
         match data_id {
             Some(data_id) => match data_id {
@@ -53,6 +53,12 @@ pub enum RuntimeModelKernelErrorCode {
 
     EncryptNotSupport,
     AesCrypto,
+
+    ExternalWriterTypeMissed,
+    ExternalWriterUrlMissed,
+    ExternalWriterAdapterNotFound,
+    ExternalWriteFailed,
+    ExternalWriterNotFound,
 }
 
 impl ErrorCode for RuntimeModelKernelErrorCode {
@@ -108,6 +114,12 @@ impl ErrorCode for RuntimeModelKernelErrorCode {
 
             Self::EncryptNotSupport => "RTMK-00800",
             Self::AesCrypto => "RTMK-00801",
+
+            Self::ExternalWriterTypeMissed => "RTMK-00900",
+            Self::ExternalWriterUrlMissed => "RTMK-00901",
+            Self::ExternalWriterAdapterNotFound => "RTMK-00902",
+            Self::ExternalWriteFailed => "RTMK-00903",
+            Self::ExternalWriterNotFound => "RTMK-00904",
         }
     }
 }
@@ -0,0 +1,72 @@
+use crate::{
+	ExternalWriteFuture, ExternalWriteParams, ExternalWriterAdapter, ExternalWriterAdapterHelper,
+};
+use elf_base::VoidR;
+use elf_model::ExternalWriter;
+use reqwest::Client;
+use serde_json::json;
+
+/// index params as a document by bulk api of elastic search,
+/// event code is used as index name, pat of writer is sent as api key.
+#[derive(Default)]
+pub struct ElasticSearchExternalWriter {
+    client: Client,
+}
+
+impl ExternalWriterAdapterHelper for ElasticSearchExternalWriter {}
+
+impl ElasticSearchExternalWriter {
+    pub fn new() -> Self {
+        ElasticSearchExternalWriter {
+            client: Client::new(),
+        }
+    }
+
+    /// bulk body is ndjson, action line and document line, ends with a new line
+    fn bulk_body(params: &ExternalWriteParams) -> Result<String, String> {
+        let action = json!({"index": {"_index": params.event_code}});
+        let document = serde_json::to_string(params).map_err(|e| e.to_string())?;
+        Ok(format!("{}\n{}\n", action, document))
+    }
+
+    async fn bulk(&self, writer: &ExternalWriter, params: &ExternalWriteParams) -> VoidR {
+        let url = format!("{}/_bulk", Self::url(writer)?.trim_end_matches('/'));
+        let body = match Self::bulk_body(params) {
+            Ok(body) => body,
+            Err(e) => return Self::failed(writer, e),
+        };
+
+        let mut request = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/x-ndjson")
+            .body(body);
+        if let Some(pat) = &writer.pat {
+            request = request.header("Authorization", format!("ApiKey {}", pat));
+        }
+
+        let response = match request.send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => return Self::failed(writer, format!("status[{}]", response.status())),
+            Err(e) => return Self::failed(writer, e.to_string()),
+        };
+        // bulk api responds 200 even when item failed, check errors flag
+        match response.json::<serde_json::Value>().await {
+            Ok(result) if result["errors"].as_bool() == Some(true) => {
+                Self::failed(writer, format!("bulk errors[{}]", result["items"]))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Self::failed(writer, e.to_string()),
+        }
+    }
+}
+
+impl ExternalWriterAdapter for ElasticSearchExternalWriter {
+    fn write<'a>(
+        &'a self,
+        writer: &'a ExternalWriter,
+        params: &'a ExternalWriteParams,
+    ) -> ExternalWriteFuture<'a> {
+        Box::pin(self.bulk(writer, params))
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{ElasticSearchExternalWriter, RuntimeModelKernelErrorCode, StandardExternalWriter};
+use elf_base::{ErrorCode, StdErrCode, StdR, VoidR};
+use elf_model::{ExternalWriter, ExternalWriterType, TopicData};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// the envelope written to external
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalWriteParams {
+    pub event_code: String,
+    pub trigger_type: String,
+    pub previous: Option<TopicData>,
+    pub current: Option<TopicData>,
+    pub variables: TopicData,
+}
+
+pub type ExternalWriteFuture<'a> = Pin<Box<dyn Future<Output = VoidR> + Send + 'a>>;
+
+pub trait ExternalWriterAdapter: Send + Sync {
+    fn write<'a>(
+        &'a self,
+        writer: &'a ExternalWriter,
+        params: &'a ExternalWriteParams,
+    ) -> ExternalWriteFuture<'a>;
+}
+
+/// key is name of [ExternalWriterType]
+static EXTERNAL_WRITER_ADAPTERS: OnceLock<RwLock<HashMap<String, Arc<dyn ExternalWriterAdapter>>>> =
+    OnceLock::new();
+
+/// adapters of external writer types,
+/// standard writer and elastic search writer are registered by default,
+/// [register] a new adapter to replace the default one.
+pub struct ExternalWriterAdapters;
+
+impl ExternalWriterAdapters {
+    fn init() -> RwLock<HashMap<String, Arc<dyn ExternalWriterAdapter>>> {
+        let mut adapters: HashMap<String, Arc<dyn ExternalWriterAdapter>> = HashMap::new();
+        adapters.insert(
+            ExternalWriterType::StandardWriter.to_string(),
+            Arc::new(StandardExternalWriter::new()),
+        );
+        adapters.insert(
+            ExternalWriterType::ElasticSearchWriter.to_string(),
+            Arc::new(ElasticSearchExternalWriter::new()),
+        );
+        RwLock::new(adapters)
+    }
+
+    pub fn get(&self, writer_type: &ExternalWriterType) -> StdR<Arc<dyn ExternalWriterAdapter>> {
+        let guard = EXTERNAL_WRITER_ADAPTERS
+            .get_or_init(ExternalWriterAdapters::init)
+            .read()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get read lock, caused by {}.", e))
+            })?;
+
+        match guard.get(&writer_type.to_string()) {
+            Some(adapter) => Ok(adapter.clone()),
+            _ => RuntimeModelKernelErrorCode::ExternalWriterAdapterNotFound.msg(format!(
+                "External writer adapter not found for type[{}].",
+                writer_type
+            )),
+        }
+    }
+
+    pub fn register(
+        &self,
+        writer_type: &ExternalWriterType,
+        adapter: Arc<dyn ExternalWriterAdapter>,
+    ) -> VoidR {
+        let mut guard = EXTERNAL_WRITER_ADAPTERS
+            .get_or_init(ExternalWriterAdapters::init)
+            .write()
+            .map_err(|e| {
+                StdErrCode::RwLock
+                    .err_with_msg(format!("Failed to get write lock, caused by {}.", e))
+            })?;
+        guard.insert(writer_type.to_string(), adapter);
+
+        Ok(())
+    }
+
+    /// find adapter by type of given writer, and write
+    pub async fn write(&self, writer: &ExternalWriter, params: &ExternalWriteParams) -> VoidR {
+        let adapter = match &writer.r#type {
+            Some(writer_type) => self.get(writer_type)?,
+            _ => {
+                return RuntimeModelKernelErrorCode::ExternalWriterTypeMissed.msg(format!(
+                    "External writer[{}] type missed.",
+                    writer.writer_id.as_deref().unwrap_or("")
+                ));
+            }
+        };
+        adapter.write(writer, params).await
+    }
+}
+
+pub trait ExternalWriterAdapterProvider {
+    fn adapters() -> StdR<Arc<ExternalWriterAdapters>> {
+        Ok(Arc::new(ExternalWriterAdapters {}))
+    }
+}
+
+/// shared by adapters
+pub trait ExternalWriterAdapterHelper {
+    fn url(writer: &ExternalWriter) -> StdR<&String> {
+        match &writer.url {
+            Some(url) if !url.trim().is_empty() => Ok(url),
+            _ => RuntimeModelKernelErrorCode::ExternalWriterUrlMissed.msg(format!(
+                "External writer[{}] url missed.",
+                writer.writer_id.as_deref().unwrap_or("")
+            )),
+        }
+    }
+
+    fn failed<R>(writer: &ExternalWriter, reason: String) -> StdR<R> {
+        RuntimeModelKernelErrorCode::ExternalWriteFailed.msg(format!(
+            "Failed to write to external writer[{}], caused by {}.",
+            writer.writer_id.as_deref().unwrap_or(""),
+            reason
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExternalWriteParams, ExternalWriterAdapterProvider, ExternalWriterService};
+    use elf_model::{ExternalWriter, ExternalWriterType, TopicDataValue};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// accept one request, respond given body, and send back the received request (head, body)
+    fn stub_server(response_body: &'static str) -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let content_length = head
+                .lines()
+                .find_map(|line| {
+                    line.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response_body.len(),
+                response_body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            sender
+                .send((head, String::from_utf8(body).unwrap()))
+                .unwrap();
+        });
+        (url, receiver)
+    }
+
+    fn create_params() -> ExternalWriteParams {
+        ExternalWriteParams {
+            event_code: "order-created".to_string(),
+            trigger_type: "insert".to_string(),
+            previous: None,
+            current: Some(HashMap::from([(
+                "name".to_string(),
+                TopicDataValue::Str("x".to_string()),
+            )])),
+            variables: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_standard_writer() {
+        let (url, receiver) = stub_server("{}");
+        let writer = ExternalWriter::new()
+            .writer_id("writer-1".to_string())
+            .r#type(ExternalWriterType::StandardWriter)
+            .pat("token-1".to_string())
+            .url(url);
+
+        ExternalWriterService::adapters()
+            .unwrap()
+            .write(&writer, &create_params())
+            .await
+            .expect("failed to write to standard writer");
+
+        let (head, body) = receiver.recv().unwrap();
+        assert!(head.starts_with("POST / "));
+        assert!(
+            head.to_ascii_lowercase()
+                .contains("authorization: pat token-1")
+        );
+        assert!(body.contains("\"eventCode\":\"order-created\""));
+        assert!(body.contains("\"current\":{\"name\":\"x\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_elastic_search_writer() {
+        let (url, receiver) = stub_server("{\"errors\":false,\"items\":[]}");
+        let writer = ExternalWriter::new()
+            .writer_id("writer-2".to_string())
+            .r#type(ExternalWriterType::ElasticSearchWriter)
+            .url(url);
+
+        ExternalWriterService::adapters()
+            .unwrap()
+            .write(&writer, &create_params())
+            .await
+            .expect("failed to write to elastic search writer");
+
+        let (head, body) = receiver.recv().unwrap();
+        assert!(head.starts_with("POST /_bulk "));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "{\"index\":{\"_index\":\"order-created\"}}");
+        assert!(lines[1].contains("\"triggerType\":\"insert\""));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::RuntimeModelKernelErrorCode;
+use elf_base::{ErrorCode, StdR};
+use elf_model::{ExternalWriter, ExternalWriterId, TenantId};
+use std::sync::Arc;
+
+/// TODO external writer meta service using tenant and it's meta datasource (or the global meta datasource)
+///  to find out external writer meta.
+pub struct ExternalWriterMetaService;
+
+impl ExternalWriterMetaService {
+    fn new() -> StdR<Arc<Self>> {
+        // TODO maybe find from cache
+        Ok(Arc::new(Self {}))
+    }
+
+    /// TODO no meta datasource yet, always returns not found error
+    pub fn find_by_id(
+        &self,
+        writer_id: &ExternalWriterId,
+        tenant_id: &TenantId,
+    ) -> StdR<ExternalWriter> {
+        RuntimeModelKernelErrorCode::ExternalWriterNotFound.msg(format!(
+            "External writer[id={}, tenant_id={}] not found.",
+            writer_id, tenant_id
+        ))
+    }
+}
+
+pub trait ExternalWriterMetaProvider {
+    fn meta() -> StdR<Arc<ExternalWriterMetaService>> {
+        ExternalWriterMetaService::new()
+    }
+}
@@ -0,0 +1,6 @@
+use crate::{ExternalWriterAdapterProvider, ExternalWriterMetaProvider};
+
+pub struct ExternalWriterService;
+
+impl ExternalWriterAdapterProvider for ExternalWriterService {}
+impl ExternalWriterMetaProvider for ExternalWriterService {}
@@ -0,0 +1,11 @@
+mod elastic_search_external_writer;
+mod external_writer_adapter;
+mod external_writer_meta_service;
+mod external_writer_service;
+mod standard_external_writer;
+
+pub use elastic_search_external_writer::*;
+pub use external_writer_adapter::*;
+pub use external_writer_meta_service::*;
+pub use external_writer_service::*;
+pub use standard_external_writer::*;
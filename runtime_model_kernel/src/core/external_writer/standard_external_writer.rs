@@ -0,0 +1,45 @@
+use crate::{
+	ExternalWriteFuture, ExternalWriteParams, ExternalWriterAdapter, ExternalWriterAdapterHelper,
+};
+use elf_base::VoidR;
+use elf_model::ExternalWriter;
+use reqwest::Client;
+
+/// post params as json to url of writer, pat of writer is sent as authorization header
+#[derive(Default)]
+pub struct StandardExternalWriter {
+    client: Client,
+}
+
+impl ExternalWriterAdapterHelper for StandardExternalWriter {}
+
+impl StandardExternalWriter {
+    pub fn new() -> Self {
+        StandardExternalWriter {
+            client: Client::new(),
+        }
+    }
+
+    async fn post(&self, writer: &ExternalWriter, params: &ExternalWriteParams) -> VoidR {
+        let mut request = self.client.post(Self::url(writer)?).json(params);
+        if let Some(pat) = &writer.pat {
+            request = request.header("Authorization", format!("pat {}", pat));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Self::failed(writer, format!("status[{}]", response.status())),
+            Err(e) => Self::failed(writer, e.to_string()),
+        }
+    }
+}
+
+impl ExternalWriterAdapter for StandardExternalWriter {
+    fn write<'a>(
+        &'a self,
+        writer: &'a ExternalWriter,
+        params: &'a ExternalWriteParams,
+    ) -> ExternalWriteFuture<'a> {
+        Box::pin(self.post(writer, params))
+    }
+}
@@ -1,5 +1,6 @@
 mod common;
 mod encrypt;
+mod external_writer;
 mod meta;
 mod pipeline;
 mod schema;
@@ -8,6 +9,7 @@ mod topic;
 
 pub use common::*;
 pub use encrypt::*;
+pub use external_writer::*;
 pub use meta::*;
 pub use pipeline::*;
 pub use schema::*;
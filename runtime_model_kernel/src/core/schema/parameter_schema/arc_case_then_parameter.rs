@@ -55,7 +55,7 @@ impl ArcCaseThenParameter {
 
         let mut default_route_count = 0;
         for route in &values {
-            if route.conditional.unwrap_or(false) {
+            if !route.conditional.unwrap_or(false) {
                 default_route_count += 1;
             }
         }
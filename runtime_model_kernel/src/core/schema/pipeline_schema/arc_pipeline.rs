@@ -1,7 +1,11 @@
-use crate::{ArcHelper, ArcParameterJoint, ArcPipelineStage, RuntimeModelKernelErrorCode};
+use crate::{
+	ArcHelper, ArcParameterJoint, ArcPipelineFailurePolicy, ArcPipelineStage,
+	RuntimeModelKernelErrorCode,
+};
 use elf_base::{ErrorCode, StdR};
 use elf_model::{Pipeline, PipelineId, PipelineTriggerType, TenantId, TopicId};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct ArcPipeline {
@@ -12,6 +16,9 @@ pub struct ArcPipeline {
     pub stages: Arc<Vec<Arc<ArcPipelineStage>>>,
     pub enabled: bool,
     pub on: Option<Arc<ArcParameterJoint>>,
+    pub failure_policy: Option<Arc<ArcPipelineFailurePolicy>>,
+    /// no limit when none or 0
+    pub timeout: Option<Duration>,
     pub tenant_id: Arc<TenantId>,
     pub version: u32,
 }
@@ -52,6 +59,11 @@ impl ArcPipeline {
             stages: arc_stages,
             enabled: pipeline.enabled.unwrap_or(true),
             on,
+            failure_policy: pipeline.failure_policy.map(ArcPipelineFailurePolicy::new),
+            timeout: pipeline
+                .timeout_in_mills
+                .filter(|timeout| *timeout > 0)
+                .map(|timeout| Duration::from_millis(timeout as u64)),
             tenant_id,
             version: pipeline.version.unwrap_or(0),
         }))
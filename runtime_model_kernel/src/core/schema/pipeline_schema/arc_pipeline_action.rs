@@ -5,7 +5,7 @@ use crate::{
 	ArcWriteFactorAction, ArcWriteToExternalAction,
 };
 use elf_base::StdR;
-use elf_model::PipelineAction;
+use elf_model::{PipelineAction, PipelineActionId};
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -73,4 +73,23 @@ impl ArcPipelineAction {
 
         Ok(Arc::new(arc_action))
     }
+
+    pub fn action_id(&self) -> &Arc<PipelineActionId> {
+        match self {
+            Self::Alarm(action) => &action.action_id,
+            Self::CopyToMemory(action) => &action.action_id,
+            Self::WriteToExternal(action) => &action.action_id,
+            Self::ReadRow(action) => &action.action_id,
+            Self::ReadFactor(action) => &action.action_id,
+            Self::Exists(action) => &action.action_id,
+            Self::ReadRows(action) => &action.action_id,
+            Self::ReadFactors(action) => &action.action_id,
+            Self::MergeRow(action) => &action.action_id,
+            Self::InsertRow(action) => &action.action_id,
+            Self::InsertOrMergeRow(action) => &action.action_id,
+            Self::WriteFactor(action) => &action.action_id,
+            Self::DeleteRow(action) => &action.action_id,
+            Self::DeleteRows(action) => &action.action_id,
+        }
+    }
 }
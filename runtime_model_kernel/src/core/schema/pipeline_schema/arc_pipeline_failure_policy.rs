@@ -0,0 +1,28 @@
+use crate::ArcHelper;
+use elf_model::PipelineFailurePolicy;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct ArcPipelineFailurePolicy {
+    pub retries: u32,
+    pub backoff_in_mills: u32,
+    pub dead_letter: bool,
+}
+
+impl ArcHelper for ArcPipelineFailurePolicy {}
+
+impl ArcPipelineFailurePolicy {
+    pub fn new(policy: PipelineFailurePolicy) -> Arc<Self> {
+        Arc::new(Self {
+            retries: policy.retries.unwrap_or(0),
+            backoff_in_mills: policy.backoff_in_mills.unwrap_or(0),
+            dead_letter: policy.dead_letter.unwrap_or(false),
+        })
+    }
+
+    /// wait before the given retry, starts from 1, doubled for each next retry
+    pub fn backoff_of(&self, retry: u32) -> u64 {
+        let exponent = retry.saturating_sub(1).min(16);
+        (self.backoff_in_mills as u64).saturating_mul(1u64 << exponent)
+    }
+}
@@ -9,6 +9,7 @@ mod arc_mapping_factor;
 mod arc_merge_row_action;
 mod arc_pipeline;
 mod arc_pipeline_action;
+mod arc_pipeline_failure_policy;
 mod arc_pipeline_stage;
 mod arc_pipeline_unit;
 mod arc_read_factor_action;
@@ -30,6 +31,7 @@ pub use arc_mapping_factor::*;
 pub use arc_merge_row_action::*;
 pub use arc_pipeline::*;
 pub use arc_pipeline_action::*;
+pub use arc_pipeline_failure_policy::*;
 pub use arc_pipeline_stage::*;
 pub use arc_pipeline_unit::*;
 pub use arc_read_factor_action::*;
@@ -1,7 +1,8 @@
-use crate::ArcPipeline;
+use crate::{ArcPipeline, ArcPipelineFailurePolicy};
 use elf_base::StdR;
-use elf_model::{Pipeline, PipelineTriggerType, TenantId};
+use elf_model::{Pipeline, PipelineId, PipelineTriggerType, TenantId};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct PipelineSchema {
     inner: Arc<ArcPipeline>,
@@ -18,6 +19,10 @@ impl PipelineSchema {
         &self.inner
     }
 
+    pub fn pipeline_id(&self) -> &Arc<PipelineId> {
+        &self.pipeline().pipeline_id
+    }
+
     pub fn name(&self) -> &Arc<String> {
         &self.pipeline().name
     }
@@ -29,4 +34,16 @@ impl PipelineSchema {
     pub fn tenant_id(&self) -> &Arc<TenantId> {
         &self.pipeline().tenant_id
     }
+
+    pub fn failure_policy(&self) -> &Option<Arc<ArcPipelineFailurePolicy>> {
+        &self.pipeline().failure_policy
+    }
+
+    pub fn timeout(&self) -> &Option<Duration> {
+        &self.pipeline().timeout
+    }
+
+    pub fn version(&self) -> u32 {
+        self.pipeline().version
+    }
 }
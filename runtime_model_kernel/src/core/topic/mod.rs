@@ -1,8 +1,12 @@
+mod topic_data_criteria;
+mod topic_data_scan;
 mod topic_data_service;
 mod topic_meta_service;
 mod topic_schema_service;
 mod topic_service;
 
+pub use topic_data_criteria::*;
+pub use topic_data_scan::*;
 pub use topic_data_service::*;
 pub use topic_meta_service::*;
 pub use topic_schema_service::*;
@@ -0,0 +1,33 @@
+use crate::ArcFactor;
+use elf_model::{ParameterExpressionOperator, ParameterJointType, TopicDataValue};
+use std::sync::Arc;
+
+/// operand of criteria expression,
+/// - factor: refers to the factor of topic which is queried,
+/// - value: the value already computed in memory.
+#[derive(Debug)]
+pub enum TopicDataCriteriaOperand {
+    Factor(Arc<ArcFactor>),
+    Value(TopicDataValue),
+}
+
+/// right is none when operator is one of empty and not-empty
+#[derive(Debug)]
+pub struct TopicDataCriteriaExpression {
+    pub left: TopicDataCriteriaOperand,
+    pub operator: Arc<ParameterExpressionOperator>,
+    pub right: Option<TopicDataCriteriaOperand>,
+}
+
+#[derive(Debug)]
+pub struct TopicDataCriteriaJoint {
+    pub joint_type: Arc<ParameterJointType>,
+    pub conditions: Vec<TopicDataCriteria>,
+}
+
+/// criteria to find topic data from storage
+#[derive(Debug)]
+pub enum TopicDataCriteria {
+    Expression(TopicDataCriteriaExpression),
+    Joint(TopicDataCriteriaJoint),
+}
@@ -0,0 +1,19 @@
+use crate::TopicDataCriteria;
+use chrono::NaiveDateTime;
+use elf_model::{TopicDataColumnNames, TopicDataId};
+
+/// scan rows page by page, in ascending order of id.
+/// the next page starts after the id of last row of current page.
+pub struct TopicDataScan {
+    pub criteria: Option<TopicDataCriteria>,
+    /// audit time column to filter rows, insert time or update time
+    pub time_column: Option<TopicDataColumnNames>,
+    /// inclusive, ignored when time column not given
+    pub start_time: Option<NaiveDateTime>,
+    /// inclusive, ignored when time column not given
+    pub end_time: Option<NaiveDateTime>,
+    /// rows after the given id, from the first row when none
+    pub after_id: Option<TopicDataId>,
+    /// max count of rows of one page
+    pub limit: u32,
+}
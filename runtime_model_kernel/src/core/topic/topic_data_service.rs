@@ -1,6 +1,6 @@
-use crate::{TopicMetaProvider, TopicSchema};
+use crate::{ArcFactor, TopicDataCriteria, TopicDataScan, TopicMetaProvider, TopicSchema};
 use elf_base::StdR;
-use elf_model::TopicData;
+use elf_model::{TopicData, TopicDataId, TopicDataValue};
 use std::sync::Arc;
 
 pub struct TopicDataService;
@@ -25,6 +25,24 @@ impl TopicDataService {
         todo!("implement insert_or_merge for TopicDataService")
     }
 
+    /// insert rows in one round, returns inserted rows in the same order
+    pub fn insert_batch(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _data: Vec<TopicData>,
+    ) -> StdR<Vec<TopicData>> {
+        todo!("implement insert_batch for TopicDataService")
+    }
+
+    /// insert or merge rows in one round, returns previous and current of each row in the same order
+    pub fn insert_or_merge_batch(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _data: Vec<TopicData>,
+    ) -> StdR<Vec<(Option<TopicData>, TopicData)>> {
+        todo!("implement insert_or_merge_batch for TopicDataService")
+    }
+
     pub fn merge(
         &self,
         _topic_schema: &Arc<TopicSchema>,
@@ -33,9 +51,84 @@ impl TopicDataService {
         todo!("implement merge for TopicDataService")
     }
 
+    /// merge only when version of stored row is same as the version of given data,
+    /// and the version is increased. returns none when version conflicted.
+    pub fn merge_versioned(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _data: TopicData,
+    ) -> StdR<Option<(TopicData, TopicData)>> {
+        todo!("implement merge_versioned for TopicDataService")
+    }
+
     pub fn delete(&self, _topic_schema: &Arc<TopicSchema>, _data: TopicData) -> StdR<TopicData> {
         todo!("implement delete for TopicDataService")
     }
+
+    /// find row by its id
+    pub fn find_by_id(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _id: &TopicDataId,
+    ) -> StdR<Option<TopicData>> {
+        todo!("implement find_by_id for TopicDataService")
+    }
+
+    /// find all rows which match the given criteria
+    pub fn find(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicData>> {
+        todo!("implement find for TopicDataService")
+    }
+
+    /// find the only row which matches the given criteria,
+    /// raise error when more than one row matched
+    pub fn find_one(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _criteria: &TopicDataCriteria,
+    ) -> StdR<Option<TopicData>> {
+        todo!("implement find_one for TopicDataService")
+    }
+
+    /// find one page of rows by given scan, in ascending order of id
+    pub fn scan(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _scan: &TopicDataScan,
+    ) -> StdR<Vec<TopicData>> {
+        todo!("implement scan for TopicDataService")
+    }
+
+    /// find values of given factor, from all rows which match the given criteria
+    pub fn find_values(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _factor: &Arc<ArcFactor>,
+        _criteria: &TopicDataCriteria,
+    ) -> StdR<Vec<TopicDataValue>> {
+        todo!("implement find_values for TopicDataService")
+    }
+
+    /// count rows which match the given criteria
+    pub fn count(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _criteria: &TopicDataCriteria,
+    ) -> StdR<u64> {
+        todo!("implement count for TopicDataService")
+    }
+
+    /// check there is any row matches the given criteria
+    pub fn exists(
+        &self,
+        _topic_schema: &Arc<TopicSchema>,
+        _criteria: &TopicDataCriteria,
+    ) -> StdR<bool> {
+        todo!("implement exists for TopicDataService")
+    }
 }
 
 pub trait TopicDataProvider {